@@ -1,4 +1,9 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{OutputFormat, ProcessOptions};
+use crate::fetch::DEFAULT_FETCH_TIMEOUT;
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -24,6 +29,9 @@ pub struct Cli {
     #[clap(default_value = "*.kamut.yaml")]
     pub pattern: String,
 
+    #[clap(flatten)]
+    pub generate_args: GenerateArgs,
+
     /// Optional subcommand
     #[clap(subcommand)]
     pub command: Option<Commands>,
@@ -36,11 +44,251 @@ pub enum Commands {
         /// File pattern to search for
         #[clap(default_value = "*.kamut.yaml")]
         pattern: String,
+
+        /// Keep running and regenerate the matching files whenever they change
+        #[clap(long, conflicts_with = "diff_against_cluster")]
+        watch: bool,
+
+        /// Shell command to run after a changed file is regenerated in watch mode, once
+        /// per written file with `{output}` replaced by its path
+        #[clap(
+            long,
+            visible_alias = "watch-exec",
+            value_name = "COMMAND",
+            requires = "watch",
+            conflicts_with_all = ["stdout", "dry_run"]
+        )]
+        exec: Option<String>,
+
+        // Boxed to keep the enum small now that the options outgrew the other variants
+        #[clap(flatten)]
+        generate_args: Box<GenerateArgs>,
+    },
+    /// List all resources the matching files would create, without writing output
+    List {
+        /// File pattern to search for
+        #[clap(default_value = "*.kamut.yaml")]
+        pattern: String,
+    },
+    /// Check kamut files for errors without writing output
+    Validate {
+        /// File pattern to search for
+        #[clap(default_value = "*.kamut.yaml")]
+        pattern: String,
+
+        /// Stop after this many problems instead of reporting all of them
+        #[clap(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_errors: Option<u32>,
+    },
+    /// Rewrite generated manifests in canonical form without re-rendering their sources;
+    /// files without the kamut provenance header are skipped
+    Fmt {
+        /// Generated files to format
+        #[clap(default_value = "*.yaml")]
+        pattern: String,
+
+        /// Omit fields that equal their Kubernetes API defaults
+        #[clap(long)]
+        trim: bool,
+
+        /// Emit top-level keys in kubectl's order (apiVersion, kind, metadata, spec)
+        #[clap(long)]
+        kubectl_order: bool,
+
+        /// Indentation width of nested mappings
+        #[clap(long, value_name = "WIDTH", value_parser = clap::value_parser!(u8).range(1..=8))]
+        indent: Option<u8>,
+
+        /// Indent block sequences under their parent key
+        #[clap(long)]
+        indent_sequences: bool,
+    },
+    /// Write the rendered manifests into a static Helm chart
+    ExportHelm {
+        /// Directory of the generated chart
+        dir: PathBuf,
+
+        /// File pattern to search for
+        #[clap(default_value = "*.kamut.yaml")]
+        pattern: String,
+
+        /// Chart name (default: the chart directory name)
+        #[clap(long)]
+        chart_name: Option<String>,
+
+        /// Chart version
+        #[clap(long, default_value = crate::helm::DEFAULT_CHART_VERSION)]
+        chart_version: String,
     },
     /// Display the version information
     Version,
 }
 
+/// Options controlling manifest generation
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct GenerateArgs {
+    /// Only render documents whose name matches
+    #[clap(long, value_name = "NAME")]
+    pub render_only: Option<String>,
+
+    /// Omit all kamut-injected labels and annotations
+    #[clap(long)]
+    pub no_managed_labels: bool,
+
+    /// Omit the default `app: <name>` label from resource metadata
+    #[clap(long)]
+    pub no_app_label: bool,
+
+    /// Treat warnings as errors
+    #[clap(long)]
+    pub strict: bool,
+
+    /// Warn when a workload references a ConfigMap or Secret by a name that looks like a
+    /// typo of one generated in the same run
+    #[clap(long)]
+    pub check_references: bool,
+
+    /// Omit fields that equal their Kubernetes API defaults
+    #[clap(long)]
+    pub trim: bool,
+
+    /// Emit top-level keys in kubectl's order (apiVersion, kind, metadata, spec)
+    #[clap(long)]
+    pub kubectl_order: bool,
+
+    /// Registry prepended to images that don't name a registry host
+    #[clap(long, value_name = "PREFIX")]
+    pub image_registry: Option<String>,
+
+    /// Namespace of documents that set none (cluster-scoped resources stay namespace-less);
+    /// documents and `# kamut: namespace=` directives win
+    #[clap(long, value_name = "NAMESPACE")]
+    pub default_namespace: Option<String>,
+
+    /// Namespace of every namespaced resource, replacing the namespace documents and
+    /// `# kamut: namespace=` directives set
+    #[clap(long, value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// YAML file of fields used for document fields left unset (default: the
+    /// `kamut.defaults.yaml` next to each kamut file)
+    #[clap(long, value_name = "FILE")]
+    pub defaults: Option<PathBuf>,
+
+    /// Print manifests to stdout instead of writing `.yaml` files
+    #[clap(long)]
+    pub stdout: bool,
+
+    /// Seconds allowed for fetching an http:// URL pattern before failing
+    #[clap(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_FETCH_TIMEOUT.as_secs(),
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub fetch_timeout: u64,
+
+    /// Write generated files to this directory (created if needed)
+    #[clap(long, value_name = "PATH")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Generate manifests and report the files that would be written without writing them
+    #[clap(long, conflicts_with = "stdout")]
+    pub dry_run: bool,
+
+    /// Write a kustomization.yaml listing the generated files into each output directory
+    #[clap(long, conflicts_with = "stdout")]
+    pub kustomize: bool,
+
+    /// Serialization format of the written manifests; JSON files hold an array of resources
+    #[clap(long, value_enum, default_value_t = OutputFormat::Yaml)]
+    pub format: OutputFormat,
+
+    /// Write each generated resource to its own file
+    #[clap(long)]
+    pub split_documents: bool,
+
+    /// File name template for --split-documents, with {name}, {kind}, {namespace},
+    /// and {base} placeholders
+    #[clap(long, value_name = "TEMPLATE", requires = "split_documents")]
+    pub name_template: Option<String>,
+
+    /// Validate generated manifests against the Kubernetes JSON schemas in this directory
+    /// (kubeconform layout; requires the `schema` feature)
+    #[clap(long, value_name = "SCHEMA_DIR")]
+    pub validate_schema: Option<PathBuf>,
+
+    /// Label resources with a hash of their rendered content (kamut.dev/content-hash)
+    #[clap(long)]
+    pub content_hash: bool,
+
+    /// Annotate resources with an ArgoCD sync wave derived from their kind
+    #[clap(long)]
+    pub sync_waves: bool,
+
+    /// Indentation width of nested mappings in the output
+    #[clap(long, value_name = "WIDTH", value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub indent: Option<u8>,
+
+    /// Indent block sequences under their parent key
+    #[clap(long)]
+    pub indent_sequences: bool,
+
+    /// Print a diff against the live cluster objects instead of writing files
+    /// (requires the `kube` feature)
+    #[clap(long)]
+    pub diff_against_cluster: bool,
+
+    /// Exit with an error when the pattern matches no files
+    #[clap(long)]
+    pub require_match: bool,
+
+    /// Variable available to `when` expressions (repeatable)
+    #[clap(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+}
+
+impl GenerateArgs {
+    /// Converts the command-line options into options for `process_file_with_options`
+    pub fn process_options(&self) -> ProcessOptions {
+        ProcessOptions {
+            render_only: self.render_only.clone(),
+            no_managed_labels: self.no_managed_labels,
+            no_app_label: self.no_app_label,
+            strict: self.strict,
+            check_references: self.check_references,
+            trim: self.trim,
+            kubectl_order: self.kubectl_order,
+            image_registry: self.image_registry.clone(),
+            default_namespace: self.default_namespace.clone(),
+            namespace_override: self.namespace.clone(),
+            defaults_file: self.defaults.clone(),
+            stdout: self.stdout,
+            output_dir: self.output_dir.clone(),
+            dry_run: self.dry_run,
+            kustomize: self.kustomize,
+            vars: self.vars.iter().cloned().collect(),
+            require_match: self.require_match,
+            split_documents: self.split_documents,
+            name_template: self.name_template.clone(),
+            schema_dir: self.validate_schema.clone(),
+            content_hash: self.content_hash,
+            sync_waves: self.sync_waves,
+            indent: self.indent.map(usize::from),
+            indent_sequences: self.indent_sequences,
+            format: self.format,
+            fetch_timeout: Some(Duration::from_secs(self.fetch_timeout)),
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    var.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid variable '{}': expected KEY=VALUE", var))
+}
+
 pub fn parse_args() -> Args {
     Args::parse()
 }
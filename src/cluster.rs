@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+
+/// Kinds kamut can generate that are not namespaced
+pub const CLUSTER_SCOPED_KINDS: &[&str] = &["ClusterRole", "ClusterRoleBinding", "PriorityClass"];
+
+/// The API coordinates of a generated resource, used to fetch its live counterpart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceRef {
+    /// API group (empty for the core group)
+    pub group: String,
+    pub version: String,
+    pub kind: String,
+    /// Lowercase plural resource name used in API paths (e.g. `deployments`)
+    pub plural: String,
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+impl ResourceRef {
+    /// Returns `group/version`, or just `version` for the core group
+    pub fn api_version(&self) -> String {
+        if self.group.is_empty() {
+            self.version.clone()
+        } else {
+            format!("{}/{}", self.group, self.version)
+        }
+    }
+}
+
+/// Maps a generated manifest to the group, version, kind, and name to GET from the cluster
+pub fn resource_ref(manifest: &str) -> Result<ResourceRef> {
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+    let field = |path: &[&str]| {
+        path.iter()
+            .try_fold(&value, |value, key| value.get(*key))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+    };
+
+    let api_version = field(&["apiVersion"])
+        .ok_or_else(|| anyhow::anyhow!("Generated manifest has no apiVersion"))?;
+    let kind = field(&["kind"]).ok_or_else(|| anyhow::anyhow!("Generated manifest has no kind"))?;
+    let name = field(&["metadata", "name"])
+        .ok_or_else(|| anyhow::anyhow!("Generated manifest has no metadata.name"))?;
+
+    let (group, version) = match api_version.split_once('/') {
+        Some((group, version)) => (group.to_string(), version.to_string()),
+        None => (String::new(), api_version),
+    };
+
+    Ok(ResourceRef {
+        group,
+        version,
+        plural: plural_resource_name(&kind),
+        kind,
+        namespace: field(&["metadata", "namespace"]),
+        name,
+    })
+}
+
+// Pluralizes a kind the way the API server names its resources
+fn plural_resource_name(kind: &str) -> String {
+    let kind = kind.to_lowercase();
+    if kind == "endpoints" {
+        kind
+    } else if kind.ends_with('s') {
+        format!("{}es", kind)
+    } else if let Some(stem) = kind.strip_suffix('y') {
+        format!("{}ies", stem)
+    } else {
+        format!("{}s", kind)
+    }
+}
+
+/// Keeps only the parts of a live object that the generated manifest declares,
+/// so server-populated fields (status, defaults, managedFields) don't show up as drift
+pub fn project_live_object(
+    live: &serde_yaml::Value,
+    generated: &serde_yaml::Value,
+) -> serde_yaml::Value {
+    match (live, generated) {
+        (serde_yaml::Value::Mapping(live), serde_yaml::Value::Mapping(generated)) => {
+            let mut projected = serde_yaml::Mapping::new();
+            for (key, generated_value) in generated {
+                if let Some(live_value) = live.get(key) {
+                    projected.insert(
+                        key.clone(),
+                        project_live_object(live_value, generated_value),
+                    );
+                }
+            }
+            serde_yaml::Value::Mapping(projected)
+        }
+        (serde_yaml::Value::Sequence(live), serde_yaml::Value::Sequence(generated)) => {
+            serde_yaml::Value::Sequence(
+                live.iter()
+                    .enumerate()
+                    .map(|(index, live_value)| match generated.get(index) {
+                        Some(generated_value) => project_live_object(live_value, generated_value),
+                        None => live_value.clone(),
+                    })
+                    .collect(),
+            )
+        }
+        (live, _) => live.clone(),
+    }
+}
+
+/// Line diff of two texts, prefixing removed lines with `-`, added lines with `+`,
+/// and unchanged lines with a space
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push_str(&format!(" {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Fetches the live object for every generated manifest and returns a report of
+/// new, unchanged, and drifted resources
+#[cfg(feature = "kube")]
+pub fn diff_against_cluster(manifests: &[String]) -> Result<String> {
+    use kube::api::{Api, ApiResource, DynamicObject};
+    use kube::core::GroupVersionKind;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")?;
+
+    runtime.block_on(async {
+        let client = kube::Client::try_default()
+            .await
+            .context("Failed to create a Kubernetes client from kubeconfig")?;
+
+        let mut report = String::new();
+        for manifest in manifests {
+            let resource = resource_ref(manifest)?;
+            let gvk = GroupVersionKind::gvk(&resource.group, &resource.version, &resource.kind);
+            let api_resource = ApiResource::from_gvk_with_plural(&gvk, &resource.plural);
+            let api: Api<DynamicObject> = match &resource.namespace {
+                _ if CLUSTER_SCOPED_KINDS.contains(&resource.kind.as_str()) => {
+                    Api::all_with(client.clone(), &api_resource)
+                }
+                Some(namespace) => Api::namespaced_with(client.clone(), namespace, &api_resource),
+                None => Api::default_namespaced_with(client.clone(), &api_resource),
+            };
+
+            let header = format!("{} {}", resource.kind, resource.name);
+            let live = api
+                .get_opt(&resource.name)
+                .await
+                .with_context(|| format!("Failed to fetch {} from the cluster", header))?;
+            let Some(live) = live else {
+                report.push_str(&format!("{}: will create\n", header));
+                continue;
+            };
+
+            let generated: serde_yaml::Value =
+                serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+            let live = serde_yaml::to_value(&live).context("Failed to serialize live object")?;
+            let live = serde_yaml::to_string(&project_live_object(&live, &generated))?;
+            let generated = serde_yaml::to_string(&generated)?;
+
+            if live == generated {
+                report.push_str(&format!("{}: unchanged\n", header));
+            } else {
+                report.push_str(&format!("{}: differs\n", header));
+                report.push_str(&diff_lines(&live, &generated));
+            }
+        }
+        Ok(report)
+    })
+}
+
+/// Without the `kube` feature there is no client to reach the cluster
+#[cfg(not(feature = "kube"))]
+pub fn diff_against_cluster(_manifests: &[String]) -> Result<String> {
+    Err(anyhow::anyhow!(
+        "--diff-against-cluster requires kamut to be built with the `kube` feature"
+    ))
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Prefix of the environment variables visible to `when` expressions
+pub const ENV_VAR_PREFIX: &str = "KAMUT_";
+
+/// Evaluates a `when` expression such as `env == prod && !canary`.
+///
+/// Terms compare a variable with a (optionally quoted) literal using `==` or `!=`,
+/// or test a boolean variable with `name` / `!name`; `&&` binds tighter than `||`.
+/// Variables come from `--var` first, then from `KAMUT_<NAME>` environment variables.
+pub fn evaluate(expression: &str, vars: &BTreeMap<String, String>) -> Result<bool> {
+    if expression.trim().is_empty() {
+        return Err(anyhow::anyhow!("when expression is empty"));
+    }
+
+    // `||` has the lowest precedence, so split on it first
+    for alternative in expression.split("||") {
+        let mut matched = true;
+        for term in alternative.split("&&") {
+            if !evaluate_term(term.trim(), vars)? {
+                matched = false;
+            }
+        }
+        if matched {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn evaluate_term(term: &str, vars: &BTreeMap<String, String>) -> Result<bool> {
+    if let Some((name, value)) = term.split_once("!=") {
+        return Ok(lookup(name.trim(), vars)? != unquote(value.trim()));
+    }
+    if let Some((name, value)) = term.split_once("==") {
+        return Ok(lookup(name.trim(), vars)? == unquote(value.trim()));
+    }
+
+    let (negated, name) = match term.strip_prefix('!') {
+        Some(name) => (true, name.trim()),
+        None => (false, term),
+    };
+    let value = match name {
+        "true" => true,
+        "false" => false,
+        name => match lookup(name, vars)?.as_str() {
+            "true" => true,
+            "false" => false,
+            value => {
+                return Err(anyhow::anyhow!(
+                    "variable '{}' is '{}', expected 'true' or 'false'",
+                    name,
+                    value
+                ))
+            }
+        },
+    };
+    Ok(value != negated)
+}
+
+fn lookup(name: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow::anyhow!("invalid variable name '{}'", name));
+    }
+    if let Some(value) = vars.get(name) {
+        return Ok(value.clone());
+    }
+    std::env::var(format!("{}{}", ENV_VAR_PREFIX, name.to_ascii_uppercase())).map_err(|_| {
+        anyhow::anyhow!(
+            "unknown variable '{}': pass --var {}=<value> or set {}{}",
+            name,
+            name,
+            ENV_VAR_PREFIX,
+            name.to_ascii_uppercase()
+        )
+    })
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
@@ -1,34 +1,188 @@
 use anyhow::{Context, Result};
 use glob::glob;
-use k8s_openapi::api::apps::v1::{Deployment, DeploymentSpec};
+use k8s_openapi::api::apps::v1::{
+    DaemonSet, DaemonSetSpec, Deployment, DeploymentSpec, DeploymentStrategy,
+    RollingUpdateDeployment, StatefulSet, StatefulSetSpec,
+};
+use k8s_openapi::api::autoscaling::v2::{
+    CrossVersionObjectReference, HorizontalPodAutoscaler, HorizontalPodAutoscalerSpec, MetricSpec,
+    MetricTarget, ResourceMetricSource,
+};
 use k8s_openapi::api::core::v1::{
-    Container, EnvVar, PodSpec, PodTemplateSpec, ResourceRequirements, Service, ServiceAccount,
-    ServicePort, ServiceSpec,
+    Capabilities, ConfigMap, ConfigMapEnvSource, ConfigMapKeySelector, ConfigMapVolumeSource,
+    Container, ContainerPort, ContainerResizePolicy, EmptyDirVolumeSource, EndpointAddress,
+    EndpointPort, EndpointSubset, Endpoints, EnvFromSource, EnvVar, EnvVarSource, ExecAction,
+    HTTPGetAction, Lifecycle, LifecycleHandler, LocalObjectReference, PersistentVolumeClaim,
+    PersistentVolumeClaimSpec, PersistentVolumeClaimVolumeSource, PodSecurityContext, PodSpec,
+    PodTemplateSpec, Probe, ResourceRequirements, SeccompProfile, Secret, SecretEnvSource,
+    SecretKeySelector, SecretVolumeSource, SecurityContext, Service, ServiceAccount, ServicePort,
+    ServiceSpec, Toleration, Volume, VolumeMount, VolumeResourceRequirements,
 };
 use k8s_openapi::api::networking::v1::{
-    HTTPIngressPath, HTTPIngressRuleValue, Ingress, IngressBackend, IngressRule,
-    IngressServiceBackend, IngressSpec, ServiceBackendPort,
+    HTTPIngressPath, HTTPIngressRuleValue, IPBlock, Ingress, IngressBackend, IngressRule,
+    IngressServiceBackend, IngressSpec, IngressTLS, NetworkPolicy, NetworkPolicyEgressRule,
+    NetworkPolicyIngressRule, NetworkPolicyPeer, NetworkPolicyPort, NetworkPolicySpec,
+    ServiceBackendPort,
 };
-use k8s_openapi::api::rbac::v1::{ClusterRole, ClusterRoleBinding, PolicyRule, RoleRef, Subject};
+use k8s_openapi::api::rbac::v1::{
+    ClusterRole, ClusterRoleBinding, PolicyRule, Role, RoleBinding, RoleRef, Subject,
+};
+use k8s_openapi::api::scheduling::v1::PriorityClass;
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use k8s_openapi::ByteString;
+use kube_custom_resources_rs::keda_sh::v1alpha1::scaledobjects::{
+    ScaledObject, ScaledObjectScaleTargetRef, ScaledObjectSpec, ScaledObjectTriggers,
+};
+use kube_custom_resources_rs::monitoring_coreos_com::v1::alertmanagers::{
+    Alertmanager, AlertmanagerResources, AlertmanagerSpec, AlertmanagerStorage,
+    AlertmanagerStorageVolumeClaimTemplate, AlertmanagerStorageVolumeClaimTemplateSpec,
+    AlertmanagerStorageVolumeClaimTemplateSpecResources,
+};
 use kube_custom_resources_rs::monitoring_coreos_com::v1::prometheuses::{
-    Prometheus, PrometheusResources, PrometheusSecurityContext, PrometheusSpec, PrometheusStorage,
-    PrometheusStorageVolumeClaimTemplate, PrometheusStorageVolumeClaimTemplateSpec,
-    PrometheusStorageVolumeClaimTemplateSpecResources, PrometheusTolerations,
+    Prometheus, PrometheusPodMonitorNamespaceSelector,
+    PrometheusPodMonitorNamespaceSelectorMatchExpressions, PrometheusRemoteWrite,
+    PrometheusResources, PrometheusScrapeClasses, PrometheusScrapeClassesTlsConfig,
+    PrometheusSecurityContext, PrometheusServiceMonitorNamespaceSelector,
+    PrometheusServiceMonitorNamespaceSelectorMatchExpressions, PrometheusServiceMonitorSelector,
+    PrometheusSpec, PrometheusStorage, PrometheusStorageVolumeClaimTemplate,
+    PrometheusStorageVolumeClaimTemplateSpec, PrometheusStorageVolumeClaimTemplateSpecResources,
+    PrometheusTolerations,
+};
+use kube_custom_resources_rs::monitoring_coreos_com::v1::servicemonitors::{
+    ServiceMonitor, ServiceMonitorEndpoints, ServiceMonitorSelector, ServiceMonitorSpec,
+};
+use kube_custom_resources_rs::monitoring_coreos_com::v1alpha1::alertmanagerconfigs::{
+    AlertmanagerConfig, AlertmanagerConfigReceivers, AlertmanagerConfigReceiversSlackConfigs,
+    AlertmanagerConfigReceiversSlackConfigsApiUrl, AlertmanagerConfigReceiversWebhookConfigs,
+    AlertmanagerConfigReceiversWebhookConfigsUrlSecret, AlertmanagerConfigRoute,
+    AlertmanagerConfigRouteMatchers, AlertmanagerConfigRouteMatchersMatchType,
+    AlertmanagerConfigSpec,
 };
 use kube_custom_resources_rs::monitoring_coreos_com::v1alpha1::scrapeconfigs::{
     ScrapeConfig, ScrapeConfigKubernetesSdConfigs, ScrapeConfigKubernetesSdConfigsRole,
     ScrapeConfigRelabelings, ScrapeConfigRelabelingsAction, ScrapeConfigSpec,
 };
-use std::collections::BTreeMap;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::cluster::CLUSTER_SCOPED_KINDS;
+use crate::condition;
+use crate::error::KamutError;
+use crate::fetch::{fetch_url, DEFAULT_FETCH_TIMEOUT};
+use crate::inventory::{summarize_manifest, ResourceEntry};
+use crate::models::{
+    ignored_fields, ContainerPortConfig, EnvConfig, EnvFromConfig, EnvFromRefConfig, EnvVarConfig,
+    IngressPathConfig, KamutConfig, KamutDefaults, LifecycleConfig, MonitorNamespace,
+    NetworkPolicyPeerConfig, NetworkPolicyPortConfig, PolicyRuleConfig, ProbeSpec, Resources,
+    SecurityContextConfig, ServiceConfig, StrategyConfig, TolerationConfig, VolumeConfig,
+    DEFAULTS_FILE_NAME,
+};
+use crate::registry;
+
+/// apiVersion of the ScrapeConfig CRD modeled by `kube_custom_resources_rs`.
+pub const SCRAPE_CONFIG_API_VERSION: &str = "monitoring.coreos.com/v1alpha1";
+
+/// ScrapeConfig apiVersions that can be selected with the `apiVersion` field.
+/// Only `v1alpha1` is available from `kube_custom_resources_rs`, so other
+/// operator versions (e.g. `monitoring.coreos.com/v1`) are rejected.
+pub const SUPPORTED_SCRAPE_CONFIG_API_VERSIONS: &[&str] = &[SCRAPE_CONFIG_API_VERSION];
+
+/// Serialization format of the written manifests (`--format`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// YAML documents joined by `---`
+    #[default]
+    Yaml,
+    /// A JSON array of resources (a single object per file with `--split-documents`)
+    Json,
+}
+
+impl OutputFormat {
+    /// Extension of the output files written in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Json => "json",
+        }
+    }
+}
+
+/// Metadata every generated resource starts from: `name_override` or the document's name,
+/// the document's namespace, and the `app: <name>` label. Generators add their own labels
+/// and annotations to it, and clear the namespace of cluster-scoped kinds.
+pub fn build_object_meta(config: &KamutConfig, name_override: Option<&str>) -> ObjectMeta {
+    let mut labels = BTreeMap::new();
+    labels.insert("app".to_string(), config.name.clone());
+    ObjectMeta {
+        name: Some(name_override.unwrap_or(&config.name).to_string()),
+        namespace: config.namespace.clone(),
+        labels: Some(labels),
+        ..Default::default()
+    }
+}
+
+/// Serializes a generated resource to YAML, dropping always-null fields such as
+/// `creationTimestamp: null` and `status: null` that only add noise to manifests
+pub fn serialize_manifest<T: Serialize>(resource: &T) -> Result<String> {
+    serialize_manifest_as(resource, OutputFormat::Yaml)
+}
+
+/// Serializes a generated resource in the given format, dropping the same always-null
+/// fields as `serialize_manifest`
+pub fn serialize_manifest_as<T: Serialize>(resource: &T, format: OutputFormat) -> Result<String> {
+    let mut value = serde_yaml::to_value(resource)?;
+    strip_null_fields(&mut value);
+    match format {
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(&value)?),
+        OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&value)?)),
+    }
+}
+
+/// Combines the YAML manifests rendered from one file into the contents of its output:
+/// documents joined by `---` for YAML, or a JSON array
+pub fn format_manifests(manifests: &[String], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Yaml => Ok(manifests.join("\n---\n")),
+        OutputFormat::Json => {
+            let resources = manifests
+                .iter()
+                .map(|manifest| serde_yaml::from_str::<serde_yaml::Value>(manifest))
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to parse generated manifest")?;
+            serialize_manifest_as(&resources, OutputFormat::Json)
+        }
+    }
+}
 
-use crate::models::KamutConfig;
+// Recursively removes null `creationTimestamp` and `status` entries
+fn strip_null_fields(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            mapping.retain(|key, value| {
+                !(value.is_null()
+                    && matches!(key.as_str(), Some("creationTimestamp") | Some("status")))
+            });
+            for (_, value) in mapping.iter_mut() {
+                strip_null_fields(value);
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) => {
+            for value in sequence.iter_mut() {
+                strip_null_fields(value);
+            }
+        }
+        _ => {}
+    }
+}
 
 pub fn find_config_files(pattern: &str) -> Result<Vec<std::path::PathBuf>> {
     let files: Vec<_> = glob(pattern)
@@ -39,9 +193,483 @@ pub fn find_config_files(pattern: &str) -> Result<Vec<std::path::PathBuf>> {
     Ok(files)
 }
 
-pub fn process_file(file_path: &Path) -> Result<()> {
-    println!("Processing file: {}", file_path.display());
+/// Prefix of labels and annotations owned by kamut
+pub const KAMUT_METADATA_PREFIX: &str = "kamut.dev/";
+
+/// Label holding a short hash of the rendered resource (`--content-hash`)
+pub const CONTENT_HASH_LABEL: &str = "kamut.dev/content-hash";
+
+/// Annotation ordering resources into ArgoCD sync waves (`--sync-waves`, `sync_wave`)
+pub const SYNC_WAVE_ANNOTATION: &str = "argocd.argoproj.io/sync-wave";
+
+/// Sync waves of `--sync-waves`, following Helm's install order: namespaces, CRDs, and
+/// cluster settings first, then RBAC, configuration, and Services ahead of the workloads,
+/// with autoscalers, Ingresses, and monitoring resources last. Unlisted kinds get wave 0.
+pub const DEFAULT_SYNC_WAVES: &[(&str, i32)] = &[
+    ("Namespace", -3),
+    ("CustomResourceDefinition", -3),
+    ("PriorityClass", -3),
+    ("ServiceAccount", -2),
+    ("ClusterRole", -2),
+    ("ClusterRoleBinding", -2),
+    ("Role", -2),
+    ("RoleBinding", -2),
+    ("ConfigMap", -1),
+    ("Secret", -1),
+    ("PersistentVolumeClaim", -1),
+    ("Service", -1),
+    ("Endpoints", -1),
+    ("NetworkPolicy", -1),
+    ("Deployment", 0),
+    ("StatefulSet", 0),
+    ("DaemonSet", 0),
+    ("Prometheus", 0),
+    ("Alertmanager", 0),
+    ("HorizontalPodAutoscaler", 1),
+    ("ScaledObject", 1),
+    ("Ingress", 1),
+    ("ServiceMonitor", 1),
+    ("ScrapeConfig", 1),
+    ("AlertmanagerConfig", 1),
+];
+
+/// Sync wave of a generated resource's kind under `--sync-waves`
+pub fn default_sync_wave(kind: &str) -> i32 {
+    DEFAULT_SYNC_WAVES
+        .iter()
+        .find(|(wave_kind, _)| *wave_kind == kind)
+        .map_or(0, |(_, wave)| *wave)
+}
+
+/// Annotates a generated manifest with an ArgoCD sync wave: `wave` when given, otherwise
+/// the default wave of the manifest's kind
+pub fn annotate_sync_wave(manifest: &str, wave: Option<i32>) -> Result<String> {
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+    let kind = value.get("kind").and_then(|kind| kind.as_str());
+    let wave = wave.unwrap_or_else(|| default_sync_wave(kind.unwrap_or_default()));
+
+    if let Some(serde_yaml::Value::Mapping(metadata)) = value.get_mut("metadata") {
+        let annotations = metadata
+            .entry("annotations".into())
+            .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+        if let serde_yaml::Value::Mapping(annotations) = annotations {
+            annotations.insert(SYNC_WAVE_ANNOTATION.into(), wave.to_string().into());
+        }
+    }
+
+    serialize_manifest(&value)
+}
+
+/// Label holding the index of a Deployment copy (`copies`)
+pub const COPY_INDEX_LABEL: &str = "kamut.dev/shard-index";
+
+/// Environment variable holding the index of a Deployment copy (`copies`)
+pub const COPY_INDEX_ENV: &str = "KAMUT_SHARD_INDEX";
+
+/// Recommended label marking the tool managing a resource
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+
+/// Start of the comment heading every generated YAML file; `kamut fmt` only rewrites
+/// files that begin with it
+pub const PROVENANCE_HEADER: &str = "# Generated by kamut";
+
+/// Header comment naming the kamut file a generated YAML file comes from
+pub fn provenance_header(file_path: &Path) -> String {
+    let source = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!("{} from {}; do not edit\n", PROVENANCE_HEADER, source)
+}
+
+/// Valid `pathType` values of an Ingress path
+pub const INGRESS_PATH_TYPES: &[&str] = &["Prefix", "Exact", "ImplementationSpecific"];
+
+/// Annotation selecting the cert-manager ClusterIssuer for an Ingress
+pub const CERT_MANAGER_ISSUER_ANNOTATION: &str = "cert-manager.io/cluster-issuer";
+
+/// Options controlling how `process_file_with_options` renders a file
+#[derive(Debug, Default, Clone)]
+pub struct ProcessOptions {
+    /// Suppress the informational output callers print from a `ProcessOutcome`
+    pub quiet: bool,
+    /// Only render documents whose `name` matches
+    pub render_only: Option<String>,
+    /// Omit all kamut-injected labels and annotations, keeping user-declared ones
+    pub no_managed_labels: bool,
+    /// Omit the default `app: <name>` label from resource metadata
+    pub no_app_label: bool,
+    /// Turn warnings (e.g. fields ignored by the document's kind) into errors
+    pub strict: bool,
+    /// Warn about workloads referencing a mistyped name of a ConfigMap or Secret
+    /// generated in the same run (`--check-references`)
+    pub check_references: bool,
+    /// Omit fields that equal their Kubernetes API defaults (e.g. `protocol: TCP`)
+    pub trim: bool,
+    /// Emit top-level keys in kubectl's order (apiVersion, kind, metadata, spec)
+    pub kubectl_order: bool,
+    /// Registry prepended to images that don't name a registry host
+    pub image_registry: Option<String>,
+    /// Namespace of documents without one and without a `# kamut: namespace=` directive
+    /// (`--default-namespace`, else `.kamutrc`)
+    pub default_namespace: Option<String>,
+    /// Namespace replacing the one of every document (`--namespace`)
+    pub namespace_override: Option<String>,
+    /// Defaults file filling in the fields documents leave unset (`--defaults`, else the
+    /// `kamut.defaults.yaml` next to the kamut file)
+    pub defaults_file: Option<PathBuf>,
+    /// Print manifests to stdout instead of writing `.yaml` files
+    pub stdout: bool,
+    /// Directory for the generated files instead of the source file's directory
+    pub output_dir: Option<PathBuf>,
+    /// Generate everything but only report the files that would be written
+    pub dry_run: bool,
+    /// Write a kustomization.yaml listing the generated files into each output directory
+    pub kustomize: bool,
+    /// Variables available to `when` expressions
+    pub vars: BTreeMap<String, String>,
+    /// Fail when the pattern matches no files instead of succeeding with a notice
+    pub require_match: bool,
+    /// Write each generated resource to its own file instead of one file per input
+    pub split_documents: bool,
+    /// File name template for `split_documents` (default: `DEFAULT_NAME_TEMPLATE`)
+    pub name_template: Option<String>,
+    /// Directory of Kubernetes JSON schemas to validate generated manifests against
+    pub schema_dir: Option<PathBuf>,
+    /// Label every resource with the first 8 hex digits of the SHA-256 of its content
+    pub content_hash: bool,
+    /// Annotate every resource with the ArgoCD sync wave of its kind (`DEFAULT_SYNC_WAVES`)
+    pub sync_waves: bool,
+    /// Indentation width of nested mappings (serde_yaml's 2 when unset)
+    pub indent: Option<usize>,
+    /// Indent block sequences under their parent key instead of aligning the dashes with it
+    pub indent_sequences: bool,
+    /// Serialization format of the written manifests
+    pub format: OutputFormat,
+    /// Time allowed for fetching a URL input (`DEFAULT_FETCH_TIMEOUT` when unset)
+    pub fetch_timeout: Option<Duration>,
+}
+
+/// File name template used by `--split-documents` when no `--name-template` is given
+pub const DEFAULT_NAME_TEMPLATE: &str = "{base}-{kind}-{name}.yaml";
+
+/// Expands the `{name}`, `{kind}` (lowercased), `{namespace}`, and `{base}` placeholders of an
+/// output file name template. Resources without a namespace use `default`, or `cluster`
+/// for cluster-scoped kinds.
+pub fn render_name_template(template: &str, resource: &ResourceEntry, base_name: &str) -> String {
+    let namespace = match &resource.namespace {
+        Some(namespace) => namespace.as_str(),
+        None if CLUSTER_SCOPED_KINDS.contains(&resource.kind.as_str()) => "cluster",
+        None => "default",
+    };
+    template
+        .replace("{name}", &resource.name)
+        .replace("{kind}", &resource.kind.to_lowercase())
+        .replace("{namespace}", namespace)
+        .replace("{base}", base_name)
+}
+
+/// First 8 hex digits of the SHA-256 of a rendered resource
+pub fn content_hash(manifest: &str) -> String {
+    Sha256::digest(manifest.as_bytes())
+        .iter()
+        .take(4)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Applies the output options to a generated manifest
+pub fn postprocess_manifest(manifest: &str, options: &ProcessOptions) -> Result<String> {
+    if !options.no_managed_labels
+        && !options.no_app_label
+        && !options.trim
+        && !options.kubectl_order
+        && !options.content_hash
+        && options.indent.is_none()
+        && !options.indent_sequences
+    {
+        return Ok(manifest.to_string());
+    }
+
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+
+    if let Some(metadata) = value.get_mut("metadata") {
+        if options.no_managed_labels {
+            strip_metadata_entries(metadata, "labels", |key, value| {
+                key.starts_with(KAMUT_METADATA_PREFIX)
+                    || (key == MANAGED_BY_LABEL && value.as_str() == Some("kamut"))
+            });
+            strip_metadata_entries(metadata, "annotations", |key, _| {
+                key.starts_with(KAMUT_METADATA_PREFIX)
+            });
+        }
+        if options.no_app_label {
+            // Only the resource's own labels; selectors and pod templates keep `app`
+            strip_metadata_entries(metadata, "labels", |key, _| key == "app");
+        }
+    }
+
+    if options.trim {
+        trim_default_fields(&mut value);
+    }
+
+    if options.kubectl_order {
+        value = order_top_level_keys(value);
+    }
+
+    // Hash the final content, so the label changes exactly when the resource does
+    if options.content_hash {
+        let hash = content_hash(&serialize_manifest(&value)?);
+        if let Some(serde_yaml::Value::Mapping(metadata)) = value.get_mut("metadata") {
+            let labels = metadata
+                .entry("labels".into())
+                .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+            if let serde_yaml::Value::Mapping(labels) = labels {
+                labels.insert(CONTENT_HASH_LABEL.into(), hash.into());
+            }
+        }
+    }
+
+    if options.indent.is_some() || options.indent_sequences {
+        let mut yaml = String::new();
+        emit_block_yaml(
+            &mut yaml,
+            &value,
+            0,
+            options.indent.unwrap_or(2),
+            options.indent_sequences,
+        )?;
+        return Ok(yaml);
+    }
+
+    serialize_manifest(&value)
+}
+
+// Writes a value in block style: every non-empty mapping and sequence goes on its own
+// lines, nested mappings are indented by `indent`, and sequences are indented under
+// their parent key only with `indent_sequences`. Empty collections stay `{}` / `[]`.
+fn emit_block_yaml(
+    out: &mut String,
+    value: &serde_yaml::Value,
+    pad: usize,
+    indent: usize,
+    indent_sequences: bool,
+) -> Result<()> {
+    match value {
+        serde_yaml::Value::Mapping(mapping) if !mapping.is_empty() => {
+            for (key, value) in mapping {
+                out.push_str(&" ".repeat(pad));
+                out.push_str(&emit_scalar(key, pad)?);
+                out.push(':');
+                match value {
+                    serde_yaml::Value::Mapping(nested) if !nested.is_empty() => {
+                        out.push('\n');
+                        emit_block_yaml(out, value, pad + indent, indent, indent_sequences)?;
+                    }
+                    serde_yaml::Value::Sequence(nested) if !nested.is_empty() => {
+                        out.push('\n');
+                        let pad = if indent_sequences { pad + indent } else { pad };
+                        emit_block_yaml(out, value, pad, indent, indent_sequences)?;
+                    }
+                    _ => {
+                        out.push(' ');
+                        out.push_str(&emit_scalar(value, pad + indent)?);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) if !sequence.is_empty() => {
+            for item in sequence {
+                // Render the item two columns in, then put the dash in front of its first line
+                let mut item_yaml = String::new();
+                emit_block_yaml(&mut item_yaml, item, pad + 2, indent, indent_sequences)?;
+                out.push_str(&" ".repeat(pad));
+                out.push_str("- ");
+                out.push_str(&item_yaml[pad + 2..]);
+            }
+        }
+        _ => {
+            out.push_str(&" ".repeat(pad));
+            out.push_str(&emit_scalar(value, pad)?);
+            out.push('\n');
+        }
+    }
+    Ok(())
+}
+
+// Serializes a scalar (or empty collection) with serde_yaml's quoting, re-indenting the
+// body of multi-line block scalars to `pad`
+fn emit_scalar(value: &serde_yaml::Value, pad: usize) -> Result<String> {
+    let yaml = serde_yaml::to_string(value).context("Failed to serialize YAML value")?;
+    let mut lines = yaml.trim_end_matches('\n').lines();
+    let mut scalar = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        scalar.push('\n');
+        if !line.is_empty() {
+            scalar.push_str(&" ".repeat(pad));
+            scalar.push_str(line.strip_prefix("  ").unwrap_or(line));
+        }
+    }
+    Ok(scalar)
+}
+
+// Top-level keys in the order kubectl prints them; other keys follow in their original order
+const KUBECTL_KEY_ORDER: &[&str] = &["apiVersion", "kind", "metadata", "spec"];
+
+// Reorders the top-level keys of a manifest to match KUBECTL_KEY_ORDER
+fn order_top_level_keys(value: serde_yaml::Value) -> serde_yaml::Value {
+    let serde_yaml::Value::Mapping(mut mapping) = value else {
+        return value;
+    };
+
+    let mut ordered = serde_yaml::Mapping::new();
+    for key in KUBECTL_KEY_ORDER {
+        if let Some(entry) = mapping.remove(*key) {
+            ordered.insert(serde_yaml::Value::from(*key), entry);
+        }
+    }
+    ordered.extend(mapping);
+
+    serde_yaml::Value::Mapping(ordered)
+}
+
+// Per-kind top-level fields omitted by --trim when they equal the Kubernetes API default
+// `pathType` is required on networking.k8s.io/v1 Ingress paths, so it is never trimmed
+const TRIMMED_DEFAULTS: &[(&str, &[&str], &str)] = &[
+    ("Service", &["spec", "type"], "ClusterIP"),
+    ("ServiceAccount", &["automountServiceAccountToken"], "true"),
+];
+
+// Removes fields that equal their Kubernetes API defaults
+fn trim_default_fields(value: &mut serde_yaml::Value) {
+    let kind = value
+        .get("kind")
+        .and_then(|kind| kind.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    for (default_kind, path, default) in TRIMMED_DEFAULTS {
+        if *default_kind != kind {
+            continue;
+        }
+        let (field, parents) = path
+            .split_last()
+            .expect("trimmed default path is never empty");
+        let mut target = Some(&mut *value);
+        for parent in parents {
+            target = target.and_then(|target| target.get_mut(*parent));
+        }
+        if let Some(serde_yaml::Value::Mapping(mapping)) = target {
+            let is_default = mapping.get(*field).is_some_and(|value| match value {
+                serde_yaml::Value::String(value) => value == default,
+                serde_yaml::Value::Bool(value) => value.to_string() == *default,
+                _ => false,
+            });
+            if is_default {
+                mapping.remove(*field);
+            }
+        }
+    }
+
+    trim_default_protocols(value);
+}
+
+// Removes `protocol: TCP` from every port list (Service and container ports)
+fn trim_default_protocols(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                if key.as_str() == Some("ports") {
+                    if let serde_yaml::Value::Sequence(ports) = value {
+                        for port in ports.iter_mut() {
+                            if let serde_yaml::Value::Mapping(port) = port {
+                                if port.get("protocol").and_then(|p| p.as_str()) == Some("TCP") {
+                                    port.remove("protocol");
+                                }
+                            }
+                        }
+                    }
+                }
+                trim_default_protocols(value);
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) => {
+            for value in sequence.iter_mut() {
+                trim_default_protocols(value);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Removes matching entries from metadata.labels/annotations, dropping the map if it ends up empty
+fn strip_metadata_entries(
+    metadata: &mut serde_yaml::Value,
+    field: &str,
+    should_strip: impl Fn(&str, &serde_yaml::Value) -> bool,
+) {
+    let Some(serde_yaml::Value::Mapping(entries)) = metadata.get_mut(field) else {
+        return;
+    };
+    entries.retain(|key, value| !key.as_str().is_some_and(|key| should_strip(key, value)));
+    if entries.is_empty() {
+        if let serde_yaml::Value::Mapping(metadata) = metadata {
+            metadata.remove(field);
+        }
+    }
+}
+
+/// Manifests rendered from a single kamut file
+#[derive(Debug, Default)]
+pub struct RenderedFile {
+    /// Number of non-empty documents in the file
+    pub document_count: usize,
+    /// Number of documents rendered after the `--render-only` filter
+    pub rendered_count: usize,
+    /// Generated manifests in document order
+    pub manifests: Vec<String>,
+    /// Warnings about documents that rendered with problems
+    pub warnings: Vec<String>,
+    /// Errors of documents that were skipped without failing the file, such as a
+    /// missing kind-specific field or an unsupported kind
+    pub errors: Vec<String>,
+}
+
+/// Writes an output file through a uniquely named temporary file in the same directory
+/// followed by a rename, so concurrent writers never interleave and readers never see
+/// a partially written file
+pub fn write_output_file(path: &Path, contents: &str) -> Result<()> {
+    static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid output path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    fs::write(&temp_path, contents)
+        .and_then(|()| fs::rename(&temp_path, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+        .with_context(|| format!("Failed to write to file: {}", path.display()))
+}
+
+/// Processes a single file with the default options, writing its output file
+pub fn process_file(file_path: &Path) -> Result<ProcessOutcome> {
+    process_file_with_options(file_path, &ProcessOptions::default())
+}
 
+/// Renders all documents of a file in memory without writing any output
+pub fn render_file(file_path: &Path, options: &ProcessOptions) -> Result<RenderedFile> {
     let mut file = File::open(file_path)
         .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
 
@@ -49,357 +677,2778 @@ pub fn process_file(file_path: &Path) -> Result<()> {
     file.read_to_string(&mut contents)
         .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
-    // Store the generated manifests
+    // The kamut.defaults.yaml next to the file applies unless --defaults names another
+    let defaults_file = options
+        .defaults_file
+        .clone()
+        .or_else(|| adjacent_defaults_file(file_path));
+    let options = ProcessOptions {
+        defaults_file,
+        ..options.clone()
+    };
+
+    render_str(&contents, file_path, &options)
+}
+
+// The kamut.defaults.yaml in the directory of a kamut file, if there is one
+fn adjacent_defaults_file(file_path: &Path) -> Option<PathBuf> {
+    let path = file_path.with_file_name(DEFAULTS_FILE_NAME);
+    path.is_file().then_some(path)
+}
+
+/// Reads a defaults file
+pub fn load_defaults(path: &Path) -> Result<KamutDefaults> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read defaults file: {}", path.display()))?;
+    KamutDefaults::from_yaml_str(&contents)
+        .with_context(|| format!("Invalid defaults file: {}", path.display()))
+}
+
+/// Renders the kamut documents of `contents` the same way as `render_file`; `file_path`
+/// only names their source in messages
+pub fn render_str(
+    contents: &str,
+    file_path: &Path,
+    options: &ProcessOptions,
+) -> Result<RenderedFile> {
+    // Store the generated manifests and the problems found while rendering them
     let mut manifests = Vec::new();
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
 
     // Handle multi-document YAML files by splitting on "---" separator
     let documents: Vec<&str> = contents.split("---").collect();
     let mut doc_count = 0;
+    let mut rendered_count = 0;
+
+    let defaults = options
+        .defaults_file
+        .as_deref()
+        .map(load_defaults)
+        .transpose()?;
+
+    // File-level defaults set by `# kamut:` directives
+    let mut default_namespace = options.default_namespace.clone();
 
     for doc in documents {
-        // Skip empty documents
-        if doc.trim().is_empty() {
+        // Apply `# kamut: namespace=<ns>` directives to this and all subsequent documents
+        if let Some(namespace) = parse_namespace_directive(doc)? {
+            default_namespace = Some(namespace);
+        }
+
+        // Skip empty documents, including documents that only hold comments
+        if doc
+            .lines()
+            .all(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        {
             continue;
         }
 
         doc_count += 1;
-        println!(
-            "\nProcessing document {} in {}",
-            doc_count,
-            file_path.display()
-        );
 
-        // Parse the YAML to KamutConfig
-        let config: KamutConfig = serde_yaml::from_str(doc).with_context(|| {
-            format!(
+        // Parse and validate the YAML to KamutConfig
+        let mut config = KamutConfig::from_yaml_str(doc).map_err(|err| match err {
+            KamutError::Parse(_) => anyhow::Error::new(err).context(format!(
                 "Failed to parse document {} in {}",
                 doc_count,
                 file_path.display()
-            )
-        })?;
-
-        // Check if kind is specified, return error if missing
-        let kind = config.kind.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "Error: 'kind' field is required in document {} of {}",
+            )),
+            err => anyhow::anyhow!(
+                "Error: {} in document {} of {}",
+                err,
                 doc_count,
                 file_path.display()
-            )
+            ),
         })?;
 
-        // Process configs based on what's present in the file
-        let mut processed = false;
+        // Inherit the file-level default namespace unless the document sets its own
+        if config.namespace.is_none() {
+            config.namespace = default_namespace.clone();
+        }
+        // Fill in the fields the document leaves unset from the defaults file
+        if let Some(defaults) = &defaults {
+            config = defaults.apply(config);
+        }
+        // --namespace replaces whatever namespace the document ended up with
+        if let Some(namespace) = &options.namespace_override {
+            config.namespace = Some(namespace.clone());
+        }
+
+        // Point images without a registry host at the --image-registry mirror
+        if let (Some(registry), Some(image)) = (&options.image_registry, &config.image) {
+            config.image = Some(apply_image_registry(image, registry));
+        }
+        if let Some(registry) = &options.image_registry {
+            for init_container in &mut config.init_containers {
+                init_container.image = apply_image_registry(&init_container.image, registry);
+            }
+            for container in &mut config.containers {
+                container.image = apply_image_registry(&container.image, registry);
+            }
+        }
+
+        // Skip documents whose `when` condition is false
+        if let Some(when) = &config.when {
+            let matched = condition::evaluate(when, &options.vars).with_context(|| {
+                format!(
+                    "Invalid when expression in document {} of {}",
+                    doc_count,
+                    file_path.display()
+                )
+            })?;
+            if !matched {
+                continue;
+            }
+        }
+
+        // Skip documents excluded by the --render-only filter
+        if let Some(render_only) = &options.render_only {
+            if &config.name != render_only {
+                continue;
+            }
+        }
+        rendered_count += 1;
+
+        // Kind presence is checked by KamutConfig::validate
+        let kind = config.kind.as_deref().unwrap_or_default();
+
+        // Warn about fields that have no effect for this kind
+        for field in ignored_fields(kind, doc) {
+            let message = format!(
+                "field '{}' is ignored for kind {} in document {} of {}",
+                field,
+                kind,
+                doc_count,
+                file_path.display()
+            );
+            if options.strict {
+                return Err(anyhow::Error::msg(message));
+            }
+            warnings.push(message);
+        }
 
-        // Process based on the specified kind
-        match kind.as_str() {
+        // Warnings that depend on the document's kind
+        match kind {
             "Deployment" => {
-                if config.image.is_some() {
-                    let manifest = generate_deployment_manifest(&config)?;
-                    manifests.push(manifest);
-                    processed = true;
-                } else {
-                    println!("\nError: Deployment requires an image to be specified");
+                if config.autoscaling.is_some() && config.replicas.is_some() {
+                    warnings.push(format!(
+                        "'replicas' is ignored for Deployment {} because 'autoscaling' is set; the HorizontalPodAutoscaler's min/max replicas apply",
+                        config.name
+                    ));
+                }
+                // A hostPort can be bound once per node, so it limits where replicas schedule
+                let host_ports = config
+                    .ports
+                    .iter()
+                    .flatten()
+                    .filter_map(|port| port.host_port);
+                for host_port in host_ports {
+                    let message = format!(
+                        "hostPort {} of Deployment {} allows one pod per node, so replicas beyond the nodes with the port free stay Pending; consider a DaemonSet, in document {} of {}",
+                        host_port,
+                        config.name,
+                        doc_count,
+                        file_path.display()
+                    );
+                    if options.strict {
+                        return Err(anyhow::Error::msg(message));
+                    }
+                    warnings.push(message);
                 }
             }
-            "Prometheus" => {
-                if config.image.is_some() {
-                    let manifest = generate_prometheus_manifest(&config)?;
-                    manifests.push(manifest);
-                    println!("Generated Prometheus for Prometheus");
-
-                    // Generate Service for Prometheus
-                    let service_manifest = generate_prometheus_service(&config)?;
-                    manifests.push(service_manifest);
-                    println!("Generated Service for Prometheus");
-
-                    // Generate Ingress if specified
-                    if let Some(ingress_config) = &config.ingress {
-                        let ingress_manifest =
-                            generate_prometheus_ingress(&config, ingress_config)?;
-                        manifests.push(ingress_manifest);
-                        println!("Generated Ingress for Prometheus");
+            "KubeScrapeConfig" if config.role.is_some() => {
+                for warning in lint_scrape_config(&build_scrape_config(&config)?) {
+                    let message = format!(
+                        "{} in document {} of {}",
+                        warning,
+                        doc_count,
+                        file_path.display()
+                    );
+                    if options.strict {
+                        return Err(anyhow::Error::msg(message));
                     }
+                    warnings.push(message);
+                }
+            }
+            _ => {}
+        }
 
-                    // Generate ServiceAccount, ClusterRole, and ClusterRoleBinding by default
-                    // If service_account is specified, use its configuration, otherwise use defaults
-                    let sa_manifests = generate_prometheus_service_account(&config)?;
-                    if !sa_manifests.is_empty() {
-                        manifests.extend(sa_manifests);
-                        println!("Generated ServiceAccount for Prometheus");
-                        println!("Generated ClusterRole and ClusterRoleBinding for Prometheus");
-                    }
+        // The document's primary resource is the first manifest it generates
+        let primary_index = manifests.len();
 
-                    processed = true;
-                } else {
-                    println!("\nError: Prometheus requires an image to be specified");
+        // Generate the document's resources with the generator registered for its kind
+        let processed = match registry::kind_generator(kind) {
+            Some(generator) => {
+                let generated = generator.generate(&config)?;
+                if generated.is_empty() {
+                    // Built-in generators skip documents lacking a required field
+                    match config.validate_kind() {
+                        Err(err) => errors.push(format!(
+                            "{} {}: {}, in document {} of {}",
+                            kind,
+                            config.name,
+                            err,
+                            doc_count,
+                            file_path.display()
+                        )),
+                        Ok(()) => warnings.push(format!(
+                            "{} {} generated no resources, in document {} of {}",
+                            kind,
+                            config.name,
+                            doc_count,
+                            file_path.display()
+                        )),
+                    }
                 }
+                manifests.extend(generated);
+                manifests.len() > primary_index
             }
-            "KubeScrapeConfig" => {
-                if let Some(_role) = &config.role {
-                    let manifest = generate_scrape_config_manifest(&config)?;
-                    manifests.push(manifest);
-                    println!("Generated ScrapeConfig");
-                    processed = true;
-                } else {
-                    println!("\nError: KubeScrapeConfig requires a role to be specified");
-                }
+            None => {
+                errors.push(format!(
+                    "unsupported kind {} in document {} of {}",
+                    kind,
+                    doc_count,
+                    file_path.display()
+                ));
+                false
             }
-            kind => {
-                println!("\nUnsupported kind: {}", kind);
+        };
+
+        // Order the document's resources into sync waves
+        if config.sync_wave.is_some() || options.sync_waves {
+            for manifest in &mut manifests[primary_index..] {
+                *manifest = annotate_sync_wave(manifest, config.sync_wave)?;
             }
         }
 
-        // If still not processed
-        if !processed {
-            println!(
-                "\nWarning: Could not determine resource type for document {}",
-                doc_count
-            );
+        // Rewrite the custom resource's apiVersion. The override is an explicit opt-in,
+        // so its warning is not escalated by --strict.
+        if let Some(api_version) = &config.api_version_override {
+            if let Some(manifest) = manifests.get_mut(primary_index).filter(|_| processed) {
+                let (original, overridden) = override_api_version(manifest, api_version)?;
+                *manifest = overridden;
+                warnings.push(format!(
+                    "apiVersion of {} {} overridden from {} to {}; kamut emits the fields of {}, so compatibility with {} is your responsibility",
+                    kind,
+                    config.name,
+                    original,
+                    api_version,
+                    original,
+                    api_version
+                ));
+            }
         }
     }
 
-    if doc_count == 0 {
-        println!("No valid YAML documents found in file");
-    } else if !manifests.is_empty() {
-        // Create output file name based on the input file name
-        if let Some(file_name) = file_path.file_name().and_then(|f| f.to_str()) {
-            // Extract the base name without the extension
-            let base_name = if let Some(dot_pos) = file_name.find(".kamut.") {
-                &file_name[0..dot_pos]
-            } else if let Some(dot_pos) = file_name.find('.') {
-                &file_name[0..dot_pos]
-            } else {
-                file_name // No extension, use the whole name
-            };
+    // Apply output options to every generated manifest
+    let manifests = manifests
+        .iter()
+        .map(|manifest| postprocess_manifest(manifest, options))
+        .collect::<Result<Vec<_>>>()?;
 
-            let base_name = if base_name.starts_with('.') {
-                &base_name[1..]
-            } else {
-                base_name
-            };
+    Ok(RenderedFile {
+        document_count: doc_count,
+        rendered_count,
+        manifests,
+        warnings,
+        errors,
+    })
+}
+
+/// Replaces the apiVersion of a generated manifest, keeping its position among the keys.
+/// Returns the original apiVersion and the rewritten manifest.
+pub fn override_api_version(manifest: &str, api_version: &str) -> Result<(String, String)> {
+    if api_version.split_once('/').is_none_or(|(group, version)| {
+        group.is_empty() || version.is_empty() || version.contains('/')
+    }) {
+        return Err(anyhow::anyhow!(
+            "Invalid api_version_override '{}': expected <group>/<version>, e.g. monitoring.coreos.com/v1",
+            api_version
+        ));
+    }
+
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+    let field = value
+        .get_mut("apiVersion")
+        .ok_or_else(|| anyhow::anyhow!("Generated manifest has no apiVersion"))?;
+    let original = field.as_str().unwrap_or_default().to_string();
+    *field = api_version.into();
+
+    Ok((original, serialize_manifest(&value)?))
+}
+
+/// Checks every document of a file without generating output, returning all problems found.
+/// Under `--strict`, fields ignored by a document's kind are problems too.
+pub fn validate_file(file_path: &Path, options: &ProcessOptions) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
-            // Create the output file name with .yaml extension
-            let output_file_name = format!("{}.yaml", base_name);
-            let output_path = file_path
-                .parent()
-                .unwrap_or(Path::new(""))
-                .join(output_file_name);
+    // Fields such as `image` may come from the kamut.defaults.yaml next to the file, or
+    // the defaults file named by --defaults
+    let defaults = options
+        .defaults_file
+        .clone()
+        .or_else(|| adjacent_defaults_file(file_path))
+        .map(|path| load_defaults(&path))
+        .transpose()?
+        .unwrap_or_default();
 
-            // Join all manifests with "---" separator
-            let combined_manifest = manifests.join("\n---\n");
+    let mut problems = Vec::new();
+    let mut doc_count = 0;
+    for doc in contents.split("---") {
+        // Skip empty documents, including documents that only hold comments
+        if doc
+            .lines()
+            .all(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        {
+            continue;
+        }
+        doc_count += 1;
 
-            // Write the manifest to the output file
-            fs::write(&output_path, &combined_manifest)
-                .with_context(|| format!("Failed to write to file: {}", output_path.display()))?;
+        let config = match KamutConfig::from_yaml_str(doc) {
+            Ok(config) => defaults.apply(config),
+            Err(err) => {
+                problems.push(format!(
+                    "{}: document {}: {}",
+                    file_path.display(),
+                    doc_count,
+                    err
+                ));
+                continue;
+            }
+        };
+        if let Err(err) = config.validate_kind() {
+            problems.push(format!(
+                "{}: document {}: {}",
+                file_path.display(),
+                doc_count,
+                err
+            ));
+            continue;
+        }
 
-            println!("\nSaved manifest to: {}", output_path.display());
+        if options.strict {
+            let kind = config.kind.as_deref().unwrap_or_default();
+            for field in ignored_fields(kind, doc) {
+                problems.push(format!(
+                    "{}: document {}: field '{}' is ignored for kind {}",
+                    file_path.display(),
+                    doc_count,
+                    field,
+                    kind
+                ));
+            }
         }
     }
 
-    Ok(())
+    Ok(problems)
 }
 
-pub fn generate_prometheus_ingress(
-    config: &KamutConfig,
-    ingress_config: &crate::models::Ingress,
-) -> Result<String> {
-    // Create metadata
-    let mut metadata = ObjectMeta::default();
-    metadata.name = Some(format!("{}-ingress", config.name));
-
-    // Set namespace if provided
-    if let Some(namespace) = &config.namespace {
-        metadata.namespace = Some(namespace.clone());
+/// Prepends `registry` to `image` unless the image already names a registry host,
+/// i.e. its first path component contains a `.` or `:` or is `localhost`
+pub fn apply_image_registry(image: &str, registry: &str) -> String {
+    let has_registry = image
+        .split_once('/')
+        .is_some_and(|(host, _)| host.contains('.') || host.contains(':') || host == "localhost");
+    if has_registry {
+        return image.to_string();
     }
+    format!("{}/{}", registry.trim_end_matches('/'), image)
+}
 
-    // Create labels
-    let mut labels = BTreeMap::new();
-    labels.insert("app".to_string(), config.name.clone());
-    metadata.labels = Some(labels);
-
-    // Create ingress rule
-    let ingress_rule = IngressRule {
-        host: Some(ingress_config.host.clone()),
-        http: Some(HTTPIngressRuleValue {
-            paths: vec![HTTPIngressPath {
-                path: Some("/".to_string()),
-                path_type: "Prefix".to_string(),
-                backend: IngressBackend {
-                    service: Some(IngressServiceBackend {
-                        name: format!("prometheus-{}", config.name),
-                        port: Some(ServiceBackendPort {
-                            number: Some(9090),
-                            name: None,
-                        }),
-                    }),
-                    resource: None,
-                },
-            }],
-        }),
-    };
+/// Pattern that reads a kamut file from stdin instead of matching files
+pub const STDIN_PATTERN: &str = "-";
 
-    // Create ingress spec
-    let ingress_spec = IngressSpec {
-        rules: Some(vec![ingress_rule]),
-        ..Default::default()
-    };
+/// Name of stdin in messages
+pub const STDIN_SOURCE: &str = "<stdin>";
 
-    // Create ingress
-    let ingress = Ingress {
-        metadata,
-        spec: Some(ingress_spec),
+/// Name of the input of `generate_from_str` in messages
+pub const STRING_SOURCE: &str = "<string>";
+
+/// A resource generated from a kamut file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GeneratedResource {
+    /// Kind of the resource, e.g. `Deployment`
+    pub kind: String,
+    /// `metadata.namespace` of the resource; cluster-scoped resources have none
+    pub namespace: Option<String>,
+    /// `metadata.name` of the resource
+    pub name: String,
+    /// The resource as written, after the output options
+    pub yaml: String,
+}
+
+/// Outcome of processing a file. Nothing is printed while processing; frontends
+/// report the generated resources, warnings, and files from it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProcessOutcome {
+    /// The processed file (`<stdin>` or the URL for those inputs)
+    pub input: PathBuf,
+    /// Resources generated from the file, in document order
+    pub resources: Vec<GeneratedResource>,
+    /// Warnings about documents that rendered with problems
+    pub warnings: Vec<String>,
+    /// Errors of documents that were skipped without failing the file
+    pub errors: Vec<String>,
+    /// Number of non-empty documents in the file
+    pub document_count: usize,
+    /// Number of documents rendered after the `--render-only` filter
+    pub rendered_count: usize,
+    /// Directory holding the output files
+    pub output_dir: Option<PathBuf>,
+    /// Files written, or with `--dry-run` the files that would be written
+    pub output_files: Vec<PathBuf>,
+    /// Size in bytes of each of `output_files`, in the same order
+    pub output_sizes: Vec<usize>,
+    /// The manifest stream to print with `--stdout`, instead of writing files
+    pub stdout: Option<String>,
+}
+
+/// Generates the resources of the kamut documents in `yaml` entirely in memory, for
+/// embedding kamut: runs the same document splitting and kind dispatch as `process_file`
+/// with default options, but neither reads nor writes files. A document that would be
+/// skipped, e.g. for a missing kind-specific field, fails the call; warnings are dropped.
+///
+/// ```
+/// let resources = kamut::config::generate_from_str(
+///     "name: api\nkind: ConfigMap\ndata:\n  LOG_LEVEL: info\n",
+/// )
+/// .unwrap();
+/// assert_eq!(resources.len(), 1);
+/// assert_eq!(resources[0].kind, "ConfigMap");
+/// assert!(resources[0].yaml.contains("LOG_LEVEL: info"));
+/// ```
+pub fn generate_from_str(yaml: &str) -> Result<Vec<GeneratedResource>> {
+    let source = Path::new(STRING_SOURCE);
+    let rendered = render_str(yaml, source, &ProcessOptions::default())?;
+    if let Some(error) = rendered.errors.first() {
+        return Err(anyhow::anyhow!("{}", error));
+    }
+    generated_resources(&rendered.manifests, source)
+}
+
+// Describes the rendered manifests of a source as generated resources
+fn generated_resources(manifests: &[String], source: &Path) -> Result<Vec<GeneratedResource>> {
+    manifests
+        .iter()
+        .map(|manifest| {
+            let resource = summarize_manifest(manifest, source)?;
+            Ok(GeneratedResource {
+                kind: resource.kind,
+                namespace: resource.namespace,
+                name: resource.name,
+                yaml: manifest.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Processes a single file and returns the resources generated and files written
+pub fn process_file_with_options(
+    file_path: &Path,
+    options: &ProcessOptions,
+) -> Result<ProcessOutcome> {
+    let rendered = render_file(file_path, options)?;
+    output_rendered(file_path, rendered, options)
+}
+
+/// Processes kamut documents read from stdin (the `-` pattern); the manifests always go
+/// to stdout since there is no file to name the output after
+pub fn process_stdin(options: &ProcessOptions) -> Result<ProcessOutcome> {
+    let options = ProcessOptions {
+        stdout: true,
+        ..options.clone()
+    };
+    let source = Path::new(STDIN_SOURCE);
+
+    let mut contents = String::new();
+    std::io::stdin()
+        .read_to_string(&mut contents)
+        .context("Failed to read stdin")?;
+
+    let rendered = render_str(&contents, source, &options)?;
+    output_rendered(source, rendered, &options)
+}
+
+/// Processes a kamut file fetched from an `http://` URL pattern; as with stdin, the
+/// manifests always go to stdout
+pub fn process_url(url: &str, options: &ProcessOptions) -> Result<ProcessOutcome> {
+    let options = ProcessOptions {
+        stdout: true,
+        ..options.clone()
+    };
+    let timeout = options.fetch_timeout.unwrap_or(DEFAULT_FETCH_TIMEOUT);
+    let contents = fetch_url(url, timeout)?;
+
+    let source = Path::new(url);
+    let rendered = render_str(&contents, source, &options)?;
+    output_rendered(source, rendered, &options)
+}
+
+// Validates the rendered manifests and collects or writes them
+fn output_rendered(
+    file_path: &Path,
+    rendered: RenderedFile,
+    options: &ProcessOptions,
+) -> Result<ProcessOutcome> {
+    let manifests = rendered.manifests;
+    let mut processed = ProcessOutcome {
+        input: file_path.to_path_buf(),
+        resources: generated_resources(&manifests, file_path)?,
+        warnings: rendered.warnings,
+        errors: rendered.errors,
+        document_count: rendered.document_count,
+        rendered_count: rendered.rendered_count,
+        ..Default::default()
+    };
+
+    // Reject the file before writing anything if a manifest violates its schema
+    if let Some(schema_dir) = &options.schema_dir {
+        let mut violations = Vec::new();
+        for manifest in &manifests {
+            violations.extend(crate::schema::validate_manifest(manifest, schema_dir)?);
+        }
+        if !violations.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} schema violation(s) in {}:\n{}",
+                violations.len(),
+                file_path.display(),
+                violations.join("\n")
+            ));
+        }
+    }
+
+    if options.stdout && !manifests.is_empty() {
+        processed.stdout = Some(match options.format {
+            // Start every file with a separator so several files form one YAML stream
+            OutputFormat::Yaml => format!("---\n{}", manifests.join("\n---\n")),
+            OutputFormat::Json => format_manifests(&manifests, options.format)?,
+        });
+    } else if !manifests.is_empty() {
+        // Create output file name based on the input file name
+        if let Some(file_name) = file_path.file_name().and_then(|f| f.to_str()) {
+            // Extract the base name without the extension
+            let base_name = if let Some(dot_pos) = file_name.find(".kamut.") {
+                &file_name[0..dot_pos]
+            } else if let Some(dot_pos) = file_name.find('.') {
+                &file_name[0..dot_pos]
+            } else {
+                file_name // No extension, use the whole name
+            };
+
+            let base_name = base_name.strip_prefix('.').unwrap_or(base_name);
+
+            let output_dir = match &options.output_dir {
+                Some(output_dir) if options.dry_run => output_dir.as_path(),
+                Some(output_dir) => {
+                    fs::create_dir_all(output_dir).with_context(|| {
+                        format!(
+                            "Failed to create output directory: {}",
+                            output_dir.display()
+                        )
+                    })?;
+                    output_dir.as_path()
+                }
+                None => file_path.parent().unwrap_or(Path::new("")),
+            };
+
+            processed.output_dir = Some(output_dir.to_path_buf());
+
+            if options.split_documents {
+                (processed.output_files, processed.output_sizes) =
+                    write_split_manifests(&manifests, file_path, output_dir, base_name, options)?
+                        .into_iter()
+                        .unzip();
+                return Ok(processed);
+            }
+
+            // Create the output file name with the format's extension
+            let output_file_name = format!("{}.{}", base_name, options.format.extension());
+            let output_path = output_dir.join(output_file_name);
+
+            // Join all manifests with "---" separator, or into a JSON array
+            let mut combined_manifest = format_manifests(&manifests, options.format)?;
+            if options.format == OutputFormat::Yaml {
+                combined_manifest.insert_str(0, &provenance_header(file_path));
+            }
+
+            // With --dry-run the frontend reports the file instead
+            if !options.dry_run {
+                // Write the manifest to the output file
+                write_output_file(&output_path, &combined_manifest)?;
+            }
+            processed.output_files.push(output_path);
+            processed.output_sizes.push(combined_manifest.len());
+        }
+    }
+
+    Ok(processed)
+}
+
+// Writes every manifest to its own file named from the name template, returning the
+// files with their sizes
+fn write_split_manifests(
+    manifests: &[String],
+    file_path: &Path,
+    output_dir: &Path,
+    base_name: &str,
+    options: &ProcessOptions,
+) -> Result<Vec<(PathBuf, usize)>> {
+    // The default template follows the output format's extension
+    let default_template = format!(
+        "{}.{}",
+        DEFAULT_NAME_TEMPLATE.trim_end_matches(".yaml"),
+        options.format.extension()
+    );
+    let template = options
+        .name_template
+        .as_deref()
+        .unwrap_or(&default_template);
+
+    let mut written: Vec<(PathBuf, usize)> = Vec::new();
+    for manifest in manifests {
+        let resource = summarize_manifest(manifest, file_path)?;
+        let output_path = output_dir.join(render_name_template(template, &resource, base_name));
+        if written.iter().any(|(path, _)| *path == output_path) {
+            return Err(anyhow::anyhow!(
+                "name template '{}' produced {} for more than one resource in {}",
+                template,
+                output_path.display(),
+                file_path.display()
+            ));
+        }
+        let manifest = match options.format {
+            OutputFormat::Yaml => format!("{}{}", provenance_header(file_path), manifest),
+            OutputFormat::Json => {
+                let resource: serde_yaml::Value =
+                    serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+                serialize_manifest_as(&resource, OutputFormat::Json)?
+            }
+        };
+
+        if !options.dry_run {
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create output directory: {}", parent.display())
+                })?;
+            }
+            write_output_file(&output_path, &manifest)?;
+        }
+        written.push((output_path, manifest.len()));
+    }
+
+    Ok(written)
+}
+
+// Parses `# kamut: namespace=<ns>` directive comments in a document
+fn parse_namespace_directive(doc: &str) -> Result<Option<String>> {
+    let mut namespace = None;
+    for line in doc.lines() {
+        let Some(directive) = line.trim().strip_prefix('#') else {
+            continue;
+        };
+        let Some(settings) = directive.trim().strip_prefix("kamut:") else {
+            continue;
+        };
+        for setting in settings.split_whitespace() {
+            match setting.split_once('=') {
+                Some(("namespace", value)) if !value.is_empty() => {
+                    namespace = Some(value.to_string());
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Unsupported kamut directive '{}': expected namespace=<namespace>",
+                        setting
+                    ))
+                }
+            }
+        }
+    }
+    Ok(namespace)
+}
+
+pub fn generate_prometheus_ingress(
+    config: &KamutConfig,
+    ingress_config: &crate::models::Ingress,
+) -> Result<String> {
+    // Every path routes to the Prometheus Service unless it names its own backend
+    let service_name = format!("prometheus-{}", config.name);
+    build_ingress(
+        config,
+        ingress_config,
+        format!("{}-ingress", config.name),
+        Some((&service_name, prometheus_service_port(config))),
+    )
+}
+
+/// Generates a standalone `kind: Ingress` whose paths each name their backend `service`
+/// and `port`
+pub fn generate_ingress_manifest(config: &KamutConfig) -> Result<String> {
+    let ingress_config = config
+        .ingress
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("ingress is required for Ingress"))?;
+    build_ingress(config, ingress_config, config.name.clone(), None)
+}
+
+// Builds an Ingress from the ingress configuration; paths without a `service` and `port`
+// use the default backend
+fn build_ingress(
+    config: &KamutConfig,
+    ingress_config: &crate::models::Ingress,
+    name: String,
+    default_backend: Option<(&str, i32)>,
+) -> Result<String> {
+    // Create metadata
+    let mut metadata = build_object_meta(config, Some(&name));
+
+    // Add annotations if available, sorted for stable output
+    let mut annotations: BTreeMap<String, String> = ingress_config
+        .annotations
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    // Request a certificate from cert-manager; explicit annotations take precedence
+    let cert_manager_issuer = ingress_config
+        .tls
+        .as_ref()
+        .and_then(|tls| tls.cert_manager_issuer.as_ref());
+    if let Some(issuer) = cert_manager_issuer {
+        annotations
+            .entry(CERT_MANAGER_ISSUER_ANNOTATION.to_string())
+            .or_insert_with(|| issuer.clone());
+    }
+
+    if !annotations.is_empty() {
+        metadata.annotations = Some(annotations);
+    }
+
+    // Hosts route `/` unless they list their own paths
+    let root_path = [IngressPathConfig {
+        path: "/".to_string(),
+        ..Default::default()
+    }];
+    let mut rule_configs: Vec<(&str, &[IngressPathConfig])> = Vec::new();
+    if let Some(host) = &ingress_config.host {
+        let paths = if ingress_config.paths.is_empty() {
+            &root_path[..]
+        } else {
+            &ingress_config.paths[..]
+        };
+        rule_configs.push((host, paths));
+    } else if !ingress_config.paths.is_empty() {
+        return Err(anyhow::anyhow!(
+            "ingress.paths of {} requires ingress.host",
+            config.name
+        ));
+    }
+    for rule in &ingress_config.rules {
+        let paths = if rule.paths.is_empty() {
+            &root_path[..]
+        } else {
+            &rule.paths[..]
+        };
+        rule_configs.push((&rule.host, paths));
+    }
+    if rule_configs.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Ingress for {} requires host or rules",
+            config.name
+        ));
+    }
+
+    // Create ingress rules
+    let mut ingress_rules = Vec::new();
+    for (host, paths) in &rule_configs {
+        let mut http_paths = Vec::new();
+        for path in paths.iter() {
+            let path_type = path.path_type.as_deref().unwrap_or("Prefix");
+            if !INGRESS_PATH_TYPES.contains(&path_type) {
+                return Err(anyhow::anyhow!(
+                    "Invalid Ingress path_type '{}' for {}{}: expected one of {}",
+                    path_type,
+                    host,
+                    path.path,
+                    INGRESS_PATH_TYPES.join(", ")
+                ));
+            }
+            let (service_name, port) = match (&path.service, path.port, default_backend) {
+                (Some(service), Some(port), _) => (service.as_str(), port),
+                (service, port, Some((default_service, default_port))) => (
+                    service.as_deref().unwrap_or(default_service),
+                    port.unwrap_or(default_port),
+                ),
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Ingress path {}{} of {} requires service and port",
+                        host,
+                        path.path,
+                        config.name
+                    ))
+                }
+            };
+            http_paths.push(HTTPIngressPath {
+                path: Some(path.path.clone()),
+                path_type: path_type.to_string(),
+                backend: IngressBackend {
+                    service: Some(IngressServiceBackend {
+                        name: service_name.to_string(),
+                        port: Some(ServiceBackendPort {
+                            number: Some(port),
+                            name: None,
+                        }),
+                    }),
+                    resource: None,
+                },
+            });
+        }
+        ingress_rules.push(IngressRule {
+            host: Some(host.to_string()),
+            http: Some(HTTPIngressRuleValue { paths: http_paths }),
+        });
+    }
+    let hosts: Vec<String> = rule_configs
+        .iter()
+        .map(|(host, _)| host.to_string())
+        .collect();
+
+    // Add TLS if a secret, hosts, or a cert-manager issuer are specified
+    let tls = if ingress_config.tls_secret_name.is_some()
+        || ingress_config.tls_hosts.is_some()
+        || cert_manager_issuer.is_some()
+    {
+        // cert-manager stores the issued certificate in `<host>-tls` of the first host
+        let secret_name = ingress_config
+            .tls_secret_name
+            .clone()
+            .or_else(|| cert_manager_issuer.map(|_| format!("{}-tls", hosts[0])));
+        Some(vec![IngressTLS {
+            hosts: Some(
+                ingress_config
+                    .tls_hosts
+                    .clone()
+                    .unwrap_or_else(|| hosts.clone()),
+            ),
+            secret_name,
+        }])
+    } else {
+        None
+    };
+
+    // Create ingress spec
+    let ingress_spec = IngressSpec {
+        ingress_class_name: ingress_config.class_name.clone(),
+        rules: Some(ingress_rules),
+        tls,
+        ..Default::default()
+    };
+
+    // Create ingress
+    let ingress = Ingress {
+        metadata,
+        spec: Some(ingress_spec),
+        status: None,
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&ingress).context("Failed to serialize ingress to YAML")?;
+
+    Ok(yaml)
+}
+
+/// Suffixes of the Kubernetes quantity grammar: decimal SI (`m`, `k`, `M`, ...) and
+/// binary SI (`Ki`, `Mi`, ...); `""` is a plain number
+const QUANTITY_SUFFIXES: &[&str] = &[
+    "", "n", "u", "m", "k", "M", "G", "T", "P", "E", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei",
+];
+
+/// Whether `value` follows the Kubernetes quantity grammar: a signed decimal number
+/// followed by an SI suffix or a decimal exponent (`1e3`), e.g. `500m`, `128Mi`, `1.5`
+pub fn is_valid_quantity(value: &str) -> bool {
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let number_end = unsigned
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(unsigned.len());
+    let (number, suffix) = unsigned.split_at(number_end);
+
+    let valid_number =
+        number.matches('.').count() <= 1 && number.chars().any(|c| c.is_ascii_digit());
+    let valid_suffix = QUANTITY_SUFFIXES.contains(&suffix)
+        || suffix
+            .strip_prefix(['e', 'E'])
+            .map(|exponent| exponent.strip_prefix(['+', '-']).unwrap_or(exponent))
+            .is_some_and(|exponent| {
+                !exponent.is_empty() && exponent.chars().all(|c| c.is_ascii_digit())
+            });
+    valid_number && valid_suffix
+}
+
+// Rejects cpu/memory values that are not Kubernetes quantities before they reach a manifest
+fn validate_resources(resources: &Resources) -> Result<()> {
+    for (section, spec) in [
+        ("requests", &resources.requests),
+        ("limits", &resources.limits),
+    ] {
+        let Some(spec) = spec else {
+            continue;
+        };
+        for (name, value) in [("cpu", &spec.cpu), ("memory", &spec.memory)] {
+            if let Some(value) = value.as_deref().filter(|value| !is_valid_quantity(value)) {
+                return Err(anyhow::anyhow!(
+                    "Invalid quantity '{}' for resources.{}.{} (expected e.g. 500m, 128Mi, 1Gi)",
+                    value,
+                    section,
+                    name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Literal environment variables of a container, in `EnvConfig::vars` order
+fn build_env_vars(env: &EnvConfig) -> Vec<EnvVar> {
+    env.vars()
+        .into_iter()
+        .map(|(name, value)| EnvVar {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+// Function to build container resource requirements from the resources config
+fn build_resource_requirements(resources: &Resources) -> Result<ResourceRequirements> {
+    validate_resources(resources)?;
+    let mut resource_requirements = ResourceRequirements::default();
+
+    // Add requests
+    if let Some(requests) = &resources.requests {
+        let mut request_map = BTreeMap::new();
+        if let Some(cpu) = &requests.cpu {
+            request_map.insert("cpu".to_string(), Quantity(cpu.clone()));
+        }
+        if let Some(memory) = &requests.memory {
+            request_map.insert("memory".to_string(), Quantity(memory.clone()));
+        }
+        resource_requirements.requests = Some(request_map);
+    }
+
+    // Add limits
+    if let Some(limits) = &resources.limits {
+        let mut limit_map = BTreeMap::new();
+        if let Some(cpu) = &limits.cpu {
+            limit_map.insert("cpu".to_string(), Quantity(cpu.clone()));
+        }
+        if let Some(memory) = &limits.memory {
+            limit_map.insert("memory".to_string(), Quantity(memory.clone()));
+        }
+        resource_requirements.limits = Some(limit_map);
+    }
+
+    Ok(resource_requirements)
+}
+
+/// Builds the workload container shared by Deployments, StatefulSets, and DaemonSets
+fn build_workload_container(config: &KamutConfig, image: &str) -> Result<Container> {
+    let mut container = Container {
+        name: config.name.clone(),
+        image: Some(image.to_string()),
+        ..Default::default()
+    };
+
+    // Override the entrypoint and its arguments if available
+    if !config.command.is_empty() {
+        container.command = Some(config.command.clone());
+    }
+    if !config.args.is_empty() {
+        container.args = Some(config.args.clone());
+    }
+
+    // Add environment variables if available
+    if let Some(env_vars) = &config.env {
+        container.env = Some(build_env_vars(env_vars));
+    }
+
+    // Add environment variables read from Secrets and ConfigMaps if available
+    for env_ref in config.env_from_refs.iter().flatten() {
+        container
+            .env
+            .get_or_insert_with(Vec::new)
+            .push(build_env_from_ref(env_ref)?);
+    }
+
+    // Import every key of the listed ConfigMaps and Secrets
+    for env_from in config.env_from.iter().flatten() {
+        container
+            .env_from
+            .get_or_insert_with(Vec::new)
+            .push(build_env_from_source(env_from)?);
+    }
+
+    // Add resource requirements if available
+    if let Some(resources) = &config.resources {
+        container.resources = Some(build_resource_requirements(resources)?);
+    }
+
+    // Add in-place resize policies if available
+    if let Some(resize_policies) = &config.resize_policy {
+        let mut policies = Vec::new();
+        for policy in resize_policies {
+            if !matches!(policy.resource_name.as_str(), "cpu" | "memory") {
+                return Err(anyhow::anyhow!(
+                    "Invalid resize_policy resourceName '{}': expected 'cpu' or 'memory'",
+                    policy.resource_name
+                ));
+            }
+            if !matches!(
+                policy.restart_policy.as_str(),
+                "NotRequired" | "RestartContainer"
+            ) {
+                return Err(anyhow::anyhow!(
+                    "Invalid resize_policy restartPolicy '{}': expected 'NotRequired' or 'RestartContainer'",
+                    policy.restart_policy
+                ));
+            }
+            policies.push(ContainerResizePolicy {
+                resource_name: policy.resource_name.clone(),
+                restart_policy: policy.restart_policy.clone(),
+            });
+        }
+        container.resize_policy = Some(policies);
+    }
+
+    // Add container ports if available
+    if let Some(ports) = config.ports.as_ref().filter(|ports| !ports.is_empty()) {
+        container.ports = Some(build_container_ports(ports));
+    }
+
+    // Add security context if available
+    if let Some(security_context) = config
+        .security_context
+        .as_ref()
+        .filter(|security_context| security_context.enabled != Some(false))
+    {
+        container.security_context = Some(build_security_context(security_context));
+    }
+
+    // Add liveness and readiness probes if available
+    if let Some(probes) = &config.probes {
+        container.liveness_probe = probes.liveness.as_ref().map(build_http_probe);
+        container.readiness_probe = probes.readiness.as_ref().map(build_http_probe);
+    }
+
+    // Add the preStop hook if available
+    if let Some(lifecycle) = &config.lifecycle {
+        container.lifecycle = build_lifecycle(lifecycle)?;
+    }
+
+    Ok(container)
+}
+
+fn build_container_ports(ports: &[ContainerPortConfig]) -> Vec<ContainerPort> {
+    ports
+        .iter()
+        .map(|port| ContainerPort {
+            name: port.name.clone(),
+            container_port: port.container_port,
+            host_port: port.host_port,
+            protocol: Some(port.protocol.clone().unwrap_or_else(|| "TCP".to_string())),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn build_env_from_ref(env_ref: &EnvFromRefConfig) -> Result<EnvVar> {
+    let value_from = match (&env_ref.secret, &env_ref.config_map) {
+        (Some(secret), None) => EnvVarSource {
+            secret_key_ref: Some(SecretKeySelector {
+                name: secret.clone(),
+                key: env_ref.key.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        (None, Some(config_map)) => EnvVarSource {
+            config_map_key_ref: Some(ConfigMapKeySelector {
+                name: config_map.clone(),
+                key: env_ref.key.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "env_from_refs entry '{}' sets both secret and config_map; use one per entry",
+                env_ref.name
+            ));
+        }
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "env_from_refs entry '{}' requires either secret or config_map",
+                env_ref.name
+            ));
+        }
+    };
+
+    Ok(EnvVar {
+        name: env_ref.name.clone(),
+        value_from: Some(value_from),
+        ..Default::default()
+    })
+}
+
+// Builds an envFrom source importing all keys of a ConfigMap or Secret
+fn build_env_from_source(env_from: &EnvFromConfig) -> Result<EnvFromSource> {
+    let mut source = EnvFromSource {
+        prefix: env_from.prefix.clone(),
+        ..Default::default()
+    };
+    match (&env_from.secret, &env_from.config_map) {
+        (Some(secret), None) => {
+            source.secret_ref = Some(SecretEnvSource {
+                name: secret.clone(),
+                optional: None,
+            });
+        }
+        (None, Some(config_map)) => {
+            source.config_map_ref = Some(ConfigMapEnvSource {
+                name: config_map.clone(),
+                optional: None,
+            });
+        }
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "env_from entry sets both secret and config_map; use one per entry"
+            ));
+        }
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "env_from entry requires either secret or config_map"
+            ));
+        }
+    }
+    Ok(source)
+}
+
+// Explicit fields take precedence over the `restricted` baseline
+fn build_security_context(config: &SecurityContextConfig) -> SecurityContext {
+    let mut security_context = SecurityContext::default();
+
+    if config.restricted {
+        security_context.allow_privilege_escalation = Some(false);
+        security_context.run_as_non_root = Some(true);
+        security_context.capabilities = Some(Capabilities {
+            drop: Some(vec!["ALL".to_string()]),
+            ..Default::default()
+        });
+        security_context.seccomp_profile = Some(SeccompProfile {
+            type_: "RuntimeDefault".to_string(),
+            ..Default::default()
+        });
+    }
+
+    if let Some(allow_privilege_escalation) = config.allow_privilege_escalation {
+        security_context.allow_privilege_escalation = Some(allow_privilege_escalation);
+    }
+    if let Some(run_as_non_root) = config.run_as_non_root {
+        security_context.run_as_non_root = Some(run_as_non_root);
+    }
+    security_context.run_as_user = config.run_as_user;
+    security_context.run_as_group = config.run_as_group;
+    if let Some(capabilities) = &config.capabilities {
+        let mut merged = security_context.capabilities.take().unwrap_or_default();
+        if !capabilities.add.is_empty() {
+            merged.add = Some(capabilities.add.clone());
+        }
+        if !capabilities.drop.is_empty() {
+            merged.drop = Some(capabilities.drop.clone());
+        }
+        security_context.capabilities = Some(merged);
+    }
+
+    security_context
+}
+
+// Pod security context of Prometheus: the defaults (fsGroup 2000, runAsUser 1000, and
+// runAsNonRoot) with the configured fields taking precedence, or none when disabled
+fn build_prometheus_security_context(config: &KamutConfig) -> Result<Option<PodSecurityContext>> {
+    let mut security_context = PodSecurityContext {
+        fs_group: Some(2000),
+        run_as_non_root: Some(true),
+        run_as_user: Some(1000),
+        ..Default::default()
+    };
+
+    let Some(overrides) = &config.security_context else {
+        return Ok(Some(security_context));
+    };
+    if overrides.enabled == Some(false) {
+        return Ok(None);
+    }
+
+    // Prometheus only exposes the pod security context
+    let container_fields = [
+        ("restricted", overrides.restricted),
+        (
+            "allow_privilege_escalation",
+            overrides.allow_privilege_escalation.is_some(),
+        ),
+        ("capabilities", overrides.capabilities.is_some()),
+    ];
+    if let Some((field, _)) = container_fields.iter().find(|(_, set)| *set) {
+        return Err(anyhow::anyhow!(
+            "security_context.{} applies to containers and is not supported for Prometheus",
+            field
+        ));
+    }
+
+    if overrides.fs_group.is_some() {
+        security_context.fs_group = overrides.fs_group;
+    }
+    if overrides.run_as_user.is_some() {
+        security_context.run_as_user = overrides.run_as_user;
+    }
+    if overrides.run_as_non_root.is_some() {
+        security_context.run_as_non_root = overrides.run_as_non_root;
+    }
+    security_context.run_as_group = overrides.run_as_group;
+
+    Ok(Some(security_context))
+}
+
+fn build_http_probe(probe: &ProbeSpec) -> Probe {
+    Probe {
+        http_get: Some(HTTPGetAction {
+            path: Some(probe.path.clone()),
+            port: IntOrString::Int(probe.port),
+            ..Default::default()
+        }),
+        initial_delay_seconds: probe.initial_delay_seconds,
+        period_seconds: probe.period_seconds,
+        ..Default::default()
+    }
+}
+
+// Builds the container lifecycle, or none when no hook is configured
+fn build_lifecycle(lifecycle: &LifecycleConfig) -> Result<Option<Lifecycle>> {
+    let Some(pre_stop) = &lifecycle.pre_stop else {
+        return Ok(None);
+    };
+
+    let handler = match (&pre_stop.exec, &pre_stop.http_get) {
+        (Some(command), None) => {
+            if command.is_empty() {
+                return Err(anyhow::anyhow!("lifecycle.pre_stop.exec must not be empty"));
+            }
+            LifecycleHandler {
+                exec: Some(ExecAction {
+                    command: Some(command.clone()),
+                }),
+                ..Default::default()
+            }
+        }
+        (None, Some(http_get)) => LifecycleHandler {
+            http_get: Some(HTTPGetAction {
+                path: Some(http_get.path.clone()),
+                port: IntOrString::Int(http_get.port),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "lifecycle.pre_stop sets both exec and http_get; use one"
+            ));
+        }
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "lifecycle.pre_stop requires either exec or http_get"
+            ));
+        }
+    };
+
+    Ok(Some(Lifecycle {
+        pre_stop: Some(handler),
+        ..Default::default()
+    }))
+}
+
+// Maps each volume entry to a pod volume backed by its ConfigMap or Secret, and its mount
+fn build_config_volumes(volumes: &[VolumeConfig]) -> Result<(Vec<Volume>, Vec<VolumeMount>)> {
+    let mut pod_volumes = Vec::new();
+    let mut volume_mounts = Vec::new();
+
+    for volume in volumes {
+        let mut pod_volume = Volume {
+            name: volume.name.clone(),
+            ..Default::default()
+        };
+        match (&volume.config_map, &volume.secret) {
+            (Some(config_map), None) => {
+                pod_volume.config_map = Some(ConfigMapVolumeSource {
+                    name: config_map.clone(),
+                    ..Default::default()
+                });
+            }
+            (None, Some(secret)) => {
+                pod_volume.secret = Some(SecretVolumeSource {
+                    secret_name: Some(secret.clone()),
+                    ..Default::default()
+                });
+            }
+            (Some(_), Some(_)) => {
+                return Err(anyhow::anyhow!(
+                    "volume '{}' sets both config_map and secret; use one per volume",
+                    volume.name
+                ));
+            }
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "volume '{}' requires either config_map or secret",
+                    volume.name
+                ));
+            }
+        }
+        pod_volumes.push(pod_volume);
+
+        volume_mounts.push(VolumeMount {
+            name: volume.name.clone(),
+            mount_path: volume.mount_path.clone(),
+            ..Default::default()
+        });
+    }
+
+    Ok((pod_volumes, volume_mounts))
+}
+
+/// Builds the pod template for a workload with the given labels and container
+fn build_pod_template(
+    config: &KamutConfig,
+    labels: BTreeMap<String, String>,
+    container: Container,
+) -> PodTemplateSpec {
+    // Create pod spec
+    let mut pod_spec = PodSpec {
+        containers: vec![container],
+        termination_grace_period_seconds: config.termination_grace_period_seconds,
+        service_account_name: config.service_account_name.clone(),
+        ..Default::default()
+    };
+
+    // Add nodeSelector if available
+    if let Some(node_selector) = &config.node_selector {
+        let node_selector_map = node_selector.clone().into_iter().collect();
+        pod_spec.node_selector = Some(node_selector_map);
+    };
+
+    // The volume group of the security context belongs to the pod
+    pod_spec.security_context = config
+        .security_context
+        .as_ref()
+        .filter(|security_context| security_context.enabled != Some(false))
+        .and_then(|security_context| security_context.fs_group)
+        .map(|fs_group| PodSecurityContext {
+            fs_group: Some(fs_group),
+            ..Default::default()
+        });
+
+    // Add imagePullSecrets if available
+    if !config.image_pull_secrets.is_empty() {
+        pod_spec.image_pull_secrets = Some(
+            config
+                .image_pull_secrets
+                .iter()
+                .map(|name| LocalObjectReference { name: name.clone() })
+                .collect(),
+        );
+    }
+
+    // Create pod template spec
+    let template_metadata = ObjectMeta {
+        labels: Some(labels),
+        ..Default::default()
+    };
+
+    PodTemplateSpec {
+        metadata: Some(template_metadata),
+        spec: Some(pod_spec),
+    }
+}
+
+/// Generates a Deployment with its optional Service, HorizontalPodAutoscaler, and KEDA
+/// ScaledObject; `copies` repeats the set for each copy
+pub fn generate_deployment_manifests(config: &KamutConfig) -> Result<Vec<String>> {
+    if config.image.is_none() {
+        return Ok(Vec::new());
+    }
+    if config.autoscaling.is_some() && config.keda.is_some() {
+        return Err(anyhow::anyhow!(
+            "Deployment {} sets both 'autoscaling' and 'keda'; choose one",
+            config.name
+        ));
+    }
+
+    // Each copy gets its own Deployment, Service, and HorizontalPodAutoscaler
+    let copies: Vec<(KamutConfig, Option<u32>)> = match config.copies {
+        Some(0) => {
+            return Err(anyhow::anyhow!(
+                "copies must be at least 1 for Deployment {}",
+                config.name
+            ))
+        }
+        Some(copies) => (0..copies)
+            .map(|index| (deployment_copy(config, index), Some(index)))
+            .collect(),
+        None => vec![(config.clone(), None)],
+    };
+
+    let mut manifests = Vec::new();
+    for (config, copy_index) in &copies {
+        manifests.push(build_deployment_manifest(config, *copy_index)?);
+
+        // Generate Service if specified
+        if config.service.is_some() {
+            manifests.push(generate_deployment_service(config)?);
+        }
+
+        // Generate HorizontalPodAutoscaler if specified
+        if config.autoscaling.is_some() {
+            manifests.push(generate_hpa_manifest(config)?);
+        }
+
+        // Generate KEDA ScaledObject if specified
+        if config.keda.is_some() {
+            manifests.push(generate_keda_scaled_object(config)?);
+        }
+    }
+
+    Ok(manifests)
+}
+
+pub fn generate_deployment_manifest(config: &KamutConfig) -> Result<String> {
+    build_deployment_manifest(config, None)
+}
+
+// Config of one of the `copies` of a Deployment: named `<name>-<index>`, with the index
+// in the KAMUT_SHARD_INDEX environment variable
+fn deployment_copy(config: &KamutConfig, index: u32) -> KamutConfig {
+    let mut copy = config.clone();
+    copy.name = format!("{}-{}", config.name, index);
+
+    let index_var = EnvVarConfig {
+        name: COPY_INDEX_ENV.to_string(),
+        value: index.to_string(),
+    };
+    copy.env = Some(match copy.env.take() {
+        Some(EnvConfig::Map(mut vars)) => {
+            vars.insert(index_var.name, index_var.value);
+            EnvConfig::Map(vars)
+        }
+        Some(EnvConfig::List(mut vars)) => {
+            vars.push(index_var);
+            EnvConfig::List(vars)
+        }
+        None => EnvConfig::List(vec![index_var]),
+    });
+
+    copy
+}
+
+// Builds a Deployment; copies carry their index in their labels and selector
+fn build_deployment_manifest(config: &KamutConfig, copy_index: Option<u32>) -> Result<String> {
+    // Create metadata; copies add their index to the labels
+    let mut metadata = build_object_meta(config, None);
+    let labels = metadata.labels.get_or_insert_with(BTreeMap::new);
+    if let Some(index) = copy_index {
+        labels.insert(COPY_INDEX_LABEL.to_string(), index.to_string());
+    }
+    let labels = labels.clone();
+
+    // Ensure image is available
+    let image = config
+        .image
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Image is required for Deployment"))?;
+
+    // Create container and pod template
+    let mut container = build_workload_container(config, image)?;
+    let volumes = config.volumes.as_deref().unwrap_or_default();
+    let pod_volumes = match volumes {
+        [] => None,
+        volumes => {
+            let (pod_volumes, volume_mounts) = build_config_volumes(volumes)?;
+            container.volume_mounts = Some(volume_mounts);
+            Some(pod_volumes)
+        }
+    };
+    let mut pod_template_spec = build_pod_template(config, labels, container);
+    if let Some(pod_spec) = pod_template_spec.spec.as_mut() {
+        pod_spec.volumes = pod_volumes;
+        pod_spec.tolerations = config
+            .tolerations
+            .as_deref()
+            .map(build_tolerations)
+            .transpose()?;
+        check_container_names(config)?;
+        pod_spec
+            .containers
+            .extend(build_sidecar_containers(config)?);
+        if !config.init_containers.is_empty() {
+            pod_spec.init_containers = Some(build_init_containers(config)?);
+        }
+    }
+
+    // Create selector
+    let mut match_labels = BTreeMap::new();
+    match_labels.insert("app".to_string(), config.name.clone());
+    if let Some(index) = copy_index {
+        match_labels.insert(COPY_INDEX_LABEL.to_string(), index.to_string());
+    }
+    let selector = LabelSelector {
+        match_labels: Some(match_labels),
+        ..Default::default()
+    };
+
+    // Create deployment spec
+    let deployment_spec = DeploymentSpec {
+        // The HorizontalPodAutoscaler (or KEDA's) owns the replica count when autoscaling is set
+        replicas: if config.autoscaling.is_some() || config.keda.is_some() {
+            None
+        } else {
+            config.replicas
+        },
+        selector,
+        template: pod_template_spec,
+        strategy: config.strategy.as_ref().map(build_strategy).transpose()?,
+        ..Default::default()
+    };
+
+    // Create deployment
+    let deployment = Deployment {
+        metadata,
+        spec: Some(deployment_spec),
+        ..Default::default()
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&deployment).context("Failed to serialize deployment to YAML")?;
+
+    Ok(yaml)
+}
+
+// Builds a Deployment rollout strategy; Recreate takes no rolling-update parameters
+fn build_strategy(strategy: &StrategyConfig) -> Result<DeploymentStrategy> {
+    let type_ = strategy.type_.as_deref().unwrap_or("RollingUpdate");
+    let rolling_update = match type_ {
+        "RollingUpdate" => {
+            for (field, value) in [
+                ("max_surge", &strategy.max_surge),
+                ("max_unavailable", &strategy.max_unavailable),
+            ] {
+                if let Some(IntOrString::String(value)) = value {
+                    let percentage = value.strip_suffix('%').unwrap_or_default();
+                    if percentage.parse::<u32>().is_err() {
+                        return Err(anyhow::anyhow!(
+                            "strategy.{} must be an integer or a percentage like 25%, got '{}'",
+                            field,
+                            value
+                        ));
+                    }
+                }
+            }
+            if strategy.max_surge.is_none() && strategy.max_unavailable.is_none() {
+                None
+            } else {
+                Some(RollingUpdateDeployment {
+                    max_surge: strategy.max_surge.clone(),
+                    max_unavailable: strategy.max_unavailable.clone(),
+                })
+            }
+        }
+        "Recreate" => {
+            if strategy.max_surge.is_some() || strategy.max_unavailable.is_some() {
+                return Err(anyhow::anyhow!(
+                    "strategy.max_surge and strategy.max_unavailable require type RollingUpdate, not Recreate"
+                ));
+            }
+            None
+        }
+        type_ => {
+            return Err(anyhow::anyhow!(
+                "Invalid strategy type '{}': expected 'RollingUpdate' or 'Recreate'",
+                type_
+            ))
+        }
+    };
+
+    Ok(DeploymentStrategy {
+        type_: Some(type_.to_string()),
+        rolling_update,
+    })
+}
+
+// Container names must be unique across the main container, the sidecars, and the init
+// containers of a pod
+fn check_container_names(config: &KamutConfig) -> Result<()> {
+    let mut names = BTreeSet::from([config.name.as_str()]);
+    let others = config
+        .containers
+        .iter()
+        .map(|container| container.name.as_str())
+        .chain(
+            config
+                .init_containers
+                .iter()
+                .map(|init_container| init_container.name.as_str()),
+        );
+    for name in others {
+        if !name.is_empty() && !names.insert(name) {
+            return Err(anyhow::anyhow!(
+                "Duplicate container name '{}' in Deployment {}",
+                name,
+                config.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Builds the containers listed in `containers`, which run after the main container
+fn build_sidecar_containers(config: &KamutConfig) -> Result<Vec<Container>> {
+    config
+        .containers
+        .iter()
+        .map(|container| {
+            if container.name.is_empty() || container.image.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "containers entries of Deployment {} require a name and an image",
+                    config.name
+                ));
+            }
+
+            Ok(Container {
+                name: container.name.clone(),
+                image: Some(container.image.clone()),
+                command: (!container.command.is_empty()).then(|| container.command.clone()),
+                args: (!container.args.is_empty()).then(|| container.args.clone()),
+                env: container.env.as_ref().map(build_env_vars),
+                resources: container
+                    .resources
+                    .as_ref()
+                    .map(build_resource_requirements)
+                    .transpose()?,
+                ports: (!container.ports.is_empty())
+                    .then(|| build_container_ports(&container.ports)),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+// Builds the init containers of a Deployment; native sidecars keep `restartPolicy: Always`
+fn build_init_containers(config: &KamutConfig) -> Result<Vec<Container>> {
+    config
+        .init_containers
+        .iter()
+        .map(|init_container| {
+            if init_container.name.is_empty() || init_container.image.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "init_containers entries of Deployment {} require a name and an image",
+                    config.name
+                ));
+            }
+            if let Some(restart_policy) = init_container.restart_policy.as_deref() {
+                if restart_policy != "Always" {
+                    return Err(anyhow::anyhow!(
+                        "Invalid restart_policy '{}' for init container '{}': only 'Always' (a native sidecar) is allowed",
+                        restart_policy,
+                        init_container.name
+                    ));
+                }
+            }
+
+            Ok(Container {
+                name: init_container.name.clone(),
+                image: Some(init_container.image.clone()),
+                command: (!init_container.command.is_empty())
+                    .then(|| init_container.command.clone()),
+                args: (!init_container.args.is_empty()).then(|| init_container.args.clone()),
+                env: init_container.env.as_ref().map(build_env_vars),
+                resources: init_container
+                    .resources
+                    .as_ref()
+                    .map(build_resource_requirements)
+                    .transpose()?,
+                restart_policy: init_container.restart_policy.clone(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+// Builds pod tolerations; `Exists` matches any value, so it can't be combined with one
+fn build_tolerations(tolerations: &[TolerationConfig]) -> Result<Vec<Toleration>> {
+    tolerations
+        .iter()
+        .map(|toleration| {
+            match toleration.operator.as_deref() {
+                None | Some("Equal") => {
+                    if toleration.key.is_none() {
+                        return Err(anyhow::anyhow!(
+                            "tolerations without a key require operator Exists"
+                        ));
+                    }
+                }
+                Some("Exists") => {
+                    if toleration.value.is_some() {
+                        return Err(anyhow::anyhow!(
+                            "tolerations with operator Exists must not set a value"
+                        ));
+                    }
+                }
+                Some(operator) => {
+                    return Err(anyhow::anyhow!(
+                        "Invalid toleration operator '{}': expected 'Equal' or 'Exists'",
+                        operator
+                    ))
+                }
+            }
+            if let Some(effect) = toleration.effect.as_deref() {
+                if !["NoSchedule", "PreferNoSchedule", "NoExecute"].contains(&effect) {
+                    return Err(anyhow::anyhow!(
+                        "Invalid toleration effect '{}': expected 'NoSchedule', 'PreferNoSchedule', or 'NoExecute'",
+                        effect
+                    ));
+                }
+            }
+
+            Ok(Toleration {
+                key: toleration.key.clone(),
+                operator: toleration.operator.clone(),
+                value: toleration.value.clone(),
+                effect: toleration.effect.clone(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+pub fn generate_hpa_manifest(config: &KamutConfig) -> Result<String> {
+    let autoscaling = config
+        .autoscaling
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("autoscaling is required for HorizontalPodAutoscaler"))?;
+
+    let min_replicas = autoscaling.min_replicas.unwrap_or(1);
+    if min_replicas == 0 {
+        return Err(anyhow::anyhow!(
+            "autoscaling.min_replicas must be at least 1; use 'keda' to scale Deployment {} to zero",
+            config.name
+        ));
+    }
+    if min_replicas < 1 || autoscaling.max_replicas < min_replicas {
+        return Err(anyhow::anyhow!(
+            "autoscaling.max_replicas ({}) must be at least min_replicas ({}), which must be at least 1",
+            autoscaling.max_replicas,
+            min_replicas
+        ));
+    }
+
+    let metrics = autoscaling.target_cpu_utilization.map(|utilization| {
+        vec![MetricSpec {
+            type_: "Resource".to_string(),
+            resource: Some(ResourceMetricSource {
+                name: "cpu".to_string(),
+                target: MetricTarget {
+                    type_: "Utilization".to_string(),
+                    average_utilization: Some(utilization),
+                    ..Default::default()
+                },
+            }),
+            ..Default::default()
+        }]
+    });
+
+    let hpa = HorizontalPodAutoscaler {
+        metadata: build_object_meta(config, None),
+        spec: Some(HorizontalPodAutoscalerSpec {
+            scale_target_ref: CrossVersionObjectReference {
+                api_version: Some("apps/v1".to_string()),
+                kind: "Deployment".to_string(),
+                name: config.name.clone(),
+            },
+            min_replicas: Some(min_replicas),
+            max_replicas: autoscaling.max_replicas,
+            metrics,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    serialize_manifest(&hpa).context("Failed to serialize HorizontalPodAutoscaler to YAML")
+}
+
+/// Metadata keys KEDA requires for the scaler types kamut checks; other types pass through
+pub const KEDA_REQUIRED_TRIGGER_METADATA: &[(&str, &[&str])] = &[
+    ("cron", &["timezone", "start", "end", "desiredReplicas"]),
+    ("prometheus", &["serverAddress", "query", "threshold"]),
+];
+
+/// Generates a `keda.sh/v1alpha1` ScaledObject scaling the Deployment on its triggers
+pub fn generate_keda_scaled_object(config: &KamutConfig) -> Result<String> {
+    let keda = config
+        .keda
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("keda is required for ScaledObject"))?;
+
+    if keda.triggers.is_empty() {
+        return Err(anyhow::anyhow!(
+            "keda.triggers must list at least one trigger for Deployment {}",
+            config.name
+        ));
+    }
+    // KEDA's defaults when the bounds are not set
+    let min_replicas = keda.min_replicas.unwrap_or(0);
+    let max_replicas = keda.max_replicas.unwrap_or(100);
+    if min_replicas < 0 || max_replicas < min_replicas.max(1) {
+        return Err(anyhow::anyhow!(
+            "keda.max_replicas ({}) must be at least 1 and min_replicas ({}), which must not be negative",
+            max_replicas,
+            min_replicas
+        ));
+    }
+
+    let mut triggers = Vec::new();
+    for trigger in &keda.triggers {
+        if let Some((_, required)) = KEDA_REQUIRED_TRIGGER_METADATA
+            .iter()
+            .find(|(type_, _)| *type_ == trigger.type_)
+        {
+            if let Some(missing) = required
+                .iter()
+                .find(|key| !trigger.metadata.contains_key(**key))
+            {
+                return Err(anyhow::anyhow!(
+                    "keda {} trigger requires metadata.{}",
+                    trigger.type_,
+                    missing
+                ));
+            }
+        }
+        triggers.push(ScaledObjectTriggers {
+            r#type: trigger.type_.clone(),
+            name: trigger.name.clone(),
+            metadata: trigger.metadata.clone(),
+            ..Default::default()
+        });
+    }
+
+    let scaled_object = ScaledObject {
+        metadata: build_object_meta(config, None),
+        spec: ScaledObjectSpec {
+            scale_target_ref: ScaledObjectScaleTargetRef {
+                api_version: Some("apps/v1".to_string()),
+                kind: Some("Deployment".to_string()),
+                name: config.name.clone(),
+                ..Default::default()
+            },
+            min_replica_count: keda.min_replicas,
+            max_replica_count: keda.max_replicas,
+            polling_interval: keda.polling_interval,
+            cooldown_period: keda.cooldown_period,
+            triggers,
+            ..Default::default()
+        },
+        status: None,
+    };
+
+    serialize_manifest(&scaled_object).context("Failed to serialize ScaledObject to YAML")
+}
+
+pub fn generate_deployment_service(config: &KamutConfig) -> Result<String> {
+    let service_config = config
+        .service
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("service is required for the Deployment Service"))?;
+
+    // Create metadata
+    let mut metadata = build_object_meta(config, None);
+    metadata.annotations = service_annotations(service_config);
+
+    // Select the Deployment's pods by their app label
+    let mut selector = BTreeMap::new();
+    selector.insert("app".to_string(), config.name.clone());
+
+    // Default to the first declared container port
+    let port = service_config
+        .port
+        .or_else(|| {
+            config
+                .ports
+                .as_ref()
+                .and_then(|ports| ports.first())
+                .map(|port| port.container_port)
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!("service.port is required when the Deployment declares no ports")
+        })?;
+
+    // Create service port
+    let mut service_ports = vec![ServicePort {
+        name: Some(
+            service_config
+                .port_name
+                .clone()
+                .unwrap_or_else(|| "http".to_string()),
+        ),
+        port,
+        target_port: Some(IntOrString::Int(service_config.target_port.unwrap_or(port))),
+        node_port: service_node_port(service_config)?,
+        protocol: Some("TCP".to_string()),
+        ..Default::default()
+    }];
+
+    // Add extra named ports
+    for extra_port in &service_config.extra_ports {
+        service_ports.push(ServicePort {
+            name: Some(extra_port.name.clone()),
+            port: extra_port.port,
+            target_port: Some(IntOrString::Int(
+                extra_port.target_port.unwrap_or(extra_port.port),
+            )),
+            protocol: Some("TCP".to_string()),
+            ..Default::default()
+        });
+    }
+
+    // Create service spec
+    let service_spec = ServiceSpec {
+        selector: Some(selector),
+        ports: Some(service_ports),
+        type_: Some(
+            service_config
+                .type_
+                .clone()
+                .unwrap_or_else(|| "ClusterIP".to_string()),
+        ),
+        ..Default::default()
+    };
+
+    // Create service
+    let service = Service {
+        metadata,
+        spec: Some(service_spec),
         status: None,
     };
 
-    // Serialize to YAML
-    let yaml = serde_yaml::to_string(&ingress).context("Failed to serialize ingress to YAML")?;
+    // Serialize to YAML
+    let yaml = serialize_manifest(&service).context("Failed to serialize service to YAML")?;
+
+    Ok(yaml)
+}
+
+pub fn generate_statefulset_manifest(config: &KamutConfig) -> Result<String> {
+    // Create metadata
+    let metadata = build_object_meta(config, None);
+    let labels = metadata.labels.clone().unwrap_or_default();
+
+    // Ensure image is available
+    let image = config
+        .image
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Image is required for StatefulSet"))?;
+
+    let mut container = build_workload_container(config, image)?;
+
+    // Map each storage entry to a volumeClaimTemplate, mounted when a mountPath is given
+    let storage_entries = config
+        .storage
+        .as_ref()
+        .map(|storage| storage.entries())
+        .unwrap_or_default();
+    let mut volume_claim_templates = Vec::new();
+    let mut volume_mounts = Vec::new();
+    for (index, storage_cfg) in storage_entries.iter().enumerate() {
+        let claim_name = match &storage_cfg.name {
+            Some(name) => name.clone(),
+            None if storage_entries.len() == 1 => "data".to_string(),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "storage entry {} requires a name when several entries are listed",
+                    index + 1
+                ))
+            }
+        };
+
+        let mut requests = BTreeMap::new();
+        requests.insert("storage".to_string(), Quantity(storage_cfg.size.clone()));
+
+        volume_claim_templates.push(PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some(claim_name.clone()),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                access_modes: Some(vec!["ReadWriteOnce".to_string()]),
+                storage_class_name: storage_cfg.class_name.clone(),
+                resources: Some(VolumeResourceRequirements {
+                    requests: Some(requests),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            status: None,
+        });
+
+        if let Some(mount_path) = &storage_cfg.mount_path {
+            volume_mounts.push(VolumeMount {
+                name: claim_name,
+                mount_path: mount_path.clone(),
+                ..Default::default()
+            });
+        }
+    }
+    if !volume_mounts.is_empty() {
+        container.volume_mounts = Some(volume_mounts);
+    }
+
+    let pod_template_spec = build_pod_template(config, labels, container);
+
+    // Create selector
+    let mut match_labels = BTreeMap::new();
+    match_labels.insert("app".to_string(), config.name.clone());
+    let selector = LabelSelector {
+        match_labels: Some(match_labels),
+        ..Default::default()
+    };
+
+    // Create statefulset spec
+    let statefulset_spec = StatefulSetSpec {
+        replicas: config.replicas,
+        selector,
+        service_name: config.name.clone(),
+        template: pod_template_spec,
+        volume_claim_templates: if volume_claim_templates.is_empty() {
+            None
+        } else {
+            Some(volume_claim_templates)
+        },
+        ..Default::default()
+    };
+
+    // Create statefulset
+    let statefulset = StatefulSet {
+        metadata,
+        spec: Some(statefulset_spec),
+        ..Default::default()
+    };
+
+    // Serialize to YAML
+    let yaml =
+        serialize_manifest(&statefulset).context("Failed to serialize statefulset to YAML")?;
+
+    Ok(yaml)
+}
+
+/// Generates a DaemonSet running the workload container on every node the pods tolerate
+pub fn generate_daemonset_manifest(config: &KamutConfig) -> Result<String> {
+    let metadata = build_object_meta(config, None);
+    let labels = metadata.labels.clone().unwrap_or_default();
+
+    let image = config
+        .image
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Image is required for DaemonSet"))?;
+
+    let container = build_workload_container(config, image)?;
+    let mut pod_template_spec = build_pod_template(config, labels.clone(), container);
+    if let Some(pod_spec) = pod_template_spec.spec.as_mut() {
+        pod_spec.tolerations = config
+            .tolerations
+            .as_deref()
+            .map(build_tolerations)
+            .transpose()?;
+    }
+
+    let daemonset = DaemonSet {
+        metadata,
+        spec: Some(DaemonSetSpec {
+            selector: LabelSelector {
+                match_labels: Some(labels),
+                ..Default::default()
+            },
+            template: pod_template_spec,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    serialize_manifest(&daemonset).context("Failed to serialize daemonset to YAML")
+}
+
+pub fn generate_priority_class_manifest(config: &KamutConfig) -> Result<String> {
+    let value = config
+        .value
+        .ok_or_else(|| anyhow::anyhow!("Value is required for PriorityClass"))?;
+
+    // PriorityClass is cluster-scoped, so the namespace is never set
+    let priority_class = PriorityClass {
+        metadata: ObjectMeta {
+            namespace: None,
+            ..build_object_meta(config, None)
+        },
+        value,
+        global_default: config.global_default,
+        description: config.description.clone(),
+        ..Default::default()
+    };
+
+    // Serialize to YAML
+    let yaml =
+        serialize_manifest(&priority_class).context("Failed to serialize PriorityClass to YAML")?;
+
+    Ok(yaml)
+}
+
+pub fn generate_configmap_manifest(config: &KamutConfig) -> Result<String> {
+    // An empty ConfigMap is almost always a mistake
+    let data = config
+        .data
+        .as_ref()
+        .filter(|data| !data.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Data is required for ConfigMap"))?;
+
+    let config_map = ConfigMap {
+        metadata: build_object_meta(config, None),
+        data: Some(data.clone().into_iter().collect()),
+        ..Default::default()
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&config_map).context("Failed to serialize ConfigMap to YAML")?;
+
+    Ok(yaml)
+}
+
+/// Type of Secrets without a `secret_type`
+pub const DEFAULT_SECRET_TYPE: &str = "Opaque";
+
+/// Key holding the registry credentials of a `kubernetes.io/dockerconfigjson` Secret
+const DOCKER_CONFIG_JSON_KEY: &str = ".dockerconfigjson";
+
+pub fn generate_secret_manifest(config: &KamutConfig) -> Result<String> {
+    let string_data = config.string_data.clone().unwrap_or_default();
+    let data = config.data.clone().unwrap_or_default();
+    if string_data.is_empty() && data.is_empty() {
+        return Err(anyhow::anyhow!(
+            "string_data or data is required for Secret"
+        ));
+    }
+    if let Some(key) = string_data.keys().find(|key| data.contains_key(*key)) {
+        return Err(anyhow::anyhow!(
+            "Secret {} sets key '{}' in both string_data and data",
+            config.name,
+            key
+        ));
+    }
+
+    let secret_type = config.secret_type.as_deref().unwrap_or(DEFAULT_SECRET_TYPE);
+    if secret_type == "kubernetes.io/dockerconfigjson"
+        && !string_data.contains_key(DOCKER_CONFIG_JSON_KEY)
+        && !data.contains_key(DOCKER_CONFIG_JSON_KEY)
+    {
+        return Err(anyhow::anyhow!(
+            "Secret {} of type kubernetes.io/dockerconfigjson requires a '{}' key",
+            config.name,
+            DOCKER_CONFIG_JSON_KEY
+        ));
+    }
+
+    let secret = Secret {
+        metadata: build_object_meta(config, None),
+        type_: Some(secret_type.to_string()),
+        // stringData is emitted as written; data values are base64-encoded on serialization
+        string_data: (!string_data.is_empty()).then(|| string_data.into_iter().collect()),
+        data: (!data.is_empty()).then(|| {
+            data.into_iter()
+                .map(|(key, value)| (key, ByteString(value.into_bytes())))
+                .collect()
+        }),
+        ..Default::default()
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&secret).context("Failed to serialize Secret to YAML")?;
+
+    Ok(yaml)
+}
+
+// Maps a route matcher operator to the CRD's enum
+fn alertmanager_match_type(match_type: &str) -> Result<AlertmanagerConfigRouteMatchersMatchType> {
+    match match_type {
+        "=" => Ok(AlertmanagerConfigRouteMatchersMatchType::KopiumVariant1),
+        "!=" => Ok(AlertmanagerConfigRouteMatchersMatchType::KopiumVariant0),
+        "=~" => Ok(AlertmanagerConfigRouteMatchersMatchType::KopiumVariant2),
+        "!~" => Ok(AlertmanagerConfigRouteMatchersMatchType::KopiumVariant3),
+        match_type => Err(anyhow::anyhow!(
+            "Unsupported route matcher match_type '{}' (expected =, !=, =~, or !~)",
+            match_type
+        )),
+    }
+}
+
+pub fn generate_alertmanager_config_manifest(config: &KamutConfig) -> Result<String> {
+    let route_config = config
+        .route
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("route is required for AlertmanagerConfig"))?;
+    let receiver_configs = config
+        .receivers
+        .as_deref()
+        .filter(|receivers| !receivers.is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!("receivers must list at least one receiver for AlertmanagerConfig")
+        })?;
+
+    if !receiver_configs
+        .iter()
+        .any(|receiver| receiver.name == route_config.receiver)
+    {
+        return Err(anyhow::anyhow!(
+            "route.receiver '{}' is not listed in receivers",
+            route_config.receiver
+        ));
+    }
+
+    let matchers = route_config
+        .matchers
+        .iter()
+        .map(|matcher| {
+            Ok(AlertmanagerConfigRouteMatchers {
+                name: matcher.name.clone(),
+                value: Some(matcher.value.clone()),
+                match_type: Some(alertmanager_match_type(
+                    matcher.match_type.as_deref().unwrap_or("="),
+                )?),
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let route = AlertmanagerConfigRoute {
+        receiver: Some(route_config.receiver.clone()),
+        matchers: (!matchers.is_empty()).then_some(matchers),
+        group_by: (!route_config.group_by.is_empty()).then(|| route_config.group_by.clone()),
+        group_wait: route_config.group_wait.clone(),
+        group_interval: route_config.group_interval.clone(),
+        repeat_interval: route_config.repeat_interval.clone(),
+        ..Default::default()
+    };
+
+    let mut receivers = Vec::new();
+    for receiver_config in receiver_configs {
+        let mut webhook_configs = Vec::new();
+        for webhook in &receiver_config.webhook_configs {
+            if webhook.url.is_some() == webhook.url_secret.is_some() {
+                return Err(anyhow::anyhow!(
+                    "webhook of receiver '{}' must set exactly one of url and url_secret",
+                    receiver_config.name
+                ));
+            }
+            webhook_configs.push(AlertmanagerConfigReceiversWebhookConfigs {
+                url: webhook.url.clone(),
+                url_secret: webhook.url_secret.as_ref().map(|secret| {
+                    AlertmanagerConfigReceiversWebhookConfigsUrlSecret {
+                        name: Some(secret.name.clone()),
+                        key: secret.key.clone(),
+                        optional: None,
+                    }
+                }),
+                send_resolved: webhook.send_resolved,
+                ..Default::default()
+            });
+        }
+
+        let slack_configs = receiver_config
+            .slack_configs
+            .iter()
+            .map(|slack| AlertmanagerConfigReceiversSlackConfigs {
+                api_url: Some(AlertmanagerConfigReceiversSlackConfigsApiUrl {
+                    name: Some(slack.api_url_secret.name.clone()),
+                    key: slack.api_url_secret.key.clone(),
+                    optional: None,
+                }),
+                channel: slack.channel.clone(),
+                send_resolved: slack.send_resolved,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        receivers.push(AlertmanagerConfigReceivers {
+            name: receiver_config.name.clone(),
+            webhook_configs: (!webhook_configs.is_empty()).then_some(webhook_configs),
+            slack_configs: (!slack_configs.is_empty()).then_some(slack_configs),
+            ..Default::default()
+        });
+    }
+
+    let alertmanager_config = AlertmanagerConfig {
+        metadata: build_object_meta(config, None),
+        spec: AlertmanagerConfigSpec {
+            route: Some(route),
+            receivers: Some(receivers),
+            ..Default::default()
+        },
+    };
+
+    serialize_manifest(&alertmanager_config)
+        .context("Failed to serialize AlertmanagerConfig to YAML")
+}
+
+pub fn generate_endpoints_manifest(config: &KamutConfig) -> Result<String> {
+    let endpoints_config = config
+        .endpoints
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("endpoints is required for Endpoints"))?;
+
+    if endpoints_config.addresses.is_empty() {
+        return Err(anyhow::anyhow!(
+            "endpoints.addresses must list at least one address"
+        ));
+    }
+    if let Some(address) = endpoints_config
+        .addresses
+        .iter()
+        .find(|address| address.trim().is_empty())
+    {
+        return Err(anyhow::anyhow!(
+            "endpoints.addresses contains an empty address: '{}'",
+            address
+        ));
+    }
+
+    let addresses = endpoints_config
+        .addresses
+        .iter()
+        .map(|address| EndpointAddress {
+            ip: address.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let ports = endpoints_config
+        .ports
+        .iter()
+        .map(|port| EndpointPort {
+            name: port.name.clone(),
+            port: port.port,
+            protocol: Some(port.protocol.clone().unwrap_or_else(|| "TCP".to_string())),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    // The name must match the selectorless Service the addresses back
+    let endpoints = Endpoints {
+        metadata: build_object_meta(config, None),
+        subsets: Some(vec![EndpointSubset {
+            addresses: Some(addresses),
+            ports: if ports.is_empty() { None } else { Some(ports) },
+            ..Default::default()
+        }]),
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&endpoints).context("Failed to serialize Endpoints to YAML")?;
+
+    Ok(yaml)
+}
+
+/// Generates a NetworkPolicy selecting the pods labeled `app: <name>`. Only the directions
+/// listed in `network_policy` are restricted; an empty list denies all their traffic.
+pub fn generate_network_policy_manifest(config: &KamutConfig) -> Result<String> {
+    let policy_config = config
+        .network_policy
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("network_policy is required for NetworkPolicy"))?;
+    if policy_config.ingress.is_none() && policy_config.egress.is_none() {
+        return Err(anyhow::anyhow!(
+            "network_policy must list ingress or egress rules; use an empty list to deny all traffic"
+        ));
+    }
+
+    let mut policy_types = Vec::new();
+    let ingress = match &policy_config.ingress {
+        Some(rules) => {
+            policy_types.push("Ingress".to_string());
+            let rules = rules
+                .iter()
+                .map(|rule| -> Result<NetworkPolicyIngressRule> {
+                    Ok(NetworkPolicyIngressRule {
+                        from: build_network_policy_peers(&rule.from)?,
+                        ports: build_network_policy_ports(&rule.ports)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Some(rules)
+        }
+        None => None,
+    };
+    let egress = match &policy_config.egress {
+        Some(rules) => {
+            policy_types.push("Egress".to_string());
+            let rules = rules
+                .iter()
+                .map(|rule| -> Result<NetworkPolicyEgressRule> {
+                    Ok(NetworkPolicyEgressRule {
+                        to: build_network_policy_peers(&rule.to)?,
+                        ports: build_network_policy_ports(&rule.ports)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Some(rules)
+        }
+        None => None,
+    };
+
+    let mut match_labels = BTreeMap::new();
+    match_labels.insert("app".to_string(), config.name.clone());
+
+    let network_policy = NetworkPolicy {
+        metadata: build_object_meta(config, None),
+        spec: Some(NetworkPolicySpec {
+            pod_selector: LabelSelector {
+                match_labels: Some(match_labels),
+                ..Default::default()
+            },
+            ingress,
+            egress,
+            policy_types: Some(policy_types),
+        }),
+    };
+
+    // Serialize to YAML
+    let yaml =
+        serialize_manifest(&network_policy).context("Failed to serialize NetworkPolicy to YAML")?;
+
+    Ok(yaml)
+}
+
+// Maps the sources or destinations of a rule; none allows every peer
+fn build_network_policy_peers(
+    peers: &[NetworkPolicyPeerConfig],
+) -> Result<Option<Vec<NetworkPolicyPeer>>> {
+    if peers.is_empty() {
+        return Ok(None);
+    }
+
+    let selector = |labels: &Option<HashMap<String, String>>| {
+        labels.as_ref().map(|labels| LabelSelector {
+            match_labels: Some(labels.clone().into_iter().collect()),
+            ..Default::default()
+        })
+    };
+    peers
+        .iter()
+        .map(|peer| {
+            let has_selector = peer.pod_selector.is_some() || peer.namespace_selector.is_some();
+            let ip_block = match &peer.cidr {
+                Some(_) if has_selector => {
+                    return Err(anyhow::anyhow!(
+                        "network_policy peer sets both cidr and a selector; use separate peers"
+                    ));
+                }
+                Some(cidr) => Some(IPBlock {
+                    cidr: cidr.clone(),
+                    except: if peer.except.is_empty() {
+                        None
+                    } else {
+                        Some(peer.except.clone())
+                    },
+                }),
+                None if !peer.except.is_empty() => {
+                    return Err(anyhow::anyhow!(
+                        "network_policy peer sets except without cidr"
+                    ));
+                }
+                None if !has_selector => {
+                    return Err(anyhow::anyhow!(
+                        "network_policy peer requires pod_selector, namespace_selector, or cidr"
+                    ));
+                }
+                None => None,
+            };
+            Ok(NetworkPolicyPeer {
+                ip_block,
+                namespace_selector: selector(&peer.namespace_selector),
+                pod_selector: selector(&peer.pod_selector),
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+// Maps the ports of a rule; none allows every port
+fn build_network_policy_ports(
+    ports: &[NetworkPolicyPortConfig],
+) -> Result<Option<Vec<NetworkPolicyPort>>> {
+    if ports.is_empty() {
+        return Ok(None);
+    }
 
-    Ok(yaml)
+    ports
+        .iter()
+        .map(|port| {
+            let protocol = port.protocol.clone().unwrap_or_else(|| "TCP".to_string());
+            if !["TCP", "UDP", "SCTP"].contains(&protocol.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid network_policy port protocol '{}': expected TCP, UDP, or SCTP",
+                    protocol
+                ));
+            }
+            Ok(NetworkPolicyPort {
+                port: Some(port.port.clone()),
+                protocol: Some(protocol),
+                end_port: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
 }
 
-pub fn generate_deployment_manifest(config: &KamutConfig) -> Result<String> {
-    // Create metadata
-    let mut metadata = ObjectMeta::default();
-    metadata.name = Some(config.name.clone());
-
-    // Set namespace if provided
-    if let Some(namespace) = &config.namespace {
-        metadata.namespace = Some(namespace.clone());
+/// Generates an Alertmanager custom resource with a Service in front of its pods
+pub fn generate_alertmanager_manifests(config: &KamutConfig) -> Result<Vec<String>> {
+    if config.image.is_none() {
+        return Ok(Vec::new());
     }
 
-    // Create labels
-    let mut labels = BTreeMap::new();
-    labels.insert("app".to_string(), config.name.clone());
-    metadata.labels = Some(labels.clone());
+    Ok(vec![
+        generate_alertmanager_manifest(config)?,
+        generate_alertmanager_service(config)?,
+    ])
+}
+
+/// Generates the `monitoring.coreos.com/v1` Alertmanager run by the Prometheus Operator
+pub fn generate_alertmanager_manifest(config: &KamutConfig) -> Result<String> {
+    let metadata = build_object_meta(config, None);
 
-    // Ensure image is available
     let image = config
         .image
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Image is required for Deployment"))?;
+        .ok_or_else(|| anyhow::anyhow!("Image is required for Alertmanager"))?;
 
-    // Create container
-    let mut container = Container {
-        name: config.name.clone(),
+    let mut alertmanager_spec = AlertmanagerSpec {
         image: Some(image.clone()),
+        replicas: config.replicas,
         ..Default::default()
     };
 
-    // Add environment variables if available
-    if let Some(env_vars) = &config.env {
-        let mut env = Vec::new();
-        for (key, value) in env_vars {
-            env.push(EnvVar {
-                name: key.clone(),
-                value: Some(value.clone()),
-                ..Default::default()
-            });
+    // Alertmanager only accepts Go durations up to hours, unlike Prometheus
+    if let Some(retention) = &config.retention {
+        if !is_valid_alertmanager_retention(retention) {
+            return Err(anyhow::anyhow!(
+                "Invalid Alertmanager retention '{}': expected a duration in h, m, s, or ms, e.g. 120h",
+                retention
+            ));
         }
-        container.env = Some(env);
+        alertmanager_spec.retention = Some(retention.clone());
     }
 
-    // Add resource requirements if available
+    // Set resource requirements if available
     if let Some(resources) = &config.resources {
-        let mut resource_requirements = ResourceRequirements::default();
+        validate_resources(resources)?;
 
-        // Add requests
+        let mut alertmanager_resources = AlertmanagerResources::default();
         if let Some(requests) = &resources.requests {
-            let mut request_map = BTreeMap::new();
+            let mut requests_map = BTreeMap::new();
             if let Some(cpu) = &requests.cpu {
-                request_map.insert("cpu".to_string(), Quantity(cpu.clone()));
+                requests_map.insert("cpu".to_string(), IntOrString::String(cpu.clone()));
             }
             if let Some(memory) = &requests.memory {
-                request_map.insert("memory".to_string(), Quantity(memory.clone()));
+                requests_map.insert("memory".to_string(), IntOrString::String(memory.clone()));
             }
-            resource_requirements.requests = Some(request_map);
+            alertmanager_resources.requests = Some(requests_map);
         }
-
-        // Add limits
         if let Some(limits) = &resources.limits {
-            let mut limit_map = BTreeMap::new();
+            let mut limits_map = BTreeMap::new();
             if let Some(cpu) = &limits.cpu {
-                limit_map.insert("cpu".to_string(), Quantity(cpu.clone()));
+                limits_map.insert("cpu".to_string(), IntOrString::String(cpu.clone()));
             }
             if let Some(memory) = &limits.memory {
-                limit_map.insert("memory".to_string(), Quantity(memory.clone()));
+                limits_map.insert("memory".to_string(), IntOrString::String(memory.clone()));
             }
-            resource_requirements.limits = Some(limit_map);
+            alertmanager_resources.limits = Some(limits_map);
         }
+        alertmanager_spec.resources = Some(alertmanager_resources);
+    }
+
+    // Set storage if available; like Prometheus, Alertmanager manages a single volumeClaimTemplate
+    if let Some(storage_config) = &config.storage {
+        let storage_cfg = match storage_config.entries() {
+            [storage_cfg] => storage_cfg,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Alertmanager supports exactly one storage entry"
+                ))
+            }
+        };
+
+        let mut requests = BTreeMap::new();
+        requests.insert(
+            "storage".to_string(),
+            IntOrString::String(storage_cfg.size.clone()),
+        );
 
-        container.resources = Some(resource_requirements);
+        alertmanager_spec.storage = Some(AlertmanagerStorage {
+            volume_claim_template: Some(AlertmanagerStorageVolumeClaimTemplate {
+                spec: Some(AlertmanagerStorageVolumeClaimTemplateSpec {
+                    storage_class_name: storage_cfg.class_name.clone(),
+                    resources: Some(AlertmanagerStorageVolumeClaimTemplateSpecResources {
+                        requests: Some(requests),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
     }
 
-    // Create pod spec
-    let mut pod_spec = PodSpec {
-        containers: vec![container],
-        ..Default::default()
+    let alertmanager = Alertmanager {
+        metadata,
+        spec: alertmanager_spec,
+        status: None,
     };
 
-    // Add nodeSelector if available
-    if let Some(node_selector) = &config.node_selector {
-        let node_selector_map = node_selector.clone().into_iter().collect();
-        pod_spec.node_selector = Some(node_selector_map);
-    };
+    let yaml =
+        serialize_manifest(&alertmanager).context("Failed to serialize alertmanager to YAML")?;
 
-    // Create pod template spec
-    let mut template_metadata = ObjectMeta::default();
-    template_metadata.labels = Some(labels);
+    Ok(yaml)
+}
 
-    let pod_template_spec = PodTemplateSpec {
-        metadata: Some(template_metadata),
-        spec: Some(pod_spec),
-    };
+// Checks a retention against the Alertmanager duration grammar: `0`, or hours, minutes,
+// seconds, and milliseconds in that order, e.g. `120h` or `1h30m`
+fn is_valid_alertmanager_retention(value: &str) -> bool {
+    if value == "0" {
+        return true;
+    }
+    const UNITS: [&str; 4] = ["h", "m", "s", "ms"];
+    let mut rest = value;
+    let mut next_unit = 0;
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let rest_unit = &rest[digits..];
+        let unit_len = if rest_unit.starts_with("ms") { 2 } else { 1 };
+        let Some(unit) = rest_unit.get(..unit_len).filter(|_| digits > 0) else {
+            return false;
+        };
+        let Some(index) = UNITS[next_unit..].iter().position(|known| *known == unit) else {
+            return false;
+        };
+        next_unit += index + 1;
+        rest = &rest_unit[unit_len..];
+    }
+    !value.is_empty()
+}
 
-    // Create selector
-    let mut match_labels = BTreeMap::new();
-    match_labels.insert("app".to_string(), config.name.clone());
-    let selector = LabelSelector {
-        match_labels: Some(match_labels),
-        ..Default::default()
-    };
+/// Generates the Service of an Alertmanager, selecting the pods the operator labels
+/// `alertmanager: <name>` on the web port 9093
+pub fn generate_alertmanager_service(config: &KamutConfig) -> Result<String> {
+    let metadata = build_object_meta(config, Some(&format!("alertmanager-{}", config.name)));
 
-    // Create deployment spec
-    let deployment_spec = DeploymentSpec {
-        replicas: config.replicas, // Use replicas from config
-        selector,
-        template: pod_template_spec,
-        ..Default::default()
-    };
+    let mut selector = BTreeMap::new();
+    selector.insert("alertmanager".to_string(), config.name.clone());
 
-    // Create deployment
-    let deployment = Deployment {
+    let service = Service {
         metadata,
-        spec: Some(deployment_spec),
-        ..Default::default()
+        spec: Some(ServiceSpec {
+            selector: Some(selector),
+            ports: Some(vec![ServicePort {
+                name: Some("web".to_string()),
+                port: 9093,
+                target_port: Some(IntOrString::Int(9093)),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            }]),
+            type_: Some("ClusterIP".to_string()),
+            ..Default::default()
+        }),
+        status: None,
     };
 
-    // Serialize to YAML
-    let yaml =
-        serde_yaml::to_string(&deployment).context("Failed to serialize deployment to YAML")?;
+    let yaml = serialize_manifest(&service).context("Failed to serialize service to YAML")?;
 
     Ok(yaml)
 }
 
-pub fn generate_prometheus_manifest(config: &KamutConfig) -> Result<String> {
-    // Create metadata
-    let mut metadata = ObjectMeta::default();
-    metadata.name = Some(config.name.clone());
+/// Generates Prometheus (the custom resource, or the plain Deployment of `mode: raw`) with
+/// its Service, optional self-scrape ServiceMonitor and Ingress, and RBAC resources
+pub fn generate_prometheus_manifests(config: &KamutConfig) -> Result<Vec<String>> {
+    if config.image.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut manifests = Vec::new();
+    match config.mode.as_deref().unwrap_or("operator") {
+        "operator" => manifests.push(generate_prometheus_manifest(config)?),
+        "raw" if config.api_version_override.is_some() => {
+            return Err(anyhow::anyhow!(
+                "api_version_override requires Prometheus mode 'operator'; raw mode generates no custom resource"
+            ));
+        }
+        "raw" if config.self_monitor.unwrap_or(false) => {
+            return Err(anyhow::anyhow!(
+                "self_monitor requires Prometheus mode 'operator'; raw mode already scrapes itself"
+            ));
+        }
+        "raw" if !config.external_labels.is_empty() || !config.remote_write.is_empty() => {
+            return Err(anyhow::anyhow!(
+                "external_labels and remote_write require Prometheus mode 'operator'"
+            ));
+        }
+        // Plain Deployment for clusters without the Prometheus Operator
+        "raw" => manifests.extend(generate_prometheus_raw_manifests(config)?),
+        mode => {
+            return Err(anyhow::anyhow!(
+                "Invalid Prometheus mode '{}': expected 'operator' or 'raw'",
+                mode
+            ));
+        }
+    }
+
+    // Generate Service for Prometheus
+    manifests.push(generate_prometheus_service(config)?);
 
-    // Set namespace if provided
-    if let Some(namespace) = &config.namespace {
-        metadata.namespace = Some(namespace.clone());
+    // Generate the self-scrape ServiceMonitor if enabled
+    if config.self_monitor.unwrap_or(false) {
+        manifests.push(generate_prometheus_service_monitor(config)?);
     }
 
-    // Create labels
-    let mut labels = BTreeMap::new();
-    labels.insert("app".to_string(), config.name.clone());
-    metadata.labels = Some(labels.clone());
+    // Generate Ingress if specified
+    if let Some(ingress_config) = &config.ingress {
+        manifests.push(generate_prometheus_ingress(config, ingress_config)?);
+    }
 
-    // Create Prometheus spec
-    let mut prometheus_spec = PrometheusSpec::default();
+    // Generate ServiceAccount, ClusterRole, and ClusterRoleBinding by default
+    // If service_account is specified, use its configuration, otherwise use defaults
+    manifests.extend(generate_prometheus_service_account(config)?);
+
+    Ok(manifests)
+}
+
+pub fn generate_prometheus_manifest(config: &KamutConfig) -> Result<String> {
+    // Create metadata
+    let metadata = build_object_meta(config, None);
 
-    // Set replicas
-    prometheus_spec.replicas = config.replicas;
+    // Create Prometheus spec
+    let mut prometheus_spec = PrometheusSpec {
+        replicas: config.replicas, // Set replicas
+        ..Default::default()
+    };
 
     // Add podMetadata with app label
     use kube_custom_resources_rs::monitoring_coreos_com::v1::prometheuses::PrometheusPodMetadata;
@@ -411,16 +3460,13 @@ pub fn generate_prometheus_manifest(config: &KamutConfig) -> Result<String> {
         name: None,
     });
 
-    // Set retention (default to 15d if not provided)
-    prometheus_spec.retention = Some(
-        config
-            .retention
-            .clone()
-            .unwrap_or_else(|| "15d".to_string()),
-    );
+    // Set retention by time and size
+    (prometheus_spec.retention, prometheus_spec.retention_size) = prometheus_retention(config)?;
 
     // Set resource requirements if available
     if let Some(resources) = &config.resources {
+        validate_resources(resources)?;
+
         // Create PrometheusResources
         let mut prometheus_resources = PrometheusResources::default();
 
@@ -461,18 +3507,33 @@ pub fn generate_prometheus_manifest(config: &KamutConfig) -> Result<String> {
     prometheus_spec.image = Some(image.clone());
 
     // Set security context
-    prometheus_spec.security_context = Some(PrometheusSecurityContext {
-        fs_group: Some(2000),
-        run_as_non_root: Some(true),
-        run_as_user: Some(1000),
-        ..Default::default()
-    });
+    prometheus_spec.security_context =
+        build_prometheus_security_context(config)?.map(|security_context| {
+            PrometheusSecurityContext {
+                fs_group: security_context.fs_group,
+                run_as_group: security_context.run_as_group,
+                run_as_non_root: security_context.run_as_non_root,
+                run_as_user: security_context.run_as_user,
+                ..Default::default()
+            }
+        });
 
-    // Set serviceMonitor to null
-    prometheus_spec.service_monitor_namespace_selector = None;
-    prometheus_spec.service_monitor_selector = None;
-    prometheus_spec.pod_monitor_namespace_selector = None;
+    // Select no ServiceMonitors, except the self-scrape one when enabled
+    prometheus_spec.service_monitor_selector = if config.self_monitor.unwrap_or(false) {
+        Some(PrometheusServiceMonitorSelector {
+            match_labels: Some(prometheus_self_monitor_labels(config)),
+            match_expressions: None,
+        })
+    } else {
+        None
+    };
     prometheus_spec.pod_monitor_selector = None;
+
+    // Configure the namespaces in which ServiceMonitors and PodMonitors are discovered
+    let (service_monitor_namespace_selector, pod_monitor_namespace_selector) =
+        generate_monitor_namespace_selectors(config)?;
+    prometheus_spec.service_monitor_namespace_selector = service_monitor_namespace_selector;
+    prometheus_spec.pod_monitor_namespace_selector = pod_monitor_namespace_selector;
     
     // Configure ScrapeConfig selectors to match all ScrapeConfigs in the current namespace
     // Reference: https://prometheus-operator.dev/docs/operator/api/#prometheusnamespaceselector
@@ -484,115 +3545,507 @@ pub fn generate_prometheus_manifest(config: &KamutConfig) -> Result<String> {
         match_labels: Some(BTreeMap::new()),
         match_expressions: None,
     };
-    prometheus_spec.scrape_config_selector = Some(empty_selector); // Empty selector matches all objects
+    prometheus_spec.scrape_config_selector = Some(empty_selector); // Empty selector matches all objects
+
+    // Set scrape classes (default TLS settings for scrapes) if available
+    if let Some(scrape_classes) = &config.scrape_classes {
+        let mut classes = Vec::new();
+        for (index, scrape_class) in scrape_classes.iter().enumerate() {
+            let name = scrape_class
+                .name
+                .clone()
+                .filter(|name| !name.is_empty())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("scrape_classes entry {} requires a name", index + 1)
+                })?;
+
+            classes.push(PrometheusScrapeClasses {
+                name,
+                default: scrape_class.default,
+                tls_config: scrape_class.tls_config.as_ref().map(|tls| {
+                    PrometheusScrapeClassesTlsConfig {
+                        ca_file: tls.ca_file.clone(),
+                        cert_file: tls.cert_file.clone(),
+                        key_file: tls.key_file.clone(),
+                        ..Default::default()
+                    }
+                }),
+                ..Default::default()
+            });
+        }
+        prometheus_spec.scrape_classes = Some(classes);
+    }
+
+    // Set external labels and remote-write endpoints if available
+    if !config.external_labels.is_empty() {
+        prometheus_spec.external_labels =
+            Some(config.external_labels.clone().into_iter().collect());
+    }
+    if !config.remote_write.is_empty() {
+        let mut remote_writes = Vec::new();
+        for (index, remote_write) in config.remote_write.iter().enumerate() {
+            if !remote_write.url.starts_with("http://") && !remote_write.url.starts_with("https://")
+            {
+                return Err(anyhow::anyhow!(
+                    "remote_write entry {} requires an http:// or https:// url, got '{}'",
+                    index + 1,
+                    remote_write.url
+                ));
+            }
+            remote_writes.push(PrometheusRemoteWrite {
+                url: remote_write.url.clone(),
+                name: remote_write.name.clone(),
+                remote_timeout: remote_write.remote_timeout.clone(),
+                headers: remote_write
+                    .headers
+                    .as_ref()
+                    .map(|headers| headers.clone().into_iter().collect()),
+                ..Default::default()
+            });
+        }
+        prometheus_spec.remote_write = Some(remote_writes);
+    }
+
+    // Set storage if available
+    if let Some(storage_config) = &config.storage {
+        // Prometheus manages a single volumeClaimTemplate
+        let storage_cfg = match storage_config.entries() {
+            [storage_cfg] => storage_cfg,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Prometheus supports exactly one storage entry"
+                ))
+            }
+        };
+
+        let mut requests = BTreeMap::new();
+        requests.insert(
+            "storage".to_string(),
+            IntOrString::String(storage_cfg.size.clone()),
+        );
+
+        let storage = PrometheusStorage {
+            volume_claim_template: Some(PrometheusStorageVolumeClaimTemplate {
+                spec: Some(PrometheusStorageVolumeClaimTemplateSpec {
+                    storage_class_name: storage_cfg.class_name.clone(),
+                    resources: Some(PrometheusStorageVolumeClaimTemplateSpecResources {
+                        requests: Some(requests.clone()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // Set storage spec in Prometheus spec
+        prometheus_spec.storage = Some(storage);
+    }
+
+    // Set nodeSelector if available
+    if let Some(node_selector) = &config.node_selector {
+        // Sorted by key, so the tolerations come out in a stable order
+        let node_selector: BTreeMap<String, String> = node_selector.clone().into_iter().collect();
+
+        let tolerations = Some(
+            node_selector
+                .iter()
+                .map(|(key, value)| PrometheusTolerations {
+                    effect: Some("NoSchedule".to_string()),
+                    key: Some(key.clone()),
+                    operator: Some("Equal".to_string()),
+                    value: Some(value.clone()),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+
+        prometheus_spec.node_selector = Some(node_selector);
+        prometheus_spec.tolerations = tolerations;
+    }
+
+    // Set serviceAccountName
+    // If service_account is specified, check if it should be created, otherwise set by default
+    let should_create_sa = match &config.service_account {
+        Some(sa_config) => sa_config.create,
+        None => true, // Create by default if not specified
+    };
+
+    if should_create_sa {
+        prometheus_spec.service_account_name = Some(format!("prometheus-{}", config.name));
+    }
+
+    // Create Prometheus
+    let prometheus = Prometheus {
+        metadata,
+        spec: prometheus_spec,
+        status: None,
+    };
+
+    // Serialize to YAML
+    let yaml = serialize_manifest(&prometheus).context("Failed to serialize prometheus to YAML")?;
+
+    Ok(yaml)
+}
+
+// Time and size retention of Prometheus. Both are emitted when set, since Prometheus
+// deletes data once either limit is reached; without either, data is kept for 15d.
+fn prometheus_retention(config: &KamutConfig) -> Result<(Option<String>, Option<String>)> {
+    if let Some(retention_size) = &config.retention_size {
+        if !is_valid_byte_size(retention_size) {
+            return Err(anyhow::anyhow!(
+                "Invalid retention_size '{}': expected a number with a unit of B, KB, MB, GB, TB, PB, or EB, e.g. 50GB",
+                retention_size
+            ));
+        }
+    }
+
+    match (&config.retention, &config.retention_size) {
+        (None, None) => Ok((Some("15d".to_string()), None)),
+        (retention, retention_size) => Ok((retention.clone(), retention_size.clone())),
+    }
+}
+
+/// Checks a value against the Prometheus byte size grammar used by `retentionSize`:
+/// `0`, or a decimal number followed by one of B, KB, MB, GB, TB, PB, and EB
+pub fn is_valid_byte_size(value: &str) -> bool {
+    if value == "0" {
+        return true;
+    }
+    let Some(number) = ["KB", "MB", "GB", "TB", "PB", "EB", "B"]
+        .iter()
+        .find_map(|unit| value.strip_suffix(unit))
+    else {
+        return false;
+    };
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, "0"));
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    is_digits(integer) && is_digits(fraction)
+}
+
+// Default prometheus.yml for Prometheus in raw mode: scrape Prometheus itself
+const RAW_PROMETHEUS_CONFIG: &str = "global:
+  scrape_interval: 30s
+  evaluation_interval: 30s
+scrape_configs:
+  - job_name: prometheus
+    static_configs:
+      - targets:
+          - localhost:9090
+";
+
+// Function to generate a plain Prometheus Deployment, its ConfigMap, and an optional PVC
+// Used by `mode: raw` for clusters without the Prometheus Operator
+pub fn generate_prometheus_raw_manifests(config: &KamutConfig) -> Result<Vec<String>> {
+    let mut manifests = Vec::new();
+
+    // Ensure image is available
+    let image = config
+        .image
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Image is required for Prometheus"))?;
+
+    // Labels match both the app label and the `prometheus` selector of the generated Service
+    let mut labels = build_object_meta(config, None).labels.unwrap_or_default();
+    labels.insert("prometheus".to_string(), config.name.clone());
+
+    // Create ConfigMap holding prometheus.yml
+    let config_map_name = format!("prometheus-{}-config", config.name);
+    let mut data = BTreeMap::new();
+    data.insert(
+        "prometheus.yml".to_string(),
+        RAW_PROMETHEUS_CONFIG.to_string(),
+    );
+    let config_map = ConfigMap {
+        metadata: ObjectMeta {
+            labels: Some(labels.clone()),
+            ..build_object_meta(config, Some(&config_map_name))
+        },
+        data: Some(data),
+        ..Default::default()
+    };
+    let cm_yaml =
+        serialize_manifest(&config_map).context("Failed to serialize ConfigMap to YAML")?;
+    manifests.push(cm_yaml);
 
-    // Set storage if available
-    if let Some(storage_cfg) = &config.storage {
-        let mut requests = BTreeMap::new();
-        requests.insert(
-            "storage".to_string(),
-            IntOrString::String(storage_cfg.size.clone()),
-        );
+    // Create PVC from storage, falling back to an emptyDir volume
+    let data_volume = match &config.storage {
+        Some(storage_config) => {
+            let storage_cfg = match storage_config.entries() {
+                [storage_cfg] => storage_cfg,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Prometheus supports exactly one storage entry"
+                    ))
+                }
+            };
 
-        let storage = PrometheusStorage {
-            volume_claim_template: Some(PrometheusStorageVolumeClaimTemplate {
-                spec: Some(PrometheusStorageVolumeClaimTemplateSpec {
-                    storage_class_name: Some(storage_cfg.class_name.clone()),
-                    resources: Some(PrometheusStorageVolumeClaimTemplateSpecResources {
-                        requests: Some(requests.clone()),
+            let claim_name = format!("prometheus-{}-data", config.name);
+            let mut requests = BTreeMap::new();
+            requests.insert("storage".to_string(), Quantity(storage_cfg.size.clone()));
+            let pvc = PersistentVolumeClaim {
+                metadata: ObjectMeta {
+                    labels: Some(labels.clone()),
+                    ..build_object_meta(config, Some(&claim_name))
+                },
+                spec: Some(PersistentVolumeClaimSpec {
+                    access_modes: Some(vec!["ReadWriteOnce".to_string()]),
+                    storage_class_name: storage_cfg.class_name.clone(),
+                    resources: Some(VolumeResourceRequirements {
+                        requests: Some(requests),
                         ..Default::default()
                     }),
                     ..Default::default()
                 }),
+                status: None,
+            };
+            let pvc_yaml = serialize_manifest(&pvc)
+                .context("Failed to serialize PersistentVolumeClaim to YAML")?;
+            manifests.push(pvc_yaml);
+
+            Volume {
+                name: "data".to_string(),
+                persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                    claim_name,
+                    read_only: None,
+                }),
                 ..Default::default()
-            }),
+            }
+        }
+        None => Volume {
+            name: "data".to_string(),
+            empty_dir: Some(EmptyDirVolumeSource::default()),
             ..Default::default()
-        };
+        },
+    };
 
-        // Set storage spec in Prometheus spec
-        prometheus_spec.storage = Some(storage);
+    // Create Prometheus container
+    let mut args = vec![
+        "--config.file=/etc/prometheus/prometheus.yml".to_string(),
+        "--storage.tsdb.path=/prometheus".to_string(),
+    ];
+    let (retention, retention_size) = prometheus_retention(config)?;
+    if let Some(retention) = retention {
+        args.push(format!("--storage.tsdb.retention.time={}", retention));
     }
+    if let Some(retention_size) = retention_size {
+        args.push(format!("--storage.tsdb.retention.size={}", retention_size));
+    }
+    let container = Container {
+        name: "prometheus".to_string(),
+        image: Some(image.clone()),
+        args: Some(args),
+        ports: Some(vec![ContainerPort {
+            name: Some("web".to_string()),
+            container_port: 9090,
+            ..Default::default()
+        }]),
+        resources: config
+            .resources
+            .as_ref()
+            .map(build_resource_requirements)
+            .transpose()?,
+        volume_mounts: Some(vec![
+            VolumeMount {
+                name: "config".to_string(),
+                mount_path: "/etc/prometheus".to_string(),
+                ..Default::default()
+            },
+            VolumeMount {
+                name: "data".to_string(),
+                mount_path: "/prometheus".to_string(),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    };
 
-    // Set nodeSelector if available
-    if let Some(node_selector) = &config.node_selector {
-        let node_selector_map = node_selector.clone().into_iter().collect();
-        prometheus_spec.node_selector = Some(node_selector_map);
-
-        let tolerations = Some(
-            node_selector
-                .iter()
-                .map(|(key, value)| PrometheusTolerations {
-                    effect: Some("NoSchedule".to_string()),
-                    key: Some(key.clone()),
-                    operator: Some("Equal".to_string()),
-                    value: Some(value.clone()),
+    // Create pod spec
+    let mut pod_spec = PodSpec {
+        containers: vec![container],
+        volumes: Some(vec![
+            Volume {
+                name: "config".to_string(),
+                config_map: Some(ConfigMapVolumeSource {
+                    name: config_map_name,
                     ..Default::default()
-                })
-                .collect(),
-        );
+                }),
+                ..Default::default()
+            },
+            data_volume,
+        ]),
+        security_context: build_prometheus_security_context(config)?,
+        ..Default::default()
+    };
 
-        prometheus_spec.tolerations = tolerations;
+    // Add nodeSelector if available
+    if let Some(node_selector) = &config.node_selector {
+        pod_spec.node_selector = Some(node_selector.clone().into_iter().collect());
     }
 
-    // Set serviceAccountName
-    // If service_account is specified, check if it should be created, otherwise set by default
+    // Use the generated ServiceAccount unless its creation is disabled
     let should_create_sa = match &config.service_account {
         Some(sa_config) => sa_config.create,
-        None => true, // Create by default if not specified
+        None => true,
     };
-
     if should_create_sa {
-        prometheus_spec.service_account_name = Some(format!("prometheus-{}", config.name));
+        pod_spec.service_account_name = Some(format!("prometheus-{}", config.name));
     }
 
-    // Create Prometheus
-    let prometheus = Prometheus {
-        metadata,
-        spec: prometheus_spec,
-        status: None,
+    // Create deployment
+    let deployment = Deployment {
+        metadata: ObjectMeta {
+            labels: Some(labels.clone()),
+            ..build_object_meta(config, None)
+        },
+        spec: Some(DeploymentSpec {
+            replicas: config.replicas,
+            selector: LabelSelector {
+                match_labels: Some(labels.clone()),
+                ..Default::default()
+            },
+            template: PodTemplateSpec {
+                metadata: Some(ObjectMeta {
+                    labels: Some(labels),
+                    ..Default::default()
+                }),
+                spec: Some(pod_spec),
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
     };
+    let deployment_yaml =
+        serialize_manifest(&deployment).context("Failed to serialize deployment to YAML")?;
+    manifests.push(deployment_yaml);
 
-    // Serialize to YAML
-    let yaml =
-        serde_yaml::to_string(&prometheus).context("Failed to serialize prometheus to YAML")?;
+    Ok(manifests)
+}
 
-    Ok(yaml)
+// Function to build the ServiceMonitor/PodMonitor namespace selectors for Prometheus
+// A null selector matches the Prometheus namespace only, an empty selector matches all namespaces
+fn generate_monitor_namespace_selectors(
+    config: &KamutConfig,
+) -> Result<(
+    Option<PrometheusServiceMonitorNamespaceSelector>,
+    Option<PrometheusPodMonitorNamespaceSelector>,
+)> {
+    match &config.monitor_namespace {
+        // Default to the own namespace for safety
+        None => Ok((None, None)),
+        Some(MonitorNamespace::Mode(mode)) => match mode.as_str() {
+            "own" => Ok((None, None)),
+            "all" => Ok((
+                Some(PrometheusServiceMonitorNamespaceSelector {
+                    match_labels: Some(BTreeMap::new()),
+                    match_expressions: None,
+                }),
+                Some(PrometheusPodMonitorNamespaceSelector {
+                    match_labels: Some(BTreeMap::new()),
+                    match_expressions: None,
+                }),
+            )),
+            mode => Err(anyhow::anyhow!(
+                "Invalid monitor_namespace '{}': expected 'all', 'own' or a list of namespaces",
+                mode
+            )),
+        },
+        Some(MonitorNamespace::Names(names)) => {
+            if names.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "monitor_namespace list must contain at least one namespace"
+                ));
+            }
+
+            // Match namespaces by their immutable kubernetes.io/metadata.name label
+            Ok((
+                Some(PrometheusServiceMonitorNamespaceSelector {
+                    match_labels: None,
+                    match_expressions: Some(vec![
+                        PrometheusServiceMonitorNamespaceSelectorMatchExpressions {
+                            key: "kubernetes.io/metadata.name".to_string(),
+                            operator: "In".to_string(),
+                            values: Some(names.clone()),
+                        },
+                    ]),
+                }),
+                Some(PrometheusPodMonitorNamespaceSelector {
+                    match_labels: None,
+                    match_expressions: Some(vec![
+                        PrometheusPodMonitorNamespaceSelectorMatchExpressions {
+                            key: "kubernetes.io/metadata.name".to_string(),
+                            operator: "In".to_string(),
+                            values: Some(names.clone()),
+                        },
+                    ]),
+                }),
+            ))
+        }
+    }
+}
+
+// Annotations scoped to the generated Service
+fn service_annotations(service_config: &ServiceConfig) -> Option<BTreeMap<String, String>> {
+    service_config
+        .annotations
+        .as_ref()
+        .map(|annotations| annotations.clone().into_iter().collect())
 }
 
 // Function to generate Service for Prometheus
 pub fn generate_prometheus_service(config: &KamutConfig) -> Result<String> {
     // Create metadata
-    let mut metadata = ObjectMeta::default();
-    metadata.name = Some(format!("prometheus-{}", config.name));
-
-    // Set namespace if provided
-    if let Some(namespace) = &config.namespace {
-        metadata.namespace = Some(namespace.clone());
-    }
-
-    // Create labels
-    let mut labels = BTreeMap::new();
-    labels.insert("app".to_string(), config.name.clone());
-    metadata.labels = Some(labels.clone());
+    let mut metadata = build_object_meta(config, Some(&format!("prometheus-{}", config.name)));
+    metadata
+        .labels
+        .get_or_insert_with(BTreeMap::new)
+        .extend(prometheus_self_monitor_labels(config));
+    metadata.annotations = config.service.as_ref().and_then(service_annotations);
 
     // Create selector
     let mut selector = BTreeMap::new();
     selector.insert("prometheus".to_string(), config.name.clone());
 
-    // Create service port
-    let service_port = ServicePort {
-        name: Some("web".to_string()),
-        port: 9090,
-        target_port: Some(IntOrString::Int(9090)),
+    // Create service port; Prometheus listens on 9090
+    let service_config = config.service.as_ref();
+    let mut service_ports = vec![ServicePort {
+        name: Some(prometheus_port_name(config)),
+        port: prometheus_service_port(config),
+        target_port: Some(IntOrString::Int(
+            service_config
+                .and_then(|service| service.target_port)
+                .unwrap_or(9090),
+        )),
+        node_port: service_config.map(service_node_port).transpose()?.flatten(),
         protocol: Some("TCP".to_string()),
         ..Default::default()
-    };
+    }];
+
+    // Add extra named ports (e.g. the config-reloader on 8080)
+    if let Some(service_config) = &config.service {
+        for extra_port in &service_config.extra_ports {
+            service_ports.push(ServicePort {
+                name: Some(extra_port.name.clone()),
+                port: extra_port.port,
+                target_port: Some(IntOrString::Int(
+                    extra_port.target_port.unwrap_or(extra_port.port),
+                )),
+                protocol: Some("TCP".to_string()),
+                ..Default::default()
+            });
+        }
+    }
 
     // Create service spec
     let service_spec = ServiceSpec {
         selector: Some(selector),
-        ports: Some(vec![service_port]),
-        type_: Some("ClusterIP".to_string()),
+        ports: Some(service_ports),
+        type_: Some(
+            service_config
+                .and_then(|service| service.type_.clone())
+                .unwrap_or_else(|| "ClusterIP".to_string()),
+        ),
         ..Default::default()
     };
 
@@ -604,26 +4057,191 @@ pub fn generate_prometheus_service(config: &KamutConfig) -> Result<String> {
     };
 
     // Serialize to YAML
-    let yaml = serde_yaml::to_string(&service).context("Failed to serialize service to YAML")?;
+    let yaml = serialize_manifest(&service).context("Failed to serialize service to YAML")?;
 
     Ok(yaml)
 }
 
+// Port of the Prometheus Service (default: 9090)
+fn prometheus_service_port(config: &KamutConfig) -> i32 {
+    config
+        .service
+        .as_ref()
+        .and_then(|service| service.port)
+        .unwrap_or(9090)
+}
+
+// Node port of a Service's main port, which must be in the default NodePort range and
+// only set for NodePort Services
+fn service_node_port(service_config: &ServiceConfig) -> Result<Option<i32>> {
+    let Some(node_port) = service_config.node_port else {
+        return Ok(None);
+    };
+    if service_config.type_.as_deref() != Some("NodePort") {
+        return Err(anyhow::anyhow!(
+            "service.node_port requires service type NodePort"
+        ));
+    }
+    if !(30000..=32767).contains(&node_port) {
+        return Err(anyhow::anyhow!(
+            "service.node_port {} is outside the NodePort range 30000-32767",
+            node_port
+        ));
+    }
+    Ok(Some(node_port))
+}
+
+// Name of the main port of the Prometheus Service
+fn prometheus_port_name(config: &KamutConfig) -> String {
+    config
+        .service
+        .as_ref()
+        .and_then(|service| service.port_name.clone())
+        .unwrap_or_else(|| "web".to_string())
+}
+
+// Label on the Prometheus Service and its self-scrape ServiceMonitor through which
+// `self_monitor` selects them; unlike `app`, no output option strips it
+fn prometheus_self_monitor_labels(config: &KamutConfig) -> BTreeMap<String, String> {
+    BTreeMap::from([("prometheus".to_string(), config.name.clone())])
+}
+
+/// Generates a ServiceMonitor through which the operator-managed Prometheus scrapes itself,
+/// selecting the Prometheus Service by its `prometheus` label and scraping its main port
+pub fn generate_prometheus_service_monitor(config: &KamutConfig) -> Result<String> {
+    let mut metadata = build_object_meta(config, Some(&format!("prometheus-{}", config.name)));
+    metadata
+        .labels
+        .get_or_insert_with(BTreeMap::new)
+        .extend(prometheus_self_monitor_labels(config));
+
+    let service_monitor = ServiceMonitor {
+        spec: ServiceMonitorSpec {
+            selector: ServiceMonitorSelector {
+                match_labels: Some(prometheus_self_monitor_labels(config)),
+                match_expressions: None,
+            },
+            endpoints: vec![ServiceMonitorEndpoints {
+                port: Some(prometheus_port_name(config)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        metadata,
+    };
+
+    serialize_manifest(&service_monitor).context("Failed to serialize ServiceMonitor to YAML")
+}
+
+// Keeps the first word of a duration such as `30s # every half minute`, dropping the
+// trailing comment
+fn strip_duration_comment(duration: &str) -> String {
+    duration
+        .split_whitespace()
+        .next()
+        .unwrap_or(duration)
+        .to_string()
+}
+
+/// Generates a `monitoring.coreos.com/v1` ServiceMonitor scraping the `port` (a Service
+/// port name, or a target port number) of the Services matching `labels`
+pub fn generate_service_monitor_manifest(config: &KamutConfig) -> Result<String> {
+    let port = config
+        .port
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("port is required for ServiceMonitor"))?;
+
+    let metadata = build_object_meta(config, None);
+
+    // Select Services by the given labels, or by app: <name>
+    let match_labels = match &config.labels {
+        Some(label_map) => label_map.clone().into_iter().collect(),
+        None => metadata.labels.clone().unwrap_or_default(),
+    };
+
+    // A number targets the pod port directly; a name refers to the Service port
+    let (port_name, target_port) = match port.parse::<i32>() {
+        Ok(number) => (None, Some(IntOrString::Int(number))),
+        Err(_) => (Some(port.clone()), None),
+    };
+
+    let service_monitor = ServiceMonitor {
+        metadata,
+        spec: ServiceMonitorSpec {
+            selector: ServiceMonitorSelector {
+                match_labels: Some(match_labels),
+                match_expressions: None,
+            },
+            endpoints: vec![ServiceMonitorEndpoints {
+                port: port_name,
+                target_port,
+                path: config.metrics_path.clone(),
+                interval: config
+                    .scrape_interval
+                    .as_deref()
+                    .map(strip_duration_comment),
+                scrape_timeout: config.scrape_timeout.as_deref().map(strip_duration_comment),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    };
+
+    serialize_manifest(&service_monitor).context("Failed to serialize ServiceMonitor to YAML")
+}
+
 // Function to generate ScrapeConfig manifest using kube_custom_resources_rs type
 pub fn generate_scrape_config_manifest(config: &KamutConfig) -> Result<String> {
-    // Create metadata
-    let mut metadata = ObjectMeta::default();
-    metadata.name = Some(config.name.clone());
+    let scrape_config = build_scrape_config(config)?;
+
+    // Serialize to YAML
+    let yaml =
+        serialize_manifest(&scrape_config).context("Failed to serialize ScrapeConfig to YAML")?;
+
+    Ok(yaml)
+}
+
+/// Heuristic cardinality lint: a ScrapeConfig whose relabelings have no `keep` action
+/// scrapes every target its service discovery finds (e.g. all pods)
+pub fn lint_scrape_config(scrape_config: &ScrapeConfig) -> Vec<String> {
+    let has_keep = scrape_config
+        .spec
+        .relabelings
+        .iter()
+        .flatten()
+        .any(|relabeling| matches!(relabeling.action, Some(ScrapeConfigRelabelingsAction::Keep)));
+
+    let mut warnings = Vec::new();
+    if !has_keep {
+        warnings.push(format!(
+            "ScrapeConfig {} has no 'keep' relabeling, so it scrapes every discovered target and may create high cardinality",
+            scrape_config.metadata.name.as_deref().unwrap_or_default()
+        ));
+    }
+    warnings
+}
 
-    // Set namespace if provided
-    if let Some(namespace) = &config.namespace {
-        metadata.namespace = Some(namespace.clone());
+fn build_scrape_config(config: &KamutConfig) -> Result<ScrapeConfig> {
+    if config.api_version.is_some() && config.api_version_override.is_some() {
+        return Err(anyhow::anyhow!(
+            "ScrapeConfig {} sets both 'apiVersion' and 'api_version_override'; choose one",
+            config.name
+        ));
     }
 
-    // Create labels
-    let mut labels = BTreeMap::new();
-    labels.insert("app".to_string(), config.name.clone());
-    metadata.labels = Some(labels);
+    // Validate the requested apiVersion against the versions the crate provides
+    if let Some(api_version) = &config.api_version {
+        if !SUPPORTED_SCRAPE_CONFIG_API_VERSIONS.contains(&api_version.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unsupported ScrapeConfig apiVersion '{}' (supported: {})",
+                api_version,
+                SUPPORTED_SCRAPE_CONFIG_API_VERSIONS.join(", ")
+            ));
+        }
+    }
+
+    // Create metadata
+    let metadata = build_object_meta(config, None);
 
     // Create a match labels map
     let mut match_labels = std::collections::BTreeMap::new();
@@ -661,14 +4279,15 @@ pub fn generate_scrape_config_manifest(config: &KamutConfig) -> Result<String> {
             own_namespace: Some(false),
             names: Some(vec![scrape_namespace.clone()]),
         })
-    } else if let Some(namespace) = &config.namespace {
-        // Fall back to namespace if scrapeNamespace is not provided
-        Some(ScrapeConfigKubernetesSdConfigsNamespaces {
-            own_namespace: Some(false),
-            names: Some(vec![namespace.clone()]),
-        })
     } else {
-        None
+        // Fall back to namespace if scrapeNamespace is not provided
+        config
+            .namespace
+            .as_ref()
+            .map(|namespace| ScrapeConfigKubernetesSdConfigsNamespaces {
+                own_namespace: Some(false),
+                names: Some(vec![namespace.clone()]),
+            })
     };
 
     // Create kubernetes SD config with namespaces support
@@ -775,29 +4394,17 @@ pub fn generate_scrape_config_manifest(config: &KamutConfig) -> Result<String> {
     };
 
     // Create ScrapeConfig spec
-    let mut spec = ScrapeConfigSpec::default();
-    spec.job_name = Some(config.name.clone());
+    let mut spec = ScrapeConfigSpec {
+        job_name: Some(config.name.clone()),
+        ..Default::default()
+    };
 
     // 주석이 포함된 문자열을 정리합니다
-    if let Some(interval) = &config.scrape_interval {
-        spec.scrape_interval = Some(
-            interval
-                .split_whitespace()
-                .next()
-                .unwrap_or(interval)
-                .to_string(),
-        );
-    }
-
-    if let Some(timeout) = &config.scrape_timeout {
-        spec.scrape_timeout = Some(
-            timeout
-                .split_whitespace()
-                .next()
-                .unwrap_or(timeout)
-                .to_string(),
-        );
-    }
+    spec.scrape_interval = config
+        .scrape_interval
+        .as_deref()
+        .map(strip_duration_comment);
+    spec.scrape_timeout = config.scrape_timeout.as_deref().map(strip_duration_comment);
 
     spec.metrics_path = config.metrics_path.clone();
     spec.kubernetes_sd_configs = Some(vec![kubernetes_sd_config]);
@@ -828,17 +4435,33 @@ pub fn generate_scrape_config_manifest(config: &KamutConfig) -> Result<String> {
         relabelings.push(port_config);
     }
     relabelings.push(drop_terminated_pods_config);
+
+    // Append custom relabelings, or use them alone when replacing the defaults
+    if let Some(custom_relabelings) = &config.relabelings {
+        if config.replace_default_relabelings.unwrap_or(false) {
+            relabelings.clear();
+        }
+        relabelings.extend(custom_relabelings.iter().cloned());
+    }
     
     spec.relabelings = Some(relabelings);
 
     // Create ScrapeConfig
-    let scrape_config = ScrapeConfig { metadata, spec };
-
-    // Serialize to YAML
-    let yaml = serde_yaml::to_string(&scrape_config)
-        .context("Failed to serialize ScrapeConfig to YAML")?;
+    Ok(ScrapeConfig { metadata, spec })
+}
 
-    Ok(yaml)
+// The `app` label plus the `service_account.labels` scoped to the RBAC resources
+fn service_account_labels(config: &KamutConfig) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    if let Some(extra_labels) = config
+        .service_account
+        .as_ref()
+        .and_then(|sa_config| sa_config.labels.as_ref())
+    {
+        labels.extend(extra_labels.clone());
+    }
+    labels.insert("app".to_string(), config.name.clone());
+    labels
 }
 
 // Function to generate ServiceAccount, ClusterRole, and ClusterRoleBinding for Prometheus
@@ -855,18 +4478,10 @@ pub fn generate_prometheus_service_account(config: &KamutConfig) -> Result<Vec<S
 
     if should_create {
         // Create ServiceAccount
-        let mut sa_metadata = ObjectMeta::default();
-        sa_metadata.name = Some(format!("prometheus-{}", config.name));
-
-        // Set namespace if provided
-        if let Some(namespace) = &config.namespace {
-            sa_metadata.namespace = Some(namespace.clone());
-        }
-
-        // Create labels
-        let mut labels = BTreeMap::new();
-        labels.insert("app".to_string(), config.name.clone());
-        sa_metadata.labels = Some(labels);
+        let mut sa_metadata = ObjectMeta {
+            labels: Some(service_account_labels(config)),
+            ..build_object_meta(config, Some(&format!("prometheus-{}", config.name)))
+        };
 
         // Add annotations if provided
         if let Some(sa_config) = &config.service_account {
@@ -885,7 +4500,7 @@ pub fn generate_prometheus_service_account(config: &KamutConfig) -> Result<Vec<S
         };
 
         // Serialize to YAML
-        let sa_yaml = serde_yaml::to_string(&service_account)
+        let sa_yaml = serialize_manifest(&service_account)
             .context("Failed to serialize ServiceAccount to YAML")?;
         manifests.push(sa_yaml);
 
@@ -897,73 +4512,79 @@ pub fn generate_prometheus_service_account(config: &KamutConfig) -> Result<Vec<S
         };
 
         if should_create_cluster_role {
-            // Create ClusterRole
-            let mut cr_metadata = ObjectMeta::default();
-            cr_metadata.name = Some(format!("{}-role", config.name));
-
-            // Create labels for ClusterRole
-            let mut cr_labels = BTreeMap::new();
-            cr_labels.insert("app".to_string(), config.name.clone());
-            cr_metadata.labels = Some(cr_labels);
-
-            // Define rules for Prometheus
-            let rules = vec![
-                PolicyRule {
-                    api_groups: Some(vec!["".to_string()]),
-                    resources: Some(vec![
-                        "nodes".to_string(),
-                        "nodes/proxy".to_string(),
-                        "services".to_string(),
-                        "endpoints".to_string(),
-                        "pods".to_string(),
-                    ]),
-                    verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
-                    ..Default::default()
-                },
-                PolicyRule {
-                    api_groups: Some(vec!["extensions".to_string()]),
-                    resources: Some(vec!["ingresses".to_string()]),
-                    verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
-                    ..Default::default()
-                },
-                PolicyRule {
-                    api_groups: Some(vec!["networking.k8s.io".to_string()]),
-                    resources: Some(vec!["ingresses".to_string()]),
-                    verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
-                    ..Default::default()
-                },
-                PolicyRule {
-                    non_resource_urls: Some(vec!["/metrics".to_string()]),
-                    verbs: vec!["get".to_string()],
-                    ..Default::default()
-                },
-            ];
-
-            // Create ClusterRole
-            let cluster_role = ClusterRole {
-                metadata: cr_metadata,
-                rules: Some(rules),
-                ..Default::default()
+            // A namespaced Role limits Prometheus to the namespace it runs in
+            let namespaced = match config
+                .service_account
+                .as_ref()
+                .and_then(|sa_config| sa_config.scope.as_deref())
+                .unwrap_or("cluster")
+            {
+                "cluster" => false,
+                "namespace" if config.namespace.is_none() => {
+                    return Err(anyhow::anyhow!(
+                        "service_account.scope 'namespace' requires a namespace for Prometheus {}",
+                        config.name
+                    ));
+                }
+                "namespace" => true,
+                scope => {
+                    return Err(anyhow::anyhow!(
+                        "Invalid service_account.scope '{}': expected 'cluster' or 'namespace'",
+                        scope
+                    ));
+                }
             };
 
-            // Serialize to YAML
-            let cr_yaml = serde_yaml::to_string(&cluster_role)
-                .context("Failed to serialize ClusterRole to YAML")?;
-            manifests.push(cr_yaml);
+            // Create metadata for the ClusterRole or Role
+            let role_metadata = ObjectMeta {
+                namespace: config.namespace.clone().filter(|_| namespaced),
+                labels: Some(service_account_labels(config)),
+                ..build_object_meta(config, Some(&format!("{}-role", config.name)))
+            };
 
-            // Create ClusterRoleBinding
-            let mut crb_metadata = ObjectMeta::default();
-            crb_metadata.name = Some(format!("{}-role-binding", config.name));
+            // Use the configured rules verbatim, or the built-in rules for Prometheus,
+            // narrowed to the discovery roles of `rbac_scope` when it is set
+            let rbac_scope = config
+                .service_account
+                .as_ref()
+                .and_then(|sa_config| sa_config.rbac_scope.as_deref());
+            let rules = match config
+                .service_account
+                .as_ref()
+                .and_then(|sa_config| sa_config.cluster_role_rules.as_deref())
+            {
+                Some(_) if rbac_scope.is_some() => {
+                    return Err(anyhow::anyhow!(
+                        "service_account.rbac_scope cannot be combined with cluster_role_rules"
+                    ));
+                }
+                Some(rules) if namespaced => {
+                    if rules.iter().any(|rule| !rule.non_resource_urls.is_empty()) {
+                        return Err(anyhow::anyhow!(
+                            "service_account.cluster_role_rules cannot grant non_resource_urls with scope 'namespace'"
+                        ));
+                    }
+                    build_policy_rules(rules)?
+                }
+                Some(rules) => build_policy_rules(rules)?,
+                None => match rbac_scope {
+                    Some(roles) => scoped_prometheus_policy_rules(roles, namespaced)?,
+                    None if namespaced => default_prometheus_namespaced_policy_rules(),
+                    None => default_prometheus_policy_rules(),
+                },
+            };
 
-            // Create labels for ClusterRoleBinding
-            let mut crb_labels = BTreeMap::new();
-            crb_labels.insert("app".to_string(), config.name.clone());
-            crb_metadata.labels = Some(crb_labels);
+            // Create metadata for the ClusterRoleBinding or RoleBinding
+            let binding_metadata = ObjectMeta {
+                namespace: role_metadata.namespace.clone(),
+                labels: Some(service_account_labels(config)),
+                ..build_object_meta(config, Some(&format!("{}-role-binding", config.name)))
+            };
 
             // Create RoleRef
             let role_ref = RoleRef {
                 api_group: "rbac.authorization.k8s.io".to_string(),
-                kind: "ClusterRole".to_string(),
+                kind: if namespaced { "Role" } else { "ClusterRole" }.to_string(),
                 name: format!("{}-role", config.name),
             };
 
@@ -975,19 +4596,226 @@ pub fn generate_prometheus_service_account(config: &KamutConfig) -> Result<Vec<S
                 ..Default::default()
             };
 
-            // Create ClusterRoleBinding
-            let cluster_role_binding = ClusterRoleBinding {
-                metadata: crb_metadata,
-                role_ref,
-                subjects: Some(vec![subject]),
-            };
+            if namespaced {
+                let role = Role {
+                    metadata: role_metadata,
+                    rules: Some(rules),
+                };
+                manifests
+                    .push(serialize_manifest(&role).context("Failed to serialize Role to YAML")?);
+
+                let role_binding = RoleBinding {
+                    metadata: binding_metadata,
+                    role_ref,
+                    subjects: Some(vec![subject]),
+                };
+                manifests.push(
+                    serialize_manifest(&role_binding)
+                        .context("Failed to serialize RoleBinding to YAML")?,
+                );
+            } else {
+                let cluster_role = ClusterRole {
+                    metadata: role_metadata,
+                    rules: Some(rules),
+                    ..Default::default()
+                };
+                manifests.push(
+                    serialize_manifest(&cluster_role)
+                        .context("Failed to serialize ClusterRole to YAML")?,
+                );
 
-            // Serialize to YAML
-            let crb_yaml = serde_yaml::to_string(&cluster_role_binding)
-                .context("Failed to serialize ClusterRoleBinding to YAML")?;
-            manifests.push(crb_yaml);
+                let cluster_role_binding = ClusterRoleBinding {
+                    metadata: binding_metadata,
+                    role_ref,
+                    subjects: Some(vec![subject]),
+                };
+                manifests.push(
+                    serialize_manifest(&cluster_role_binding)
+                        .context("Failed to serialize ClusterRoleBinding to YAML")?,
+                );
+            }
         }
     }
 
     Ok(manifests)
 }
+
+// Rules of the Prometheus ClusterRole when `cluster_role_rules` is not set: read access
+// to the discovery targets and the `/metrics` endpoint
+fn default_prometheus_policy_rules() -> Vec<PolicyRule> {
+    vec![
+        PolicyRule {
+            api_groups: Some(vec!["".to_string()]),
+            resources: Some(vec![
+                "nodes".to_string(),
+                "nodes/proxy".to_string(),
+                "services".to_string(),
+                "endpoints".to_string(),
+                "pods".to_string(),
+            ]),
+            verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
+            ..Default::default()
+        },
+        PolicyRule {
+            api_groups: Some(vec!["extensions".to_string()]),
+            resources: Some(vec!["ingresses".to_string()]),
+            verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
+            ..Default::default()
+        },
+        PolicyRule {
+            api_groups: Some(vec!["networking.k8s.io".to_string()]),
+            resources: Some(vec!["ingresses".to_string()]),
+            verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
+            ..Default::default()
+        },
+        PolicyRule {
+            non_resource_urls: Some(vec!["/metrics".to_string()]),
+            verbs: vec!["get".to_string()],
+            ..Default::default()
+        },
+    ]
+}
+
+// Rules of the Prometheus Role with `scope: namespace`: the namespaced discovery targets,
+// since Roles cannot grant nodes or non-resource URLs
+fn default_prometheus_namespaced_policy_rules() -> Vec<PolicyRule> {
+    vec![
+        PolicyRule {
+            api_groups: Some(vec!["".to_string()]),
+            resources: Some(vec![
+                "services".to_string(),
+                "endpoints".to_string(),
+                "pods".to_string(),
+            ]),
+            verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
+            ..Default::default()
+        },
+        PolicyRule {
+            api_groups: Some(vec!["networking.k8s.io".to_string()]),
+            resources: Some(vec!["ingresses".to_string()]),
+            verbs: vec!["get".to_string(), "list".to_string(), "watch".to_string()],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Kubernetes service discovery roles accepted by `service_account.rbac_scope`
+pub const SCRAPE_DISCOVERY_ROLES: &[&str] = &[
+    "node",
+    "pod",
+    "service",
+    "endpoints",
+    "endpointslice",
+    "ingress",
+];
+
+// Rules of the Prometheus ClusterRole (or Role) reading only what the given discovery roles
+// need; the endpoints roles also read the services and pods behind the endpoints
+fn scoped_prometheus_policy_rules(roles: &[String], namespaced: bool) -> Result<Vec<PolicyRule>> {
+    if roles.is_empty() {
+        return Err(anyhow::anyhow!(
+            "service_account.rbac_scope must list at least one discovery role"
+        ));
+    }
+
+    let mut core_resources = BTreeSet::new();
+    let mut endpoint_slices = false;
+    let mut ingresses = false;
+    for role in roles {
+        match role.as_str() {
+            "node" if namespaced => {
+                return Err(anyhow::anyhow!(
+                    "service_account.rbac_scope role 'node' requires scope 'cluster', since nodes are cluster-scoped"
+                ));
+            }
+            "node" => core_resources.extend(["nodes", "nodes/proxy"]),
+            "pod" => core_resources.extend(["pods"]),
+            "service" => core_resources.extend(["services"]),
+            "endpoints" => core_resources.extend(["services", "endpoints", "pods"]),
+            "endpointslice" => {
+                core_resources.extend(["services", "pods"]);
+                endpoint_slices = true;
+            }
+            "ingress" => ingresses = true,
+            role => {
+                return Err(anyhow::anyhow!(
+                    "Invalid service_account.rbac_scope role '{}': expected one of {}",
+                    role,
+                    SCRAPE_DISCOVERY_ROLES.join(", ")
+                ));
+            }
+        }
+    }
+
+    let read = || vec!["get".to_string(), "list".to_string(), "watch".to_string()];
+    let mut rules = Vec::new();
+    if !core_resources.is_empty() {
+        rules.push(PolicyRule {
+            api_groups: Some(vec!["".to_string()]),
+            resources: Some(core_resources.into_iter().map(String::from).collect()),
+            verbs: read(),
+            ..Default::default()
+        });
+    }
+    if endpoint_slices {
+        rules.push(PolicyRule {
+            api_groups: Some(vec!["discovery.k8s.io".to_string()]),
+            resources: Some(vec!["endpointslices".to_string()]),
+            verbs: read(),
+            ..Default::default()
+        });
+    }
+    if ingresses {
+        rules.push(PolicyRule {
+            api_groups: Some(vec!["networking.k8s.io".to_string()]),
+            resources: Some(vec!["ingresses".to_string()]),
+            verbs: read(),
+            ..Default::default()
+        });
+    }
+    if !namespaced {
+        rules.push(PolicyRule {
+            non_resource_urls: Some(vec!["/metrics".to_string()]),
+            verbs: vec!["get".to_string()],
+            ..Default::default()
+        });
+    }
+    Ok(rules)
+}
+
+// Converts configured ClusterRole rules; every rule needs verbs and either resources or
+// non-resource URLs
+fn build_policy_rules(rules: &[PolicyRuleConfig]) -> Result<Vec<PolicyRule>> {
+    let non_empty = |values: &Vec<String>| (!values.is_empty()).then(|| values.clone());
+    rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            if rule.verbs.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "service_account.cluster_role_rules[{}] requires verbs",
+                    index
+                ));
+            }
+            if rule.resources.is_empty() == rule.non_resource_urls.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "service_account.cluster_role_rules[{}] must set exactly one of resources and non_resource_urls",
+                    index
+                ));
+            }
+            if !rule.resources.is_empty() && rule.api_groups.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "service_account.cluster_role_rules[{}] requires api_groups for its resources; use [\"\"] for the core group",
+                    index
+                ));
+            }
+            Ok(PolicyRule {
+                api_groups: non_empty(&rule.api_groups),
+                resources: non_empty(&rule.resources),
+                verbs: rule.verbs.clone(),
+                non_resource_urls: non_empty(&rule.non_resource_urls),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
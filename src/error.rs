@@ -0,0 +1,56 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned when parsing and validating kamut configuration
+#[derive(Debug)]
+pub enum KamutError {
+    /// The document is not valid YAML or does not match the kamut schema
+    Parse(serde_yaml::Error),
+    /// A required field is missing from the document
+    MissingField(&'static str),
+    /// The document's kind is not supported
+    UnsupportedKind(String),
+    /// No kamut files matched the pattern and `--require-match` was given
+    NoMatchingFiles(String),
+    /// Fetching a URL input did not finish within `--fetch-timeout`
+    FetchTimeout(String, Duration),
+}
+
+impl fmt::Display for KamutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KamutError::Parse(err) => write!(f, "Failed to parse kamut config: {}", err),
+            KamutError::MissingField(field) => write!(f, "'{}' field is required", field),
+            KamutError::UnsupportedKind(kind) => write!(f, "unsupported kind '{}'", kind),
+            KamutError::NoMatchingFiles(pattern) => {
+                write!(f, "no matching kamut files found for pattern: {}", pattern)
+            }
+            KamutError::FetchTimeout(url, timeout) => {
+                write!(
+                    f,
+                    "timed out after {}s fetching {}",
+                    timeout.as_secs_f64(),
+                    url
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KamutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KamutError::Parse(err) => Some(err),
+            KamutError::MissingField(_)
+            | KamutError::UnsupportedKind(_)
+            | KamutError::NoMatchingFiles(_)
+            | KamutError::FetchTimeout(..) => None,
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for KamutError {
+    fn from(err: serde_yaml::Error) -> Self {
+        KamutError::Parse(err)
+    }
+}
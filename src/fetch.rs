@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::error::KamutError;
+
+/// Time allowed for fetching a URL input when `--fetch-timeout` is not given
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether a pattern names a remote kamut file instead of local files
+pub fn is_url(pattern: &str) -> bool {
+    pattern.starts_with("http://") || pattern.starts_with("https://")
+}
+
+/// Fetches a kamut file over plain HTTP. The whole request, from connecting to reading
+/// the last byte, must finish within `timeout`, so an unresponsive server fails with
+/// `KamutError::FetchTimeout` instead of hanging.
+pub fn fetch_url(url: &str, timeout: Duration) -> Result<String> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unsupported URL '{}': only http:// inputs can be fetched; download https:// files first",
+            url
+        )
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err(anyhow::anyhow!("Invalid URL '{}': missing host", url));
+    }
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+
+    let deadline = Instant::now() + timeout;
+    let timed_out = || KamutError::FetchTimeout(url.to_string(), timeout);
+    let remaining = || {
+        deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(timed_out)
+    };
+
+    let socket_address = address
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve {}", url))?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve {}", url))?;
+    let mut stream = TcpStream::connect_timeout(&socket_address, remaining()?).map_err(|err| {
+        match err.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => anyhow::Error::new(timed_out()),
+            _ => anyhow::Error::new(err).context(format!("Failed to connect to {}", url)),
+        }
+    })?;
+
+    // Send the request in one write; a server may answer and close after the first
+    // segment, failing the writes of a request sent piecewise
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, authority
+    );
+    stream.set_write_timeout(Some(remaining()?))?;
+    stream
+        .write_all(request.as_bytes())
+        .with_context(|| format!("Failed to send request to {}", url))?;
+
+    // Re-arm the read timeout before every read so a server trickling bytes still
+    // cannot outlast the deadline
+    let mut response = Vec::new();
+    let mut buffer = [0; 8192];
+    loop {
+        stream.set_read_timeout(Some(remaining()?))?;
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => response.extend_from_slice(&buffer[..read]),
+            Err(err) if matches!(err.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                return Err(timed_out().into());
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => {
+                return Err(anyhow::Error::new(err).context(format!("Failed to read {}", url)))
+            }
+        }
+    }
+
+    let response = String::from_utf8(response)
+        .with_context(|| format!("Response from {} is not valid UTF-8", url))?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| anyhow::anyhow!("Malformed HTTP response from {}", url))?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(anyhow::anyhow!(
+            "Failed to fetch {}: HTTP status {}",
+            url,
+            status
+        ));
+    }
+
+    Ok(body.to_string())
+}
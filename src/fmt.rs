@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{
+    format_manifests, postprocess_manifest, serialize_manifest, write_output_file, OutputFormat,
+    ProcessOptions, PROVENANCE_HEADER,
+};
+
+/// What `format_file` did with a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FmtOutcome {
+    /// The file was rewritten in canonical form
+    Formatted,
+    /// The file was already in canonical form
+    Unchanged,
+    /// The file has no kamut provenance header and was left alone
+    Skipped,
+}
+
+/// Re-serializes generated YAML through the canonical pipeline: mapping keys sorted, the
+/// output options of `options` (`trim`, `kubectl_order`, `indent`, ...) applied, and the
+/// documents joined by single `---` separators. Lines before the first document (the
+/// provenance header) are kept as they are.
+pub fn format_manifest_str(contents: &str, options: &ProcessOptions) -> Result<String> {
+    let body_start = contents
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        .min(contents.len());
+    let (header, body) = contents.split_at(body_start);
+
+    let mut manifests = Vec::new();
+    let mut document = String::new();
+    for line in body.lines().chain(std::iter::once("---")) {
+        if line.trim_end() != "---" {
+            document.push_str(line);
+            document.push('\n');
+            continue;
+        }
+        let mut value: serde_yaml::Value =
+            serde_yaml::from_str(&document).context("Failed to parse manifest")?;
+        document.clear();
+        // Empty and comment-only documents are dropped
+        if value.is_null() {
+            continue;
+        }
+        sort_keys(&mut value);
+        manifests.push(postprocess_manifest(&serialize_manifest(&value)?, options)?);
+    }
+
+    Ok(format!(
+        "{}{}",
+        header,
+        format_manifests(&manifests, OutputFormat::Yaml)?
+    ))
+}
+
+/// Formats a generated file in place, skipping files without the provenance header
+pub fn format_file(path: &Path, options: &ProcessOptions) -> Result<FmtOutcome> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    if !contents.starts_with(PROVENANCE_HEADER) {
+        return Ok(FmtOutcome::Skipped);
+    }
+
+    let formatted = format_manifest_str(&contents, options)
+        .with_context(|| format!("Failed to format {}", path.display()))?;
+    if formatted == contents {
+        return Ok(FmtOutcome::Unchanged);
+    }
+
+    write_output_file(path, &formatted)?;
+    Ok(FmtOutcome::Formatted)
+}
+
+// Recursively sorts mapping keys
+fn sort_keys(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut entries: Vec<_> = std::mem::take(mapping).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(&b.as_str()));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                mapping.insert(key, value);
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) => {
+            for value in sequence.iter_mut() {
+                sort_keys(value);
+            }
+        }
+        _ => {}
+    }
+}
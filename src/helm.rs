@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{render_file, write_output_file, ProcessOptions};
+use crate::inventory::summarize_manifest;
+use crate::kamutrc::options_for_file;
+
+/// Chart version used when none is given
+pub const DEFAULT_CHART_VERSION: &str = "0.1.0";
+
+/// Contents of the generated `Chart.yaml`
+#[derive(Debug, Serialize)]
+pub struct ChartMetadata {
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub version: String,
+}
+
+impl ChartMetadata {
+    pub fn new(name: &str, version: &str) -> Self {
+        ChartMetadata {
+            api_version: "v2".to_string(),
+            name: name.to_string(),
+            description: "Static chart generated by kamut".to_string(),
+            type_: "application".to_string(),
+            version: version.to_string(),
+        }
+    }
+}
+
+/// Renders the given files, with the defaults of the `.kamutrc` files above them, and
+/// writes them as a static Helm chart in `chart_dir`, one template per resource. Returns the paths of the written templates.
+pub fn export_helm_chart(
+    files: &[PathBuf],
+    chart_dir: &Path,
+    chart: &ChartMetadata,
+    options: &ProcessOptions,
+) -> Result<Vec<PathBuf>> {
+    let templates_dir = chart_dir.join("templates");
+    fs::create_dir_all(&templates_dir)
+        .with_context(|| format!("Failed to create directory: {}", templates_dir.display()))?;
+
+    let chart_path = chart_dir.join("Chart.yaml");
+    let chart_yaml = serde_yaml::to_string(chart).context("Failed to serialize Chart.yaml")?;
+    write_output_file(&chart_path, &chart_yaml)?;
+
+    let mut written = Vec::new();
+    let mut used_names = HashSet::new();
+    for file_path in files {
+        let file_options = options_for_file(file_path, options)?;
+        let rendered = render_file(file_path, &file_options)?;
+        for manifest in &rendered.manifests {
+            let entry = summarize_manifest(manifest, file_path)?;
+
+            // Name templates after the resource, disambiguating repeated kind/name pairs
+            let base_name = format!("{}-{}", entry.kind.to_lowercase(), entry.name);
+            let mut template_name = base_name.clone();
+            let mut suffix = 2;
+            while !used_names.insert(template_name.clone()) {
+                template_name = format!("{}-{}", base_name, suffix);
+                suffix += 1;
+            }
+
+            let template_path = templates_dir.join(format!("{}.yaml", template_name));
+            write_output_file(&template_path, manifest)?;
+            written.push(template_path);
+        }
+    }
+
+    Ok(written)
+}
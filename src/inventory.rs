@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::{render_file, ProcessOptions};
+use crate::kamutrc::options_for_file;
+
+/// A resource rendered from a kamut file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceEntry {
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    /// The kamut file the resource was rendered from
+    pub source: PathBuf,
+}
+
+/// Extracts the kind, namespace, and name of a generated manifest
+pub fn summarize_manifest(manifest: &str, source: &Path) -> Result<ResourceEntry> {
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+
+    let field = |path: &[&str]| {
+        path.iter()
+            .try_fold(&value, |value, key| value.get(*key))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+    };
+
+    Ok(ResourceEntry {
+        kind: field(&["kind"]).unwrap_or_default(),
+        namespace: field(&["metadata", "namespace"]),
+        name: field(&["metadata", "name"]).unwrap_or_default(),
+        source: source.to_path_buf(),
+    })
+}
+
+/// Renders the given files in memory, with the defaults of the `.kamutrc` files above
+/// them, and lists every resource they would create
+pub fn list_resources(files: &[PathBuf], options: &ProcessOptions) -> Result<Vec<ResourceEntry>> {
+    let mut entries = Vec::new();
+
+    for file_path in files {
+        let file_options = options_for_file(file_path, options)?;
+        let rendered = render_file(file_path, &file_options)?;
+        for manifest in &rendered.manifests {
+            entries.push(summarize_manifest(manifest, file_path)?);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Describes every resource listed more than once with the same kind, namespace, and
+/// name, which `kubectl apply` would silently merge into one object
+pub fn find_duplicates(entries: &[ResourceEntry]) -> Vec<String> {
+    let mut sources: BTreeMap<(&str, Option<&str>, &str), Vec<&Path>> = BTreeMap::new();
+    for entry in entries {
+        sources
+            .entry((&entry.kind, entry.namespace.as_deref(), &entry.name))
+            .or_default()
+            .push(&entry.source);
+    }
+
+    sources
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|((kind, namespace, name), sources)| {
+            let sources: Vec<String> = sources
+                .iter()
+                .map(|source| source.display().to_string())
+                .collect();
+            let name = match namespace {
+                Some(namespace) => format!("{}/{}", namespace, name),
+                None => name.to_string(),
+            };
+            format!(
+                "{} {} is generated {} times, from {}",
+                kind,
+                name,
+                sources.len(),
+                sources.join(", ")
+            )
+        })
+        .collect()
+}
+
+/// Formats resources as a human-readable table
+pub fn format_resource_table(entries: &[ResourceEntry]) -> String {
+    let header = ["KIND", "NAMESPACE", "NAME", "SOURCE"];
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.kind.clone(),
+                entry.namespace.clone().unwrap_or_else(|| "-".to_string()),
+                entry.name.clone(),
+                entry.source.display().to_string(),
+            ]
+        })
+        .collect();
+
+    // Size each column to its widest cell
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_row = |cells: [&str; 4]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut table = format_row(header);
+    for row in &rows {
+        table.push_str(&format_row(row.each_ref().map(String::as_str)));
+    }
+    table
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ProcessOptions;
+
+/// Name of the per-directory defaults file
+pub const KAMUTRC_FILE_NAME: &str = ".kamutrc";
+
+/// Defaults read from `.kamutrc` files, applied to the kamut files below their directory
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KamutRc {
+    /// Stop looking for `.kamutrc` files in parent directories
+    pub root: bool,
+    /// Namespace of documents without one and without a `# kamut: namespace=` directive
+    pub namespace: Option<String>,
+    /// Default for `--image-registry`
+    pub image_registry: Option<String>,
+    /// Default for `--strict`
+    pub strict: Option<bool>,
+    /// Default for `--defaults`, relative to the directory of the `.kamutrc`
+    pub defaults: Option<PathBuf>,
+    /// Variables for `when` expressions; `--var` takes precedence
+    pub vars: BTreeMap<String, String>,
+}
+
+impl KamutRc {
+    /// Reads a `.kamutrc` file; an empty file holds no defaults
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if contents.trim().is_empty() {
+            return Ok(KamutRc::default());
+        }
+        let mut rc: KamutRc = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        // Resolved here, since a closer .kamutrc may be in another directory
+        if let (Some(defaults), Some(dir)) = (&rc.defaults, path.parent()) {
+            rc.defaults = Some(dir.join(defaults));
+        }
+        Ok(rc)
+    }
+
+    /// Overrides these defaults with the ones of a `.kamutrc` closer to the kamut file
+    pub fn merge(&mut self, closer: KamutRc) {
+        self.root |= closer.root;
+        if closer.namespace.is_some() {
+            self.namespace = closer.namespace;
+        }
+        if closer.image_registry.is_some() {
+            self.image_registry = closer.image_registry;
+        }
+        if closer.strict.is_some() {
+            self.strict = closer.strict;
+        }
+        if closer.defaults.is_some() {
+            self.defaults = closer.defaults;
+        }
+        self.vars.extend(closer.vars);
+    }
+
+    /// Fills in the options not given on the command line
+    pub fn apply(&self, options: &ProcessOptions) -> ProcessOptions {
+        let mut vars = self.vars.clone();
+        vars.extend(options.vars.clone());
+
+        ProcessOptions {
+            default_namespace: options
+                .default_namespace
+                .clone()
+                .or_else(|| self.namespace.clone()),
+            image_registry: options
+                .image_registry
+                .clone()
+                .or_else(|| self.image_registry.clone()),
+            strict: options.strict || self.strict.unwrap_or(false),
+            defaults_file: options
+                .defaults_file
+                .clone()
+                .or_else(|| self.defaults.clone()),
+            vars,
+            ..options.clone()
+        }
+    }
+}
+
+/// Merges the `.kamutrc` files of a kamut file's directory and its parents, with closer
+/// files overriding farther ones. The search stops at a file with `root: true`.
+pub fn discover(file_path: &Path) -> Result<KamutRc> {
+    let dir = file_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve directory: {}", dir.display()))?;
+
+    // Collected closest first
+    let mut found = Vec::new();
+    for ancestor in dir.ancestors() {
+        let path = ancestor.join(KAMUTRC_FILE_NAME);
+        if path.is_file() {
+            let rc = KamutRc::from_file(&path)?;
+            let root = rc.root;
+            found.push(rc);
+            if root {
+                break;
+            }
+        }
+    }
+
+    let mut merged = KamutRc::default();
+    for rc in found.into_iter().rev() {
+        merged.merge(rc);
+    }
+    Ok(merged)
+}
+
+/// Options for rendering one kamut file: the command-line options completed with the
+/// defaults of the `.kamutrc` files above it
+pub fn options_for_file(file_path: &Path, options: &ProcessOptions) -> Result<ProcessOptions> {
+    Ok(discover(file_path)?.apply(options))
+}
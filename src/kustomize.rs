@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::write_output_file;
+
+/// Name of the kustomization written by `--kustomize`
+pub const KUSTOMIZATION_FILE_NAME: &str = "kustomization.yaml";
+
+/// First line of every kustomization kamut writes; files without it are left alone
+pub const GENERATED_HEADER: &str = "# Generated by kamut --kustomize; do not edit";
+
+/// Renders a kustomization listing `resources` (paths relative to its directory) in
+/// sorted order
+pub fn render_kustomization(resources: &[String]) -> Result<String> {
+    let mut resources = resources.to_vec();
+    resources.sort();
+    resources.dedup();
+
+    let mut kustomization = serde_yaml::Mapping::new();
+    kustomization.insert(
+        "apiVersion".into(),
+        "kustomize.config.k8s.io/v1beta1".into(),
+    );
+    kustomization.insert("kind".into(), "Kustomization".into());
+    kustomization.insert(
+        "resources".into(),
+        resources
+            .into_iter()
+            .map(serde_yaml::Value::from)
+            .collect::<Vec<_>>()
+            .into(),
+    );
+
+    let yaml =
+        serde_yaml::to_string(&kustomization).context("Failed to serialize kustomization")?;
+    Ok(format!("{}\n{}", GENERATED_HEADER, yaml))
+}
+
+/// Writes `kustomization.yaml` into `dir`, listing the generated files relative to it.
+/// Returns the path written, or `None` when an existing kustomization was not written by
+/// kamut and is kept.
+pub fn write_kustomization(dir: &Path, files: &[PathBuf]) -> Result<Option<PathBuf>> {
+    let path = dir.join(KUSTOMIZATION_FILE_NAME);
+    if path.exists() {
+        let existing = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if !existing.starts_with(GENERATED_HEADER) {
+            return Ok(None);
+        }
+    }
+
+    let resources: Vec<String> = files
+        .iter()
+        .map(|file| {
+            file.strip_prefix(dir)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    write_output_file(&path, &render_kustomization(&resources)?)?;
+    Ok(Some(path))
+}
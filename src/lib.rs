@@ -1,6 +1,20 @@
 pub mod cli;
+pub mod cluster;
+pub mod condition;
 pub mod config;
+pub mod error;
+pub mod fetch;
+pub mod fmt;
+pub mod helm;
+pub mod inventory;
+pub mod kamutrc;
+pub mod kustomize;
 pub mod models;
+pub mod references;
+pub mod registry;
+pub mod schema;
+pub mod watch;
 
 pub use cli::Args;
+pub use error::KamutError;
 pub use models::KamutConfig;
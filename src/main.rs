@@ -1,13 +1,103 @@
 use anyhow::Result;
 use clap::Parser;
+use kamut::config::{ProcessOptions, ProcessOutcome};
+use kamut::fmt::FmtOutcome;
+use kamut::inventory::ResourceEntry;
+use kamut::KamutError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
+/// Exit code for errors while reading, validating, or rendering files
+const EXIT_ERROR: u8 = 1;
+/// Exit code when `--require-match` is given and the pattern matched no files
+/// (clap already uses 2 for usage errors)
+const EXIT_NO_MATCH: u8 = 3;
+
+// Progress output goes to stderr with --stdout so stdout only holds manifests
+macro_rules! status {
+    ($options:expr, $($arg:tt)*) => {
+        if $options.stdout {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+// Per-file details, omitted when rendering quietly
+macro_rules! info {
+    ($options:expr, $($arg:tt)*) => {
+        if !$options.quiet {
+            status!($options, $($arg)*);
+        }
+    };
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            match err.downcast_ref::<KamutError>() {
+                Some(KamutError::NoMatchingFiles(_)) => ExitCode::from(EXIT_NO_MATCH),
+                _ => ExitCode::from(EXIT_ERROR),
+            }
+        }
+    }
+}
+
+fn run() -> Result<()> {
     let cli = kamut::cli::Cli::parse();
 
     // If a command is specified, use it; otherwise, use the pattern from the top-level args
     match &cli.command {
-        Some(kamut::cli::Commands::Generate { pattern }) => {
-            generate_manifests(pattern)?;
+        Some(kamut::cli::Commands::Generate {
+            pattern,
+            watch,
+            exec,
+            generate_args,
+        }) => {
+            if *watch {
+                watch_manifests(pattern, exec.as_deref(), &generate_args.process_options())?;
+            } else if generate_args.diff_against_cluster {
+                diff_against_cluster(pattern, &generate_args.process_options())?;
+            } else {
+                generate_manifests(pattern, &generate_args.process_options())?;
+            }
+        }
+        Some(kamut::cli::Commands::List { pattern }) => {
+            list_resources(pattern)?;
+        }
+        Some(kamut::cli::Commands::Validate {
+            pattern,
+            max_errors,
+        }) => {
+            validate_files(pattern, max_errors.map(|max| max as usize))?;
+        }
+        Some(kamut::cli::Commands::Fmt {
+            pattern,
+            trim,
+            kubectl_order,
+            indent,
+            indent_sequences,
+        }) => {
+            let options = ProcessOptions {
+                trim: *trim,
+                kubectl_order: *kubectl_order,
+                indent: indent.map(usize::from),
+                indent_sequences: *indent_sequences,
+                ..Default::default()
+            };
+            format_files(pattern, &options)?;
+        }
+        Some(kamut::cli::Commands::ExportHelm {
+            dir,
+            pattern,
+            chart_name,
+            chart_version,
+        }) => {
+            export_helm(dir, pattern, chart_name.as_deref(), chart_version)?;
         }
         Some(kamut::cli::Commands::Version) => {
             // Display version information
@@ -16,29 +106,415 @@ fn main() -> Result<()> {
         }
         None => {
             // No command specified, use the pattern from the top-level args
-            generate_manifests(&cli.pattern)?;
+            if cli.generate_args.diff_against_cluster {
+                diff_against_cluster(&cli.pattern, &cli.generate_args.process_options())?;
+            } else {
+                generate_manifests(&cli.pattern, &cli.generate_args.process_options())?;
+            }
         }
     }
 
     Ok(())
 }
 
-fn generate_manifests(pattern: &str) -> Result<()> {
+fn generate_manifests(pattern: &str, options: &ProcessOptions) -> Result<()> {
+    // `-` renders the kamut file piped to stdin, and a URL the file fetched from it; both
+    // print the manifests
+    if pattern == kamut::config::STDIN_PATTERN || kamut::fetch::is_url(pattern) {
+        // The .kamutrc files of the working directory apply
+        let source_options =
+            kamut::kamutrc::options_for_file(Path::new(kamut::config::STDIN_PATTERN), options)?;
+        // The manifests go to stdout, so the details go to stderr
+        let source_options = ProcessOptions {
+            stdout: true,
+            ..source_options
+        };
+        let processed = if kamut::fetch::is_url(pattern) {
+            info!(source_options, "Processing file: {}", pattern);
+            kamut::config::process_url(pattern, &source_options)?
+        } else {
+            info!(
+                source_options,
+                "Processing file: {}",
+                kamut::config::STDIN_SOURCE
+            );
+            kamut::config::process_stdin(&source_options)?
+        };
+        report_outcome(&processed, &source_options);
+        let rendered = processed.rendered_count;
+        let outcomes = [processed];
+        check_duplicates(&outcomes, &source_options)?;
+        check_references(&outcomes, &source_options)?;
+        return check_render_only(rendered, options);
+    }
+
     // Find matching files
     let files = kamut::config::find_config_files(pattern)?;
 
     if files.is_empty() {
-        println!("No matching kamut files found for pattern: {}", pattern);
+        if options.require_match {
+            return Err(KamutError::NoMatchingFiles(pattern.to_string()).into());
+        }
+        status!(
+            options,
+            "No matching kamut files found for pattern: {}",
+            pattern
+        );
         return Ok(());
     }
 
-    println!("Found {} configuration files", files.len());
+    status!(options, "Found {} configuration files", files.len());
 
+    let mut rendered = 0;
+    // Output files grouped by their output directory, for --kustomize
+    let mut outputs: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    let mut outcomes = Vec::new();
     for file_path in files {
-        println!("\n=====================");
-        kamut::config::process_file(&file_path)?;
-        println!("=====================\n");
+        status!(options, "\n=====================");
+        let processed = process_and_report(&file_path, options)?;
+        rendered += processed.rendered_count;
+        if let Some(output_dir) = &processed.output_dir {
+            outputs
+                .entry(output_dir.clone())
+                .or_default()
+                .extend(processed.output_files.iter().cloned());
+        }
+        outcomes.push(processed);
+        status!(options, "=====================\n");
+    }
+
+    if options.kustomize {
+        write_kustomizations(&outputs, options)?;
+    }
+
+    check_duplicates(&outcomes, options)?;
+    check_references(&outcomes, options)?;
+
+    check_render_only(rendered, options)
+}
+
+// Processes a file with the defaults of the .kamutrc files above it and prints what
+// it generated
+fn process_and_report(file_path: &Path, options: &ProcessOptions) -> Result<ProcessOutcome> {
+    info!(options, "Processing file: {}", file_path.display());
+    let file_options = kamut::kamutrc::options_for_file(file_path, options)?;
+    let processed = kamut::config::process_file_with_options(file_path, &file_options)?;
+    report_outcome(&processed, &file_options);
+    Ok(processed)
+}
+
+// Prints the problems, generated resources, and the manifests or written files of a
+// processed file
+fn report_outcome(outcome: &ProcessOutcome, options: &ProcessOptions) {
+    for warning in &outcome.warnings {
+        info!(options, "\nWarning: {}", warning);
+    }
+    for error in &outcome.errors {
+        info!(options, "\nError: {}", error);
+    }
+    if outcome.document_count == 0 {
+        info!(options, "No valid YAML documents found in file");
+    }
+    for resource in &outcome.resources {
+        info!(options, "Generated {} {}", resource.kind, resource.name);
+    }
+    if let Some(stdout) = &outcome.stdout {
+        print!("{}", stdout);
+    }
+    for (output_file, size) in outcome.output_files.iter().zip(&outcome.output_sizes) {
+        if options.dry_run {
+            println!("Would write {} bytes to {}", size, output_file.display());
+        } else {
+            info!(options, "\nSaved manifest to: {}", output_file.display());
+        }
     }
+}
+
+// Generates the matching files, then regenerates each one that changes until Ctrl-C,
+// running the --exec command on its output files. Generation and command failures are
+// reported without ending the watch.
+fn watch_manifests(pattern: &str, exec: Option<&str>, options: &ProcessOptions) -> Result<()> {
+    if pattern == kamut::config::STDIN_PATTERN || kamut::fetch::is_url(pattern) {
+        return Err(anyhow::anyhow!(
+            "--watch needs a file pattern, not {}",
+            pattern
+        ));
+    }
+
+    if let Err(err) = generate_manifests(pattern, options) {
+        eprintln!("Error: {:?}", err);
+    }
+    status!(options, "Watching {} (press Ctrl-C to stop)", pattern);
+
+    kamut::watch::watch(pattern, |file_path| {
+        status!(options, "\n=====================");
+        let processed = process_and_report(file_path, options);
+        status!(options, "=====================\n");
+        let processed = processed?;
+        if let Some(exec) = exec {
+            for output in &processed.output_files {
+                run_exec(exec, output, options);
+            }
+        }
+        Ok(())
+    })?;
+
+    status!(options, "Stopped watching {}", pattern);
+    Ok(())
+}
+
+// Runs the --exec command for an output file and prints what it wrote
+fn run_exec(exec: &str, output: &Path, options: &ProcessOptions) {
+    let command_line = kamut::watch::exec_command_line(exec, output);
+    status!(options, "Running: {}", command_line);
+    match kamut::watch::run_exec(exec, output) {
+        Ok(result) => {
+            print!("{}", String::from_utf8_lossy(&result.stdout));
+            eprint!("{}", String::from_utf8_lossy(&result.stderr));
+            if !result.status.success() {
+                eprintln!("Warning: {} failed ({})", command_line, result.status);
+            }
+        }
+        Err(err) => eprintln!("Warning: {:#}", err),
+    }
+}
+
+// Rewrites the generated files matching the pattern in canonical form
+fn format_files(pattern: &str, options: &ProcessOptions) -> Result<()> {
+    let files = kamut::config::find_config_files(pattern)?;
+
+    let (mut formatted, mut unchanged, mut skipped) = (0, 0, 0);
+    for file_path in &files {
+        match kamut::fmt::format_file(file_path, options)? {
+            FmtOutcome::Formatted => {
+                println!("Formatted {}", file_path.display());
+                formatted += 1;
+            }
+            FmtOutcome::Unchanged => unchanged += 1,
+            FmtOutcome::Skipped => skipped += 1,
+        }
+    }
+
+    println!(
+        "{} formatted, {} unchanged, {} skipped without a kamut header",
+        formatted, unchanged, skipped
+    );
+    Ok(())
+}
+
+// Reports resources generated more than once with the same kind, namespace, and name,
+// across all files of the run; an error under --strict
+fn check_duplicates(outcomes: &[ProcessOutcome], options: &ProcessOptions) -> Result<()> {
+    let entries: Vec<ResourceEntry> = outcomes
+        .iter()
+        .flat_map(|outcome| {
+            outcome.resources.iter().map(|resource| ResourceEntry {
+                kind: resource.kind.clone(),
+                namespace: resource.namespace.clone(),
+                name: resource.name.clone(),
+                source: outcome.input.clone(),
+            })
+        })
+        .collect();
+    for message in kamut::inventory::find_duplicates(&entries) {
+        if options.strict {
+            return Err(anyhow::anyhow!(message));
+        }
+        status!(options, "\nWarning: {}", message);
+    }
+    Ok(())
+}
+
+// With --check-references, reports references to mistyped names of the ConfigMaps and
+// Secrets generated by all files of the run
+fn check_references(outcomes: &[ProcessOutcome], options: &ProcessOptions) -> Result<()> {
+    if !options.check_references {
+        return Ok(());
+    }
+
+    let manifests: Vec<String> = outcomes
+        .iter()
+        .flat_map(|outcome| &outcome.resources)
+        .map(|resource| resource.yaml.clone())
+        .collect();
+    for message in kamut::references::check_references(&manifests)? {
+        if options.strict {
+            return Err(anyhow::anyhow!(message));
+        }
+        status!(options, "\nWarning: {}", message);
+    }
+    Ok(())
+}
+
+// A --render-only filter that matched nothing is most likely a typo
+fn check_render_only(rendered: usize, options: &ProcessOptions) -> Result<()> {
+    if let Some(name) = &options.render_only {
+        if rendered == 0 {
+            return Err(anyhow::anyhow!("no matching document named {}", name));
+        }
+    }
+
+    Ok(())
+}
+
+// Writes a kustomization.yaml into every output directory, listing its generated files
+fn write_kustomizations(
+    outputs: &BTreeMap<PathBuf, Vec<PathBuf>>,
+    options: &ProcessOptions,
+) -> Result<()> {
+    for (dir, files) in outputs {
+        let path = dir.join(kamut::kustomize::KUSTOMIZATION_FILE_NAME);
+        if options.dry_run {
+            println!(
+                "Would write {} listing {} file(s)",
+                path.display(),
+                files.len()
+            );
+            continue;
+        }
+        match kamut::kustomize::write_kustomization(dir, files)? {
+            Some(path) => status!(options, "Saved kustomization to: {}", path.display()),
+            None => eprintln!(
+                "Warning: {} was not generated by kamut; leaving it unchanged",
+                path.display()
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn diff_against_cluster(pattern: &str, options: &ProcessOptions) -> Result<()> {
+    // Find matching files
+    let files = kamut::config::find_config_files(pattern)?;
+
+    if files.is_empty() {
+        if options.require_match {
+            return Err(KamutError::NoMatchingFiles(pattern.to_string()).into());
+        }
+        println!("No matching kamut files found for pattern: {}", pattern);
+        return Ok(());
+    }
+
+    // Render quietly so only the diff is printed
+    let options = ProcessOptions {
+        quiet: true,
+        ..options.clone()
+    };
+    let mut manifests = Vec::new();
+    for file_path in &files {
+        let file_options = kamut::kamutrc::options_for_file(file_path, &options)?;
+        manifests.extend(kamut::config::render_file(file_path, &file_options)?.manifests);
+    }
+    print!("{}", kamut::cluster::diff_against_cluster(&manifests)?);
+
+    Ok(())
+}
+
+fn list_resources(pattern: &str) -> Result<()> {
+    // Find matching files
+    let files = kamut::config::find_config_files(pattern)?;
+
+    if files.is_empty() {
+        println!("No matching kamut files found for pattern: {}", pattern);
+        return Ok(());
+    }
+
+    // Render quietly so only the table is printed
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let entries = kamut::inventory::list_resources(&files, &options)?;
+    print!("{}", kamut::inventory::format_resource_table(&entries));
+
+    Ok(())
+}
+
+fn validate_files(pattern: &str, max_errors: Option<usize>) -> Result<()> {
+    // Find matching files
+    let files = kamut::config::find_config_files(pattern)?;
+
+    if files.is_empty() {
+        println!("No matching kamut files found for pattern: {}", pattern);
+        return Ok(());
+    }
+
+    // Report every problem across all files before failing, up to --max-errors
+    let mut problems = Vec::new();
+    let mut checked = 0;
+    let mut stopped = false;
+    for file_path in &files {
+        checked += 1;
+        let file_options = kamut::kamutrc::options_for_file(file_path, &ProcessOptions::default())?;
+        match kamut::config::validate_file(file_path, &file_options) {
+            Ok(file_problems) => problems.extend(file_problems),
+            Err(err) => problems.push(format!("{:#}", err)),
+        }
+        if let Some(max_errors) = max_errors.filter(|max| problems.len() >= *max) {
+            stopped = problems.len() > max_errors || checked < files.len();
+            problems.truncate(max_errors);
+            break;
+        }
+    }
+
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("Error: {}", problem);
+        }
+        if stopped {
+            return Err(anyhow::anyhow!(
+                "stopped after {} problem(s) in {} of {} file(s) (--max-errors)",
+                problems.len(),
+                checked,
+                files.len()
+            ));
+        }
+        return Err(anyhow::anyhow!(
+            "{} problem(s) found in {} file(s)",
+            problems.len(),
+            files.len()
+        ));
+    }
+
+    println!("{} file(s) are valid", files.len());
+    Ok(())
+}
+
+fn export_helm(
+    dir: &Path,
+    pattern: &str,
+    chart_name: Option<&str>,
+    chart_version: &str,
+) -> Result<()> {
+    // Find matching files
+    let files = kamut::config::find_config_files(pattern)?;
+
+    if files.is_empty() {
+        println!("No matching kamut files found for pattern: {}", pattern);
+        return Ok(());
+    }
+
+    // Default the chart name to the directory name
+    let chart_name = match chart_name {
+        Some(chart_name) => chart_name.to_string(),
+        None => dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("--chart-name is required for {}", dir.display()))?
+            .to_string(),
+    };
+    let chart = kamut::helm::ChartMetadata::new(&chart_name, chart_version);
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let templates = kamut::helm::export_helm_chart(&files, dir, &chart, &options)?;
+    println!(
+        "Exported {} templates to Helm chart {}",
+        templates.len(),
+        dir.display()
+    );
 
     Ok(())
 }
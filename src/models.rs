@@ -1,28 +1,90 @@
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
 use kube_custom_resources_rs::monitoring_coreos_com::v1::prometheuses::PrometheusSpec;
+use kube_custom_resources_rs::monitoring_coreos_com::v1alpha1::scrapeconfigs::ScrapeConfigRelabelings;
 // ScrapeConfig is used directly in config.rs
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Deserialize)]
+use crate::error::KamutError;
+use crate::registry;
+
+#[derive(Debug, Deserialize, Clone)]
 #[serde(default)]
 pub struct KamutConfig {
     pub name: String,
     pub kind: Option<String>,
     pub namespace: Option<String>,
+    /// Condition deciding whether the document is rendered (see `condition::evaluate`)
+    pub when: Option<String>,
+    /// apiVersion written over the generated custom resource's, for operators serving a
+    /// different version of a compatible CRD
+    pub api_version_override: Option<String>,
+    /// ArgoCD sync wave of every resource the document generates, overriding the wave
+    /// `--sync-waves` derives from their kinds
+    pub sync_wave: Option<i32>,
     pub image: Option<String>,
-    pub env: Option<HashMap<String, String>>,
+    /// Overrides the image's entrypoint
+    pub command: Vec<String>,
+    /// Arguments passed to the entrypoint
+    pub args: Vec<String>,
+    pub env: Option<EnvConfig>,
+    /// Environment variables read from Secret or ConfigMap keys, added after `env`
+    pub env_from_refs: Option<Vec<EnvFromRefConfig>>,
+    /// ConfigMaps and Secrets whose keys all become environment variables
+    pub env_from: Option<Vec<EnvFromConfig>>,
     pub resources: Option<Resources>,
-    pub storage: Option<Storage>,
+    pub storage: Option<StorageConfig>,
     pub node_selector: Option<HashMap<String, String>>,
+    /// Taints a Deployment's pods tolerate
+    pub tolerations: Option<Vec<TolerationConfig>>,
+    /// Containers started before a Deployment's container, including native sidecars
+    pub init_containers: Vec<InitContainerConfig>,
+    /// Containers run next to a Deployment's container, e.g. a log shipper
+    pub containers: Vec<ContainerConfig>,
+    pub resize_policy: Option<Vec<ResizePolicy>>,
+    pub probes: Option<Probes>,
+    /// Hooks the kubelet runs around the workload container's lifetime
+    pub lifecycle: Option<LifecycleConfig>,
+    /// Seconds a pod gets to shut down after SIGTERM before it is killed
+    pub termination_grace_period_seconds: Option<i64>,
+    pub ports: Option<Vec<ContainerPortConfig>>,
+    pub security_context: Option<SecurityContextConfig>,
+    /// HorizontalPodAutoscaler generated alongside a Deployment
+    pub autoscaling: Option<AutoscalingConfig>,
+    /// KEDA ScaledObject generated alongside a Deployment, instead of `autoscaling`
+    pub keda: Option<KedaConfig>,
+    /// Rollout strategy of a Deployment
+    pub strategy: Option<StrategyConfig>,
+    /// ConfigMaps and Secrets mounted into a Deployment's container
+    pub volumes: Option<Vec<VolumeConfig>>,
+    /// Secrets used to pull the image from a private registry
+    pub image_pull_secrets: Vec<String>,
+    /// Existing ServiceAccount the workload's pods run as; kamut does not create it
+    pub service_account_name: Option<String>,
+
+    /// Number of independent copies of a Deployment, named `<name>-0`, `<name>-1`, ...
+    pub copies: Option<u32>,
 
     // Prometheus specific fields
     pub replicas: Option<i32>,
     pub retention: Option<String>,
+    /// Maximum size of the Prometheus TSDB, e.g. `50GB`
+    pub retention_size: Option<String>,
+    pub mode: Option<String>,
     pub ingress: Option<Ingress>,
+    pub service: Option<ServiceConfig>,
     pub service_account: Option<ServiceAccount>,
+    pub monitor_namespace: Option<MonitorNamespace>,
+    pub scrape_classes: Option<Vec<ScrapeClass>>,
+    /// Generate a ServiceMonitor so Prometheus scrapes itself through its Service
+    pub self_monitor: Option<bool>,
+    /// Labels Prometheus attaches to series and alerts it sends, e.g. to federation
+    pub external_labels: HashMap<String, String>,
+    /// Endpoints Prometheus forwards its samples to
+    pub remote_write: Vec<RemoteWriteConfig>,
     
     // ScrapeConfig specific fields
     pub role: Option<String>,
@@ -36,6 +98,42 @@ pub struct KamutConfig {
     pub metrics_path: Option<String>,
     pub labels: Option<HashMap<String, String>>,
     pub port: Option<String>,
+    #[serde(rename = "apiVersion")]
+    pub api_version: Option<String>,
+    /// Custom relabelings appended after the generated defaults
+    pub relabelings: Option<Vec<ScrapeConfigRelabelings>>,
+    /// Use only the custom `relabelings`, dropping the generated defaults
+    #[serde(rename = "replaceDefaultRelabelings")]
+    pub replace_default_relabelings: Option<bool>,
+
+    // PriorityClass specific fields
+    pub value: Option<i32>,
+    #[serde(rename = "globalDefault")]
+    pub global_default: Option<bool>,
+    pub description: Option<String>,
+
+    // ConfigMap specific fields
+    /// ConfigMap data, or Secret values that kamut base64-encodes into the Secret's `data`
+    pub data: Option<HashMap<String, String>>,
+
+    // Secret specific fields
+    /// Secret values emitted as plain-text `stringData`
+    pub string_data: Option<HashMap<String, String>>,
+    /// Secret type (default: `Opaque`), e.g. `kubernetes.io/dockerconfigjson`
+    pub secret_type: Option<String>,
+
+    // Endpoints specific fields
+    pub endpoints: Option<EndpointsConfig>,
+
+    // AlertmanagerConfig specific fields
+    /// Route sending the namespace's alerts to a receiver
+    pub route: Option<AlertmanagerRouteConfig>,
+    /// Receivers the route can send alerts to
+    pub receivers: Option<Vec<AlertmanagerReceiverConfig>>,
+
+    // NetworkPolicy specific fields
+    /// Traffic allowed to and from the pods labeled `app: <name>`
+    pub network_policy: Option<NetworkPolicyConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
@@ -43,8 +141,69 @@ pub struct ServiceAccount {
     #[serde(default = "default_true")]
     pub create: bool,
     pub annotations: Option<HashMap<String, String>>,
+    /// Labels added to the ServiceAccount, ClusterRole, and ClusterRoleBinding only
+    pub labels: Option<HashMap<String, String>>,
     #[serde(default)]
     pub cluster_role: Option<bool>,
+    /// Rules of the ClusterRole, replacing the built-in Prometheus rules
+    pub cluster_role_rules: Option<Vec<PolicyRuleConfig>>,
+    /// `cluster` (default) for a ClusterRole and ClusterRoleBinding, or `namespace` for a
+    /// Role and RoleBinding in the Prometheus namespace
+    pub scope: Option<String>,
+    /// Kubernetes discovery roles Prometheus scrapes (`node`, `pod`, `service`, `endpoints`,
+    /// `endpointslice`, `ingress`); the built-in rules then only grant what they need
+    pub rbac_scope: Option<Vec<String>>,
+}
+
+/// A ClusterRole rule, emitted as written
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct PolicyRuleConfig {
+    /// API groups of the `resources`; `""` is the core group
+    pub api_groups: Vec<String>,
+    pub resources: Vec<String>,
+    pub verbs: Vec<String>,
+    /// Non-resource URLs such as `/metrics`, instead of `resources`
+    pub non_resource_urls: Vec<String>,
+}
+
+/// Namespaces in which Prometheus discovers ServiceMonitors and PodMonitors:
+/// `all`, `own` (the Prometheus namespace), or an explicit list of namespaces
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum MonitorNamespace {
+    Mode(String),
+    Names(Vec<String>),
+}
+
+/// Prometheus scrape class providing default settings for scrapes (operator v0.73+)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct ScrapeClass {
+    pub name: Option<String>,
+    /// Whether this class applies to scrape objects that don't select a class
+    pub default: Option<bool>,
+    pub tls_config: Option<ScrapeClassTlsConfig>,
+}
+
+/// TLS files used by a scrape class; the operator only supports file references here
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct ScrapeClassTlsConfig {
+    pub ca_file: Option<String>,
+    pub cert_file: Option<String>,
+    pub key_file: Option<String>,
+}
+
+/// A Prometheus remote-write endpoint
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct RemoteWriteConfig {
+    /// URL of the endpoint receiving the samples
+    pub url: String,
+    /// Name of the queue, shown in metrics and logs
+    pub name: Option<String>,
+    /// Timeout of each request to the endpoint (e.g. `30s`)
+    pub remote_timeout: Option<String>,
+    /// HTTP headers sent with every request
+    pub headers: Option<HashMap<String, String>>,
 }
 
 fn default_true() -> bool {
@@ -57,15 +216,46 @@ impl Default for KamutConfig {
             name: "default".to_string(),
             kind: None,
             namespace: None,
+            when: None,
+            api_version_override: None,
+            sync_wave: None,
             image: None,
+            command: Vec::new(),
+            args: Vec::new(),
             env: None,
+            env_from_refs: None,
+            env_from: None,
             resources: None,
             storage: None,
             node_selector: None,
+            tolerations: None,
+            init_containers: Vec::new(),
+            containers: Vec::new(),
+            resize_policy: None,
+            probes: None,
+            lifecycle: None,
+            termination_grace_period_seconds: None,
+            ports: None,
+            security_context: None,
+            autoscaling: None,
+            keda: None,
+            strategy: None,
+            volumes: None,
+            image_pull_secrets: Vec::new(),
+            service_account_name: None,
+            copies: None,
             replicas: None,
             retention: None,
+            retention_size: None,
+            mode: None,
             ingress: None,
+            service: None,
             service_account: None,
+            monitor_namespace: None,
+            scrape_classes: None,
+            self_monitor: None,
+            external_labels: HashMap::new(),
+            remote_write: Vec::new(),
             role: None,
             scrape_interval: None,
             scrape_timeout: None,
@@ -73,6 +263,423 @@ impl Default for KamutConfig {
             metrics_path: None,
             labels: None,
             port: None,
+            api_version: None,
+            relabelings: None,
+            replace_default_relabelings: None,
+            value: None,
+            global_default: None,
+            description: None,
+            data: None,
+            string_data: None,
+            secret_type: None,
+            endpoints: None,
+            route: None,
+            receivers: None,
+            network_policy: None,
+        }
+    }
+}
+
+/// Name of the defaults file applied to the kamut files of its directory
+pub const DEFAULTS_FILE_NAME: &str = "kamut.defaults.yaml";
+
+/// Fields that only apply to specific kinds; fields not listed here apply to every kind
+pub const KIND_SPECIFIC_FIELDS: &[(&str, &[&str])] = &[
+    (
+        "api_version_override",
+        &["Prometheus", "KubeScrapeConfig", "AlertmanagerConfig"],
+    ),
+    (
+        "image",
+        &[
+            "Deployment",
+            "StatefulSet",
+            "DaemonSet",
+            "Prometheus",
+            "Alertmanager",
+        ],
+    ),
+    ("command", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("args", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("env", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("env_from_refs", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("env_from", &["Deployment", "StatefulSet", "DaemonSet"]),
+    (
+        "resources",
+        &[
+            "Deployment",
+            "StatefulSet",
+            "DaemonSet",
+            "Prometheus",
+            "Alertmanager",
+        ],
+    ),
+    ("resize_policy", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("probes", &["Deployment", "StatefulSet", "DaemonSet"]),
+    ("lifecycle", &["Deployment", "StatefulSet", "DaemonSet"]),
+    (
+        "termination_grace_period_seconds",
+        &["Deployment", "StatefulSet", "DaemonSet"],
+    ),
+    ("ports", &["Deployment", "StatefulSet", "DaemonSet"]),
+    (
+        "security_context",
+        &["Deployment", "StatefulSet", "DaemonSet", "Prometheus"],
+    ),
+    ("autoscaling", &["Deployment"]),
+    ("keda", &["Deployment"]),
+    ("strategy", &["Deployment"]),
+    ("copies", &["Deployment"]),
+    ("volumes", &["Deployment"]),
+    (
+        "image_pull_secrets",
+        &["Deployment", "StatefulSet", "DaemonSet"],
+    ),
+    (
+        "service_account_name",
+        &["Deployment", "StatefulSet", "DaemonSet"],
+    ),
+    ("storage", &["StatefulSet", "Prometheus", "Alertmanager"]),
+    (
+        "node_selector",
+        &["Deployment", "StatefulSet", "DaemonSet", "Prometheus"],
+    ),
+    ("tolerations", &["Deployment", "DaemonSet"]),
+    ("init_containers", &["Deployment"]),
+    ("containers", &["Deployment"]),
+    (
+        "replicas",
+        &["Deployment", "StatefulSet", "Prometheus", "Alertmanager"],
+    ),
+    ("retention", &["Prometheus", "Alertmanager"]),
+    ("retention_size", &["Prometheus"]),
+    ("mode", &["Prometheus"]),
+    ("ingress", &["Prometheus", "Ingress"]),
+    ("service", &["Deployment", "Prometheus"]),
+    ("service_account", &["Prometheus"]),
+    ("monitor_namespace", &["Prometheus"]),
+    ("scrape_classes", &["Prometheus"]),
+    ("self_monitor", &["Prometheus"]),
+    ("external_labels", &["Prometheus"]),
+    ("remote_write", &["Prometheus"]),
+    ("role", &["KubeScrapeConfig"]),
+    ("scrapeInterval", &["KubeScrapeConfig", "ServiceMonitor"]),
+    ("scrapeTimeout", &["KubeScrapeConfig", "ServiceMonitor"]),
+    ("scrapeNamespace", &["KubeScrapeConfig"]),
+    ("metricsPath", &["KubeScrapeConfig", "ServiceMonitor"]),
+    ("labels", &["KubeScrapeConfig", "ServiceMonitor"]),
+    ("port", &["KubeScrapeConfig", "ServiceMonitor"]),
+    ("apiVersion", &["KubeScrapeConfig"]),
+    ("relabelings", &["KubeScrapeConfig"]),
+    ("replaceDefaultRelabelings", &["KubeScrapeConfig"]),
+    ("value", &["PriorityClass"]),
+    ("globalDefault", &["PriorityClass"]),
+    ("description", &["PriorityClass"]),
+    ("data", &["ConfigMap", "Secret"]),
+    ("string_data", &["Secret"]),
+    ("secret_type", &["Secret"]),
+    ("endpoints", &["Endpoints"]),
+    ("route", &["AlertmanagerConfig"]),
+    ("receivers", &["AlertmanagerConfig"]),
+    ("network_policy", &["NetworkPolicy"]),
+];
+
+/// Returns the fields set in a document that are ignored by its kind
+pub fn ignored_fields(kind: &str, document: &str) -> Vec<String> {
+    let Ok(serde_yaml::Value::Mapping(mapping)) = serde_yaml::from_str(document) else {
+        return Vec::new();
+    };
+
+    // Unsupported kinds are reported separately
+    if !KIND_SPECIFIC_FIELDS
+        .iter()
+        .any(|(_, kinds)| kinds.contains(&kind))
+    {
+        return Vec::new();
+    }
+
+    mapping
+        .keys()
+        .filter_map(|key| key.as_str())
+        .filter(|key| {
+            KIND_SPECIFIC_FIELDS
+                .iter()
+                .any(|(field, kinds)| field == key && !kinds.contains(&kind))
+        })
+        .map(|key| key.to_string())
+        .collect()
+}
+
+/// Fills the fields a document leaves unset (`None`, or empty lists and maps) from
+/// `defaults`; `name` and `kind` always come from the document.
+///
+/// ```
+/// use kamut::models::{merge_defaults, KamutConfig};
+///
+/// let config: KamutConfig = serde_yaml::from_str("name: api\nreplicas: 5").unwrap();
+/// let defaults: KamutConfig = serde_yaml::from_str("replicas: 3\nimage: api:v1").unwrap();
+/// let config = merge_defaults(config, &defaults);
+/// assert_eq!(config.replicas, Some(5));
+/// assert_eq!(config.image.as_deref(), Some("api:v1"));
+/// ```
+pub fn merge_defaults(config: KamutConfig, defaults: &KamutConfig) -> KamutConfig {
+    // Destructured without `..` so a new field cannot be forgotten here
+    let KamutConfig {
+        name: _,
+        kind: _,
+        namespace,
+        when,
+        api_version_override,
+        sync_wave,
+        image,
+        command,
+        args,
+        env,
+        env_from_refs,
+        env_from,
+        resources,
+        storage,
+        node_selector,
+        tolerations,
+        init_containers,
+        containers,
+        resize_policy,
+        probes,
+        lifecycle,
+        termination_grace_period_seconds,
+        ports,
+        security_context,
+        autoscaling,
+        keda,
+        strategy,
+        volumes,
+        image_pull_secrets,
+        service_account_name,
+        copies,
+        replicas,
+        retention,
+        retention_size,
+        mode,
+        ingress,
+        service,
+        service_account,
+        monitor_namespace,
+        scrape_classes,
+        self_monitor,
+        external_labels,
+        remote_write,
+        role,
+        scrape_interval,
+        scrape_timeout,
+        scrape_namespace,
+        metrics_path,
+        labels,
+        port,
+        api_version,
+        relabelings,
+        replace_default_relabelings,
+        value,
+        global_default,
+        description,
+        data,
+        string_data,
+        secret_type,
+        endpoints,
+        route,
+        receivers,
+        network_policy,
+    } = defaults.clone();
+
+    let mut config = config;
+    config.namespace = config.namespace.or(namespace);
+    config.when = config.when.or(when);
+    config.api_version_override = config.api_version_override.or(api_version_override);
+    config.sync_wave = config.sync_wave.or(sync_wave);
+    config.image = config.image.or(image);
+    config.env = config.env.or(env);
+    config.env_from_refs = config.env_from_refs.or(env_from_refs);
+    config.env_from = config.env_from.or(env_from);
+    config.resources = config.resources.or(resources);
+    config.storage = config.storage.or(storage);
+    config.node_selector = config.node_selector.or(node_selector);
+    config.tolerations = config.tolerations.or(tolerations);
+    config.resize_policy = config.resize_policy.or(resize_policy);
+    config.probes = config.probes.or(probes);
+    config.lifecycle = config.lifecycle.or(lifecycle);
+    config.termination_grace_period_seconds = config
+        .termination_grace_period_seconds
+        .or(termination_grace_period_seconds);
+    config.ports = config.ports.or(ports);
+    config.security_context = config.security_context.or(security_context);
+    config.autoscaling = config.autoscaling.or(autoscaling);
+    config.keda = config.keda.or(keda);
+    config.strategy = config.strategy.or(strategy);
+    config.volumes = config.volumes.or(volumes);
+    config.service_account_name = config.service_account_name.or(service_account_name);
+    config.copies = config.copies.or(copies);
+    config.replicas = config.replicas.or(replicas);
+    config.retention = config.retention.or(retention);
+    config.retention_size = config.retention_size.or(retention_size);
+    config.mode = config.mode.or(mode);
+    config.ingress = config.ingress.or(ingress);
+    config.service = config.service.or(service);
+    config.service_account = config.service_account.or(service_account);
+    config.monitor_namespace = config.monitor_namespace.or(monitor_namespace);
+    config.scrape_classes = config.scrape_classes.or(scrape_classes);
+    config.self_monitor = config.self_monitor.or(self_monitor);
+    config.role = config.role.or(role);
+    config.scrape_interval = config.scrape_interval.or(scrape_interval);
+    config.scrape_timeout = config.scrape_timeout.or(scrape_timeout);
+    config.scrape_namespace = config.scrape_namespace.or(scrape_namespace);
+    config.metrics_path = config.metrics_path.or(metrics_path);
+    config.labels = config.labels.or(labels);
+    config.port = config.port.or(port);
+    config.api_version = config.api_version.or(api_version);
+    config.relabelings = config.relabelings.or(relabelings);
+    config.replace_default_relabelings = config
+        .replace_default_relabelings
+        .or(replace_default_relabelings);
+    config.value = config.value.or(value);
+    config.global_default = config.global_default.or(global_default);
+    config.description = config.description.or(description);
+    config.data = config.data.or(data);
+    config.string_data = config.string_data.or(string_data);
+    config.secret_type = config.secret_type.or(secret_type);
+    config.endpoints = config.endpoints.or(endpoints);
+    config.route = config.route.or(route);
+    config.receivers = config.receivers.or(receivers);
+    config.network_policy = config.network_policy.or(network_policy);
+    if config.command.is_empty() {
+        config.command = command;
+    }
+    if config.args.is_empty() {
+        config.args = args;
+    }
+    if config.init_containers.is_empty() {
+        config.init_containers = init_containers;
+    }
+    if config.containers.is_empty() {
+        config.containers = containers;
+    }
+    if config.image_pull_secrets.is_empty() {
+        config.image_pull_secrets = image_pull_secrets;
+    }
+    if config.external_labels.is_empty() {
+        config.external_labels = external_labels;
+    }
+    if config.remote_write.is_empty() {
+        config.remote_write = remote_write;
+    }
+    config
+}
+
+/// Fallback values of a defaults file (`kamut.defaults.yaml` or `--defaults`): top-level
+/// fields apply to every document, and the fields under `kinds.<Kind>` to documents of
+/// that kind, winning over the top-level ones
+#[derive(Debug, Clone, Default)]
+pub struct KamutDefaults {
+    pub all: KamutConfig,
+    pub kinds: HashMap<String, KamutConfig>,
+}
+
+impl KamutDefaults {
+    /// Parses the content of a defaults file
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, KamutError> {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+        if value.is_null() {
+            return Ok(KamutDefaults::default());
+        }
+        let kinds = match value
+            .as_mapping_mut()
+            .and_then(|mapping| mapping.remove("kinds"))
+        {
+            Some(kinds) => serde_yaml::from_value(kinds)?,
+            None => HashMap::new(),
+        };
+        Ok(KamutDefaults {
+            all: serde_yaml::from_value(value)?,
+            kinds,
+        })
+    }
+
+    /// Fills the unset fields of a document from the defaults of its kind, then from the
+    /// top-level defaults
+    pub fn apply(&self, config: KamutConfig) -> KamutConfig {
+        let config = match config.kind.as_deref().and_then(|kind| self.kinds.get(kind)) {
+            Some(kind_defaults) => merge_defaults(config, kind_defaults),
+            None => config,
+        };
+        merge_defaults(config, &self.all)
+    }
+}
+
+impl KamutConfig {
+    /// Parses a single kamut document and runs the same validation as `process_file`.
+    ///
+    /// ```
+    /// use kamut::{KamutConfig, KamutError};
+    ///
+    /// let err = KamutConfig::from_yaml_str("name: api\nimage: api:v1.0.0").unwrap_err();
+    /// assert!(matches!(err, KamutError::MissingField("kind")));
+    /// assert_eq!(err.to_string(), "'kind' field is required");
+    /// ```
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, KamutError> {
+        let config: KamutConfig = serde_yaml::from_str(yaml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the fields required for every document
+    pub fn validate(&self) -> Result<(), KamutError> {
+        if self.kind.is_none() {
+            return Err(KamutError::MissingField("kind"));
+        }
+        Ok(())
+    }
+
+    /// Checks the fields required by the document's kind
+    pub fn validate_kind(&self) -> Result<(), KamutError> {
+        match self.kind.as_deref().unwrap_or_default() {
+            "Deployment" | "StatefulSet" | "DaemonSet" | "Prometheus" | "Alertmanager"
+                if self.image.is_none() =>
+            {
+                Err(KamutError::MissingField("image"))
+            }
+            "KubeScrapeConfig" if self.role.is_none() => Err(KamutError::MissingField("role")),
+            "ServiceMonitor" if self.port.is_none() => Err(KamutError::MissingField("port")),
+            "PriorityClass" if self.value.is_none() => Err(KamutError::MissingField("value")),
+            "ConfigMap" if self.data.as_ref().is_none_or(|data| data.is_empty()) => {
+                Err(KamutError::MissingField("data"))
+            }
+            "Secret"
+                if self.string_data.as_ref().is_none_or(|data| data.is_empty())
+                    && self.data.as_ref().is_none_or(|data| data.is_empty()) =>
+            {
+                Err(KamutError::MissingField("string_data"))
+            }
+            "Ingress" if self.ingress.is_none() => Err(KamutError::MissingField("ingress")),
+            "Endpoints"
+                if self
+                    .endpoints
+                    .as_ref()
+                    .is_none_or(|endpoints| endpoints.addresses.is_empty()) =>
+            {
+                Err(KamutError::MissingField("endpoints.addresses"))
+            }
+            "AlertmanagerConfig" if self.route.is_none() => Err(KamutError::MissingField("route")),
+            "AlertmanagerConfig"
+                if self
+                    .receivers
+                    .as_ref()
+                    .is_none_or(|receivers| receivers.is_empty()) =>
+            {
+                Err(KamutError::MissingField("receivers"))
+            }
+            "NetworkPolicy" if self.network_policy.is_none() => {
+                Err(KamutError::MissingField("network_policy"))
+            }
+            kind if registry::is_registered(kind) => Ok(()),
+            kind => Err(KamutError::UnsupportedKind(kind.to_string())),
         }
     }
 }
@@ -82,14 +689,422 @@ impl Default for ServiceAccount {
         ServiceAccount {
             create: true,
             annotations: None,
+            labels: None,
             cluster_role: Some(true),
+            cluster_role_rules: None,
+            scope: None,
+            rbac_scope: None,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
 pub struct Ingress {
+    /// Shorthand for a single rule on this host, routing `paths` (default: `/`)
+    pub host: Option<String>,
+    /// Paths routed on `host`
+    #[serde(default)]
+    pub paths: Vec<IngressPathConfig>,
+    /// Rules with their own hosts and paths, added after the `host` shorthand
+    #[serde(default)]
+    pub rules: Vec<IngressRuleConfig>,
+    /// IngressClass handling this Ingress (`spec.ingressClassName`)
+    pub class_name: Option<String>,
+    /// Secret holding the TLS certificate; enables HTTPS termination
+    pub tls_secret_name: Option<String>,
+    /// Hosts covered by the TLS certificate (defaults to `host`)
+    pub tls_hosts: Option<Vec<String>>,
+    /// Annotations for the ingress controller (e.g. `nginx.ingress.kubernetes.io/ssl-redirect`)
+    pub annotations: Option<HashMap<String, String>>,
+    /// HTTPS shorthands
+    pub tls: Option<IngressTls>,
+}
+
+/// An Ingress rule routing paths of a host
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct IngressRuleConfig {
     pub host: String,
+    /// Paths routed on the host (default: `/`)
+    #[serde(default)]
+    pub paths: Vec<IngressPathConfig>,
+}
+
+/// A path of an Ingress rule
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct IngressPathConfig {
+    pub path: String,
+    /// `Prefix` (default), `Exact`, or `ImplementationSpecific`
+    pub path_type: Option<String>,
+    /// Backend Service (default: the Prometheus Service; required for `kind: Ingress`)
+    pub service: Option<String>,
+    /// Backend Service port (default: the Prometheus Service port; required for `kind: Ingress`)
+    pub port: Option<i32>,
+}
+
+/// Shorthand TLS settings for the Ingress
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct IngressTls {
+    /// cert-manager ClusterIssuer; adds the issuer annotation and a TLS entry
+    /// with a `<host>-tls` secret (unless `tls_secret_name` is set)
+    pub cert_manager_issuer: Option<String>,
+}
+
+/// Container environment variables, either a map or a list that keeps the declared order
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(untagged)]
+pub enum EnvConfig {
+    Map(HashMap<String, String>),
+    /// Emitted in the given order, so later values can reference earlier ones with `$(NAME)`
+    List(Vec<EnvVarConfig>),
+}
+
+impl EnvConfig {
+    /// Returns the variables as name/value pairs; list entries keep their order and map
+    /// entries are sorted by name, so the output is the same on every run
+    pub fn vars(&self) -> Vec<(&str, &str)> {
+        match self {
+            EnvConfig::Map(vars) => {
+                let mut vars: Vec<(&str, &str)> = vars
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect();
+                vars.sort();
+                vars
+            }
+            EnvConfig::List(vars) => vars
+                .iter()
+                .map(|var| (var.name.as_str(), var.value.as_str()))
+                .collect(),
+        }
+    }
+
+    /// Returns the value of the named variable
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars()
+            .into_iter()
+            .find(|(var_name, _)| *var_name == name)
+            .map(|(_, value)| value)
+    }
+}
+
+impl From<HashMap<String, String>> for EnvConfig {
+    fn from(vars: HashMap<String, String>) -> Self {
+        EnvConfig::Map(vars)
+    }
+}
+
+/// An entry of the ordered `env` list
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct EnvVarConfig {
+    pub name: String,
+    pub value: String,
+}
+
+/// An environment variable set from a key of a Secret or ConfigMap; exactly one of
+/// `secret` and `config_map` must be set
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct EnvFromRefConfig {
+    /// Name of the environment variable
+    pub name: String,
+    /// Name of the Secret holding the key
+    pub secret: Option<String>,
+    /// Name of the ConfigMap holding the key
+    pub config_map: Option<String>,
+    pub key: String,
+}
+
+/// A ConfigMap or Secret whose keys are all imported as environment variables; exactly one
+/// of `config_map` and `secret` must be set
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct EnvFromConfig {
+    /// Name of the ConfigMap to import
+    pub config_map: Option<String>,
+    /// Name of the Secret to import
+    pub secret: Option<String>,
+    /// Prepended to every imported key, e.g. `APP_`, to avoid collisions
+    pub prefix: Option<String>,
+}
+
+/// A ConfigMap or Secret mounted into the container; exactly one of
+/// `config_map` and `secret` must be set
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct VolumeConfig {
+    pub name: String,
+    /// Name of the ConfigMap to mount
+    pub config_map: Option<String>,
+    /// Name of the Secret to mount
+    pub secret: Option<String>,
+    pub mount_path: String,
+}
+
+/// Addresses and ports behind a selectorless Service of the same name,
+/// e.g. an external database
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct EndpointsConfig {
+    /// IP addresses of the backends
+    pub addresses: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<EndpointPortConfig>,
+}
+
+/// A port served by every address of an `Endpoints` resource
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct EndpointPortConfig {
+    /// Must match the name of the Service port when the Service has several ports
+    pub name: Option<String>,
+    pub port: i32,
+    /// Protocol (default: TCP)
+    pub protocol: Option<String>,
+}
+
+/// Route of an AlertmanagerConfig; the operator only applies it to alerts from the
+/// resource's namespace
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct AlertmanagerRouteConfig {
+    /// Name of the receiver; must be listed in `receivers`
+    pub receiver: String,
+    /// Label matchers an alert must satisfy
+    #[serde(default)]
+    pub matchers: Vec<AlertmanagerMatcherConfig>,
+    /// Labels alerts are grouped by into one notification
+    #[serde(default)]
+    pub group_by: Vec<String>,
+    /// Wait before the first notification of a group, e.g. `30s`
+    pub group_wait: Option<String>,
+    /// Wait before notifying about new alerts of a group, e.g. `5m`
+    pub group_interval: Option<String>,
+    /// Wait before repeating a notification, e.g. `4h`
+    pub repeat_interval: Option<String>,
+}
+
+/// A label matcher of an AlertmanagerConfig route
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct AlertmanagerMatcherConfig {
+    pub name: String,
+    pub value: String,
+    /// `=` (default), `!=`, `=~`, or `!~`
+    pub match_type: Option<String>,
+}
+
+/// A notification receiver of an AlertmanagerConfig
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct AlertmanagerReceiverConfig {
+    pub name: String,
+    #[serde(default)]
+    pub webhook_configs: Vec<WebhookReceiverConfig>,
+    #[serde(default)]
+    pub slack_configs: Vec<SlackReceiverConfig>,
+}
+
+/// A webhook notification; exactly one of `url` and `url_secret` must be set
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct WebhookReceiverConfig {
+    pub url: Option<String>,
+    /// Secret key holding the URL, for URLs that embed credentials
+    pub url_secret: Option<SecretKeyConfig>,
+    /// Also notify when alerts are resolved
+    pub send_resolved: Option<bool>,
+}
+
+/// A Slack notification
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct SlackReceiverConfig {
+    /// Secret key holding the Slack webhook URL
+    pub api_url_secret: SecretKeyConfig,
+    /// Channel or user to notify (default: the webhook's channel)
+    pub channel: Option<String>,
+    /// Also notify when alerts are resolved
+    pub send_resolved: Option<bool>,
+}
+
+/// A key of a Secret in the resource's namespace
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct SecretKeyConfig {
+    pub name: String,
+    pub key: String,
+}
+
+/// Rules of a NetworkPolicy. A direction is only restricted when its list is given, and
+/// an empty list denies all traffic in that direction.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct NetworkPolicyConfig {
+    /// Traffic allowed into the pods
+    pub ingress: Option<Vec<NetworkPolicyIngressRuleConfig>>,
+    /// Traffic allowed out of the pods
+    pub egress: Option<Vec<NetworkPolicyEgressRuleConfig>>,
+}
+
+/// Incoming traffic allowed from any of `from` (every source when empty) to any of
+/// `ports` (every port when empty)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct NetworkPolicyIngressRuleConfig {
+    pub from: Vec<NetworkPolicyPeerConfig>,
+    pub ports: Vec<NetworkPolicyPortConfig>,
+}
+
+/// Outgoing traffic allowed to any of `to` (every destination when empty) on any of
+/// `ports` (every port when empty)
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct NetworkPolicyEgressRuleConfig {
+    pub to: Vec<NetworkPolicyPeerConfig>,
+    pub ports: Vec<NetworkPolicyPortConfig>,
+}
+
+/// Pods selected by labels (in the policy's namespace, or in the namespaces matched by
+/// `namespace_selector`), or an IP range
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct NetworkPolicyPeerConfig {
+    pub pod_selector: Option<HashMap<String, String>>,
+    /// Labels of the namespaces; an empty map matches every namespace
+    pub namespace_selector: Option<HashMap<String, String>>,
+    /// CIDR range, e.g. `10.0.0.0/8`; cannot be combined with the selectors
+    pub cidr: Option<String>,
+    /// CIDR ranges excluded from `cidr`
+    #[serde(default)]
+    pub except: Vec<String>,
+}
+
+/// A port of a NetworkPolicy rule
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct NetworkPolicyPortConfig {
+    /// Port number or named container port
+    #[schemars(with = "String")]
+    pub port: IntOrString,
+    /// `TCP` (default), `UDP`, or `SCTP`
+    pub protocol: Option<String>,
+}
+
+/// CPU-based autoscaling for a Deployment
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct AutoscalingConfig {
+    /// Lower replica bound (default: 1)
+    pub min_replicas: Option<i32>,
+    pub max_replicas: i32,
+    /// Average CPU utilization, as a percentage of requests, the HPA aims for
+    pub target_cpu_utilization: Option<i32>,
+}
+
+/// Event-driven autoscaling of a Deployment through a KEDA ScaledObject, which unlike the
+/// HorizontalPodAutoscaler can scale to zero
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct KedaConfig {
+    /// Lower replica bound; 0 scales to zero (KEDA default: 0)
+    pub min_replicas: Option<i32>,
+    /// Upper replica bound (KEDA default: 100)
+    pub max_replicas: Option<i32>,
+    /// Seconds between trigger checks (KEDA default: 30)
+    pub polling_interval: Option<i32>,
+    /// Seconds after the last active trigger before scaling to `min_replicas` (KEDA default: 300)
+    pub cooldown_period: Option<i32>,
+    /// Scalers; at least one is required
+    pub triggers: Vec<KedaTriggerConfig>,
+}
+
+/// A KEDA scaler, passed through to the ScaledObject
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct KedaTriggerConfig {
+    /// Scaler type, e.g. `cron` or `prometheus`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Optional trigger name
+    pub name: Option<String>,
+    /// Scaler-specific settings
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Rollout strategy of a Deployment; the surge and unavailability bounds only apply to
+/// `RollingUpdate`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct StrategyConfig {
+    /// `RollingUpdate` (default) or `Recreate`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// Pods created above the desired count during a rollout, e.g. `1` or `25%`
+    #[schemars(with = "Option<String>")]
+    pub max_surge: Option<IntOrString>,
+    /// Pods that may be unavailable during a rollout, e.g. `0` or `25%`
+    #[schemars(with = "Option<String>")]
+    pub max_unavailable: Option<IntOrString>,
+}
+
+/// A container run before the main container of a Deployment. With `restart_policy: Always`
+/// it is a native sidecar (Kubernetes 1.28+): started in order before the main container,
+/// kept running alongside it, and stopped after it.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct InitContainerConfig {
+    pub name: String,
+    pub image: String,
+    pub command: Vec<String>,
+    pub args: Vec<String>,
+    /// Environment variables, as for the main container's `env`
+    pub env: Option<EnvConfig>,
+    /// Requests and limits, validated like the main container's `resources`
+    pub resources: Option<Resources>,
+    /// `Always` for a native sidecar; init containers run to completion when absent
+    pub restart_policy: Option<String>,
+}
+
+/// A container run next to the main container of a Deployment, listed after it
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+pub struct ContainerConfig {
+    pub name: String,
+    pub image: String,
+    pub command: Vec<String>,
+    pub args: Vec<String>,
+    /// Environment variables, as for the main container's `env`
+    pub env: Option<EnvConfig>,
+    /// Requests and limits, validated like the main container's `resources`
+    pub resources: Option<Resources>,
+    /// Ports, as for the main container's `ports`
+    pub ports: Vec<ContainerPortConfig>,
+}
+
+/// A taint the pods of a Deployment tolerate, e.g. `dedicated=gpu:NoSchedule`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct TolerationConfig {
+    /// Taint key; without a key, `operator: Exists` tolerates every taint
+    pub key: Option<String>,
+    /// `Equal` (default) or `Exists`
+    pub operator: Option<String>,
+    /// Taint value matched by `Equal`
+    pub value: Option<String>,
+    /// `NoSchedule`, `PreferNoSchedule`, or `NoExecute`; all effects when absent
+    pub effect: Option<String>,
+}
+
+/// Configuration for the generated Service
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct ServiceConfig {
+    /// Service type (default: ClusterIP)
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// Port exposed by the Service
+    pub port: Option<i32>,
+    /// Container port targeted by the main Service port (defaults to `port`)
+    pub target_port: Option<i32>,
+    /// Node port of the main Service port (30000-32767); requires `type: NodePort`
+    pub node_port: Option<i32>,
+    /// Name of the main Service port (default: web for Prometheus, http for Deployments)
+    pub port_name: Option<String>,
+    /// Additional named ports exposed by the Service
+    #[serde(default)]
+    pub extra_ports: Vec<ServicePortConfig>,
+    /// Annotations added to the Service only (e.g. for a cloud load balancer)
+    pub annotations: Option<HashMap<String, String>>,
+}
+
+/// A named Service port
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ServicePortConfig {
+    pub name: String,
+    pub port: i32,
+    /// Container port targeted by the Service port (defaults to `port`)
+    pub target_port: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -123,11 +1138,129 @@ pub struct ResourceSpec {
     pub memory: Option<String>,
 }
 
+/// In-place resize policy for a container resource (Kubernetes 1.27+)
 #[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ResizePolicy {
+    /// Resource the policy applies to (`cpu` or `memory`)
+    #[serde(rename = "resourceName")]
+    pub resource_name: String,
+    /// Restart behavior when the resource is resized (`NotRequired` or `RestartContainer`)
+    #[serde(rename = "restartPolicy")]
+    pub restart_policy: String,
+}
+
+/// A port exposed by the workload container
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ContainerPortConfig {
+    pub name: Option<String>,
+    pub container_port: i32,
+    /// Port protocol (default: TCP)
+    pub protocol: Option<String>,
+    /// Port opened on the node's IP and forwarded to the container port
+    pub host_port: Option<i32>,
+}
+
+/// Security settings for the workload container
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct SecurityContextConfig {
+    /// Apply the Pod Security Standards `restricted` baseline: drop ALL capabilities,
+    /// no privilege escalation, runAsNonRoot, and the RuntimeDefault seccomp profile
+    #[serde(default)]
+    pub restricted: bool,
+    pub allow_privilege_escalation: Option<bool>,
+    pub run_as_non_root: Option<bool>,
+    pub run_as_user: Option<i64>,
+    pub run_as_group: Option<i64>,
+    /// Group owning the pod's volumes; set on the pod rather than the container
+    pub fs_group: Option<i64>,
+    pub capabilities: Option<CapabilitiesConfig>,
+    /// `false` emits no securityContext at all, including Prometheus' default one
+    pub enabled: Option<bool>,
+}
+
+/// Linux capabilities added to or dropped from the container
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct CapabilitiesConfig {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub drop: Vec<String>,
+}
+
+/// Health checks for the workload container
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct Probes {
+    pub liveness: Option<ProbeSpec>,
+    pub readiness: Option<ProbeSpec>,
+}
+
+/// An HTTP GET probe
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ProbeSpec {
+    pub path: String,
+    pub port: i32,
+    pub initial_delay_seconds: Option<i32>,
+    pub period_seconds: Option<i32>,
+}
+
+/// Lifecycle hooks of the workload container
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct LifecycleConfig {
+    /// Runs before the container receives SIGTERM, e.g. to drain connections
+    pub pre_stop: Option<LifecycleHandlerConfig>,
+}
+
+/// A lifecycle hook: a command run in the container or an HTTP GET against it; set one
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+pub struct LifecycleHandlerConfig {
+    /// Command run in the container, e.g. `["sleep", "5"]`
+    pub exec: Option<Vec<String>>,
+    /// HTTP GET sent to the container
+    pub http_get: Option<HttpGetConfig>,
+}
+
+/// An HTTP GET against the container
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct HttpGetConfig {
+    pub path: String,
+    pub port: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
 pub struct Storage {
     pub size: String,
+    /// StorageClass of the claim; omitted to use the cluster's default StorageClass
     #[serde(rename = "className")]
-    pub class_name: String,
+    pub class_name: Option<String>,
+    /// Name of the volumeClaimTemplate (used when several storage entries are listed)
+    pub name: Option<String>,
+    /// Path where the volume is mounted in the workload container
+    #[serde(rename = "mountPath")]
+    pub mount_path: Option<String>,
+}
+
+/// Storage configuration, either a single storage spec or a list of named specs
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(untagged)]
+pub enum StorageConfig {
+    Single(Storage),
+    Multiple(Vec<Storage>),
+}
+
+impl StorageConfig {
+    /// Returns all storage entries, regardless of the form used in the config
+    pub fn entries(&self) -> &[Storage] {
+        match self {
+            StorageConfig::Single(storage) => std::slice::from_ref(storage),
+            StorageConfig::Multiple(storages) => storages,
+        }
+    }
+}
+
+impl From<Storage> for StorageConfig {
+    fn from(storage: Storage) -> Self {
+        StorageConfig::Single(storage)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
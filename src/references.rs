@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::collections::BTreeSet;
+
+/// Kinds whose pod template is checked for references
+const WORKLOAD_KINDS: &[&str] = &["Deployment", "StatefulSet", "DaemonSet"];
+
+/// Largest edit distance at which a missing name counts as a typo of a generated one;
+/// names shorter than three characters per edit never do
+const MAX_TYPO_DISTANCE: usize = 2;
+
+/// A ConfigMap or Secret named by a workload's pod template
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ObjectReference {
+    /// `ConfigMap` or `Secret`
+    pub kind: &'static str,
+    /// Name of the referenced object
+    pub name: String,
+    /// Namespace of the referencing workload, where the object must live
+    pub namespace: Option<String>,
+    /// `<kind>/<name>` of the referencing workload
+    pub referrer: String,
+}
+
+/// Collects the ConfigMaps and Secrets a workload manifest references through `envFrom`,
+/// `valueFrom`, and volumes of its containers and init containers
+pub fn find_references(manifest: &str) -> Result<Vec<ObjectReference>> {
+    let value: Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+    let kind = value["kind"].as_str().unwrap_or_default();
+    if !WORKLOAD_KINDS.contains(&kind) {
+        return Ok(Vec::new());
+    }
+
+    let namespace = value["metadata"]["namespace"].as_str().map(str::to_string);
+    let referrer = format!(
+        "{}/{}",
+        kind,
+        value["metadata"]["name"].as_str().unwrap_or_default()
+    );
+    let mut references = Vec::new();
+    let mut add = |kind: &'static str, name: &Value| {
+        if let Some(name) = name.as_str() {
+            references.push(ObjectReference {
+                kind,
+                name: name.to_string(),
+                namespace: namespace.clone(),
+                referrer: referrer.clone(),
+            });
+        }
+    };
+
+    let pod_spec = &value["spec"]["template"]["spec"];
+    let containers = ["containers", "initContainers"]
+        .iter()
+        .filter_map(|field| pod_spec[*field].as_sequence())
+        .flatten();
+    for container in containers {
+        for source in container["envFrom"].as_sequence().into_iter().flatten() {
+            add("ConfigMap", &source["configMapRef"]["name"]);
+            add("Secret", &source["secretRef"]["name"]);
+        }
+        for env in container["env"].as_sequence().into_iter().flatten() {
+            add("ConfigMap", &env["valueFrom"]["configMapKeyRef"]["name"]);
+            add("Secret", &env["valueFrom"]["secretKeyRef"]["name"]);
+        }
+    }
+    for volume in pod_spec["volumes"].as_sequence().into_iter().flatten() {
+        add("ConfigMap", &volume["configMap"]["name"]);
+        add("Secret", &volume["secret"]["secretName"]);
+    }
+
+    Ok(references)
+}
+
+/// Checks the ConfigMap and Secret references of the workloads among `manifests` against
+/// the ConfigMaps and Secrets generated in the same run. A reference whose name is not
+/// generated but is within a typo of a generated object of the same kind and namespace
+/// is reported; other missing names are assumed to be created outside kamut.
+pub fn check_references(manifests: &[String]) -> Result<Vec<String>> {
+    let mut generated = BTreeSet::new();
+    let mut references = BTreeSet::new();
+    for manifest in manifests {
+        let value: Value =
+            serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+        if let (Some(kind @ ("ConfigMap" | "Secret")), Some(name)) =
+            (value["kind"].as_str(), value["metadata"]["name"].as_str())
+        {
+            let namespace = value["metadata"]["namespace"].as_str().map(str::to_string);
+            generated.insert((kind.to_string(), namespace, name.to_string()));
+        }
+        references.extend(find_references(manifest)?);
+    }
+
+    let mut warnings = Vec::new();
+    for reference in references {
+        let key = (
+            reference.kind.to_string(),
+            reference.namespace.clone(),
+            reference.name.clone(),
+        );
+        if generated.contains(&key) {
+            continue;
+        }
+        let closest = generated
+            .iter()
+            .filter(|(kind, namespace, _)| {
+                kind == reference.kind && *namespace == reference.namespace
+            })
+            .map(|(_, _, name)| (edit_distance(name, &reference.name), name))
+            .filter(|(distance, _)| {
+                *distance <= MAX_TYPO_DISTANCE && distance * 3 <= reference.name.chars().count()
+            })
+            .min();
+        if let Some((_, name)) = closest {
+            warnings.push(format!(
+                "{} references {} '{}', which is not generated in this run; did you mean '{}'?",
+                reference.referrer, reference.kind, reference.name, name
+            ));
+        }
+    }
+
+    Ok(warnings)
+}
+
+// Levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::config::{
+    generate_alertmanager_config_manifest, generate_alertmanager_manifests,
+    generate_configmap_manifest, generate_daemonset_manifest, generate_deployment_manifests,
+    generate_endpoints_manifest, generate_ingress_manifest, generate_network_policy_manifest,
+    generate_priority_class_manifest, generate_prometheus_manifests,
+    generate_scrape_config_manifest, generate_secret_manifest, generate_service_monitor_manifest,
+    generate_statefulset_manifest,
+};
+use crate::models::KamutConfig;
+
+/// Generates the manifests of the documents of one kind.
+///
+/// Closures taking a `&KamutConfig` and returning `Result<Vec<String>>` implement it, so
+/// an embedder can add a kind without forking:
+///
+/// ```
+/// use kamut::registry::{is_registered, register_kind};
+///
+/// register_kind("Greeting", |config: &kamut::KamutConfig| {
+///     Ok(vec![format!("apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: {}\n", config.name)])
+/// });
+/// assert!(is_registered("Greeting"));
+/// ```
+pub trait KindGenerator: Send + Sync {
+    /// Generates the YAML manifests of a document, its primary resource first. Built-in
+    /// kinds return no manifests for documents that lack a required field.
+    fn generate(&self, config: &KamutConfig) -> Result<Vec<String>>;
+}
+
+impl<F> KindGenerator for F
+where
+    F: Fn(&KamutConfig) -> Result<Vec<String>> + Send + Sync,
+{
+    fn generate(&self, config: &KamutConfig) -> Result<Vec<String>> {
+        self(config)
+    }
+}
+
+type Registry = RwLock<BTreeMap<String, Arc<dyn KindGenerator>>>;
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| RwLock::new(builtin_kinds()))
+}
+
+// A built-in kind generating a single resource, skipped unless `required` holds
+fn single(
+    required: fn(&KamutConfig) -> bool,
+    generate: fn(&KamutConfig) -> Result<String>,
+) -> Arc<dyn KindGenerator> {
+    Arc::new(move |config: &KamutConfig| -> Result<Vec<String>> {
+        if !required(config) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![generate(config)?])
+    })
+}
+
+fn builtin_kinds() -> BTreeMap<String, Arc<dyn KindGenerator>> {
+    let kinds: [(&str, Arc<dyn KindGenerator>); 14] = [
+        ("Deployment", Arc::new(generate_deployment_manifests)),
+        (
+            "StatefulSet",
+            single(
+                |config| config.image.is_some(),
+                generate_statefulset_manifest,
+            ),
+        ),
+        (
+            "DaemonSet",
+            single(|config| config.image.is_some(), generate_daemonset_manifest),
+        ),
+        ("Prometheus", Arc::new(generate_prometheus_manifests)),
+        ("Alertmanager", Arc::new(generate_alertmanager_manifests)),
+        (
+            "KubeScrapeConfig",
+            single(
+                |config| config.role.is_some(),
+                generate_scrape_config_manifest,
+            ),
+        ),
+        (
+            "ServiceMonitor",
+            single(
+                |config| config.port.is_some(),
+                generate_service_monitor_manifest,
+            ),
+        ),
+        (
+            "PriorityClass",
+            single(
+                |config| config.value.is_some(),
+                generate_priority_class_manifest,
+            ),
+        ),
+        (
+            "ConfigMap",
+            single(|config| config.data.is_some(), generate_configmap_manifest),
+        ),
+        ("Secret", single(|_| true, generate_secret_manifest)),
+        (
+            "Endpoints",
+            single(
+                |config| config.endpoints.is_some(),
+                generate_endpoints_manifest,
+            ),
+        ),
+        (
+            "Ingress",
+            single(|config| config.ingress.is_some(), generate_ingress_manifest),
+        ),
+        (
+            "AlertmanagerConfig",
+            single(|_| true, generate_alertmanager_config_manifest),
+        ),
+        (
+            "NetworkPolicy",
+            single(
+                |config| config.network_policy.is_some(),
+                generate_network_policy_manifest,
+            ),
+        ),
+    ];
+    kinds
+        .into_iter()
+        .map(|(kind, generator)| (kind.to_string(), generator))
+        .collect()
+}
+
+/// Registers the generator of a kind, replacing the previous one (including a built-in)
+pub fn register_kind(name: impl Into<String>, generator: impl KindGenerator + 'static) {
+    registry()
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(name.into(), Arc::new(generator));
+}
+
+/// Returns the generator registered for a kind
+pub fn kind_generator(name: &str) -> Option<Arc<dyn KindGenerator>> {
+    registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(name)
+        .cloned()
+}
+
+/// Whether a generator is registered for a kind
+pub fn is_registered(name: &str) -> bool {
+    kind_generator(name).is_some()
+}
+
+/// Names of the registered kinds, sorted
+pub fn registered_kinds() -> Vec<String> {
+    registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .keys()
+        .cloned()
+        .collect()
+}
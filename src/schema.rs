@@ -0,0 +1,74 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::cluster::ResourceRef;
+
+/// Whether a resource comes from a CustomResourceDefinition rather than the Kubernetes API
+/// itself; built-in groups are the core group, single-word groups (`apps`), and `*.k8s.io`
+pub fn is_custom_resource(resource: &ResourceRef) -> bool {
+    resource.group.contains('.') && !resource.group.ends_with(".k8s.io")
+}
+
+/// Path of a resource's schema inside a schema directory, following the layout used by
+/// kubeconform: `deployment-apps-v1.json` for built-in kinds (`service-v1.json` for the
+/// core group) and `monitoring.coreos.com/prometheus_v1.json` for custom resources
+pub fn schema_path(schema_dir: &Path, resource: &ResourceRef) -> PathBuf {
+    let kind = resource.kind.to_lowercase();
+    if is_custom_resource(resource) {
+        schema_dir
+            .join(&resource.group)
+            .join(format!("{}_{}.json", kind, resource.version))
+    } else if resource.group.is_empty() {
+        schema_dir.join(format!("{}-{}.json", kind, resource.version))
+    } else {
+        // networking.k8s.io -> networking
+        let group = resource.group.split('.').next().unwrap_or_default();
+        schema_dir.join(format!("{}-{}-{}.json", kind, group, resource.version))
+    }
+}
+
+/// Validates a generated manifest against its JSON schema from `schema_dir` and returns
+/// one message per violation. Custom resources without a schema are skipped; a missing
+/// schema for a built-in kind is reported as a violation.
+#[cfg(feature = "schema")]
+pub fn validate_manifest(manifest: &str, schema_dir: &Path) -> Result<Vec<String>> {
+    use anyhow::Context;
+
+    let resource = crate::cluster::resource_ref(manifest)?;
+    let header = format!("{} {}", resource.kind, resource.name);
+    let path = schema_path(schema_dir, &resource);
+
+    if !path.exists() {
+        if is_custom_resource(&resource) {
+            return Ok(Vec::new());
+        }
+        return Ok(vec![format!(
+            "{}: no schema found at {}",
+            header,
+            path.display()
+        )]);
+    }
+
+    let schema: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read schema: {}", path.display()))?,
+    )
+    .with_context(|| format!("Failed to parse schema: {}", path.display()))?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|err| anyhow::anyhow!("Invalid schema {}: {}", path.display(), err))?;
+
+    let instance: serde_json::Value =
+        serde_yaml::from_str(manifest).context("Failed to parse generated manifest")?;
+    Ok(validator
+        .iter_errors(&instance)
+        .map(|err| format!("{}: {}: {}", header, err.instance_path, err))
+        .collect())
+}
+
+/// Without the `schema` feature there is no JSON schema validator
+#[cfg(not(feature = "schema"))]
+pub fn validate_manifest(_manifest: &str, _schema_dir: &Path) -> Result<Vec<String>> {
+    Err(anyhow::anyhow!(
+        "--validate-schema requires kamut to be built with the `schema` feature"
+    ))
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::config::find_config_files;
+
+/// Quiet period after the last change before files are regenerated, so an editor's
+/// burst of writes (temp file, rename, chmod) triggers a single run
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Placeholder of `--exec` replaced by the path of the written manifest file
+pub const EXEC_OUTPUT_PLACEHOLDER: &str = "{output}";
+
+/// What the watch loop reacts to
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// Paths that were created, modified, or removed
+    Changed(Vec<PathBuf>),
+    /// Stop watching (Ctrl-C)
+    Stop,
+}
+
+/// The directory to watch for a glob pattern: the components before the first one with a
+/// glob character, watched recursively when the pattern descends further than one level
+/// (`deploy/**/*.kamut.yaml` watches `deploy` recursively, `*.kamut.yaml` only `.`).
+pub fn watch_root(pattern: &str) -> (PathBuf, RecursiveMode) {
+    let is_glob = |component: &Component| {
+        component
+            .as_os_str()
+            .to_string_lossy()
+            .contains(['*', '?', '['])
+    };
+
+    let components: Vec<Component> = Path::new(pattern).components().collect();
+    let literal = components
+        .iter()
+        .position(is_glob)
+        .unwrap_or(components.len().saturating_sub(1));
+
+    let root: PathBuf = components[..literal].iter().collect();
+    let root = if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root
+    };
+    let mode = if components.len() - literal > 1 {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    (root, mode)
+}
+
+/// The files matching `pattern` among the changed paths, sorted and deduplicated.
+/// Re-running the glob keeps its exact semantics and ignores the manifests kamut
+/// writes next to the kamut files, which would otherwise retrigger the watch.
+pub fn changed_config_files(pattern: &str, changed: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let changed: BTreeSet<PathBuf> = changed
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = find_config_files(pattern)?
+        .into_iter()
+        .filter(|file| {
+            file.canonicalize()
+                .map(|file| changed.contains(&file))
+                .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Calls `on_change` for every changed file matching `pattern` until `WatchEvent::Stop`
+/// arrives or the channel closes. Changes are collected until no event arrives for
+/// `debounce`, so each file is regenerated once per burst of edits. Errors are reported
+/// on stderr and the loop keeps watching, so a typo in one file doesn't end the session.
+pub fn run_watch_loop<F>(
+    pattern: &str,
+    events: Receiver<WatchEvent>,
+    debounce: Duration,
+    mut on_change: F,
+) -> Result<()>
+where
+    F: FnMut(&Path) -> Result<()>,
+{
+    while let Ok(event) = events.recv() {
+        let mut changed = match event {
+            WatchEvent::Changed(changed) => changed,
+            WatchEvent::Stop => break,
+        };
+        let mut stopped = false;
+        loop {
+            match events.recv_timeout(debounce) {
+                Ok(WatchEvent::Changed(more)) => changed.extend(more),
+                Ok(WatchEvent::Stop) | Err(RecvTimeoutError::Disconnected) => {
+                    stopped = true;
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+        if stopped {
+            break;
+        }
+
+        let files = match changed_config_files(pattern, &changed) {
+            Ok(files) => files,
+            Err(err) => {
+                eprintln!("Error: {:?}", err);
+                continue;
+            }
+        };
+        for file_path in &files {
+            if let Err(err) = on_change(file_path) {
+                eprintln!("Error: {:?}", err);
+                eprintln!("Still watching {}", pattern);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Watches the directories implied by `pattern` and calls `on_change` for each matching
+/// file after every burst of changes, until Ctrl-C.
+pub fn watch<F>(pattern: &str, on_change: F) -> Result<()>
+where
+    F: FnMut(&Path) -> Result<()>,
+{
+    let (sender, events) = channel();
+
+    let stop = sender.clone();
+    ctrlc::set_handler(move || {
+        let _ = stop.send(WatchEvent::Stop);
+    })
+    .context("Failed to install the Ctrl-C handler")?;
+
+    // Reading a kamut file to regenerate it raises access events, which must not
+    // trigger another run
+    let forward = move |result: notify::Result<Event>| match result {
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => {
+            let _ = sender.send(WatchEvent::Changed(event.paths));
+        }
+        Err(err) => eprintln!("Warning: watch error: {}", err),
+    };
+    let mut watcher =
+        notify::recommended_watcher(forward).context("Failed to start the file watcher")?;
+
+    let (root, mode) = watch_root(pattern);
+    watcher
+        .watch(&root, mode)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+
+    run_watch_loop(pattern, events, WATCH_DEBOUNCE, on_change)
+}
+
+/// The shell command line of an `--exec` template for one output file, with every
+/// `{output}` replaced by the quoted path
+pub fn exec_command_line(template: &str, output: &Path) -> String {
+    let path = output.display().to_string();
+    let quoted = if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    };
+    template.replace(EXEC_OUTPUT_PLACEHOLDER, &quoted)
+}
+
+/// Runs an `--exec` template for one output file through the shell and captures its
+/// output. A command that runs but exits non-zero is not an error here; callers inspect
+/// the returned status.
+pub fn run_exec(template: &str, output: &Path) -> Result<Output> {
+    let command_line = exec_command_line(template, output);
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command
+        .arg(&command_line)
+        .output()
+        .with_context(|| format!("Failed to run {}", command_line))
+}
@@ -0,0 +1,83 @@
+use kamut::config::{generate_alertmanager_config_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_alertmanager_config_webhook_receiver() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("alerts.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: team-alerts
+kind: AlertmanagerConfig
+namespace: payments
+route:
+  receiver: oncall
+  group_by: [alertname, service]
+  repeat_interval: 4h
+  matchers:
+    - name: severity
+      value: critical|page
+      match_type: "=~"
+receivers:
+  - name: oncall
+    webhook_configs:
+      - url_secret:
+          name: oncall-webhook
+          key: url
+        send_resolved: true
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let value: serde_yaml::Value = serde_yaml::from_str(&rendered.manifests[0]).unwrap();
+    assert_eq!(value["apiVersion"], "monitoring.coreos.com/v1alpha1");
+    assert_eq!(value["kind"], "AlertmanagerConfig");
+    assert_eq!(value["metadata"]["namespace"], "payments");
+
+    let route = &value["spec"]["route"];
+    assert_eq!(route["receiver"], "oncall");
+    assert_eq!(route["groupBy"][1], "service");
+    assert_eq!(route["repeatInterval"], "4h");
+    assert_eq!(route["matchers"][0]["matchType"], "=~");
+    assert_eq!(route["matchers"][0]["value"], "critical|page");
+
+    let receiver = &value["spec"]["receivers"][0];
+    assert_eq!(receiver["name"], "oncall");
+    let webhook = &receiver["webhookConfigs"][0];
+    assert_eq!(webhook["urlSecret"]["name"], "oncall-webhook");
+    assert_eq!(webhook["urlSecret"]["key"], "url");
+    assert_eq!(webhook["sendResolved"], true);
+}
+
+#[test]
+fn test_alertmanager_config_requires_route_and_receivers() {
+    let config: KamutConfig = serde_yaml::from_str(
+        "name: team-alerts\nkind: AlertmanagerConfig\nroute:\n  receiver: oncall\n",
+    )
+    .unwrap();
+    assert!(config.validate_kind().is_err());
+    let result = generate_alertmanager_config_manifest(&config);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("receivers must list at least one receiver"));
+
+    let config: KamutConfig = serde_yaml::from_str(
+        "name: team-alerts\nkind: AlertmanagerConfig\nroute:\n  receiver: oncall\nreceivers:\n  - name: slack\n",
+    )
+    .unwrap();
+    let result = generate_alertmanager_config_manifest(&config);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("route.receiver 'oncall' is not listed in receivers"));
+}
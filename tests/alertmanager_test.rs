@@ -0,0 +1,118 @@
+use kamut::config::{generate_alertmanager_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use kamut::KamutError;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_alertmanager_with_service() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("main.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: main
+kind: Alertmanager
+namespace: monitoring
+image: quay.io/prometheus/alertmanager:v0.27.0
+replicas: 3
+retention: 240h
+resources:
+  requests:
+    cpu: 100m
+    memory: 128Mi
+  limits:
+    memory: 256Mi
+storage:
+  size: 5Gi
+  className: fast
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 2);
+
+    let alertmanager: serde_yaml::Value = serde_yaml::from_str(&rendered.manifests[0]).unwrap();
+    assert_eq!(alertmanager["apiVersion"], "monitoring.coreos.com/v1");
+    assert_eq!(alertmanager["kind"], "Alertmanager");
+    assert_eq!(alertmanager["metadata"]["name"], "main");
+    assert_eq!(alertmanager["metadata"]["namespace"], "monitoring");
+
+    let spec = &alertmanager["spec"];
+    assert_eq!(spec["image"], "quay.io/prometheus/alertmanager:v0.27.0");
+    assert_eq!(spec["replicas"], 3);
+    assert_eq!(spec["retention"], "240h");
+    assert_eq!(spec["resources"]["requests"]["cpu"], "100m");
+    assert_eq!(spec["resources"]["limits"]["memory"], "256Mi");
+    let claim = &spec["storage"]["volumeClaimTemplate"]["spec"];
+    assert_eq!(claim["storageClassName"], "fast");
+    assert_eq!(claim["resources"]["requests"]["storage"], "5Gi");
+
+    let service: serde_yaml::Value = serde_yaml::from_str(&rendered.manifests[1]).unwrap();
+    assert_eq!(service["kind"], "Service");
+    assert_eq!(service["metadata"]["name"], "alertmanager-main");
+    assert_eq!(service["metadata"]["namespace"], "monitoring");
+    assert_eq!(service["spec"]["selector"]["alertmanager"], "main");
+    assert_eq!(service["spec"]["ports"][0]["name"], "web");
+    assert_eq!(service["spec"]["ports"][0]["port"], 9093);
+    assert_eq!(service["spec"]["ports"][0]["targetPort"], 9093);
+}
+
+#[test]
+fn test_alertmanager_defaults() {
+    let yaml = r#"
+    name: main
+    kind: Alertmanager
+    image: quay.io/prometheus/alertmanager:v0.27.0
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_alertmanager_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let spec = &value["spec"];
+    // Replicas and retention are left to the operator's defaults
+    assert!(spec["replicas"].is_null());
+    assert!(spec["retention"].is_null());
+    assert!(spec["storage"].is_null());
+}
+
+#[test]
+fn test_alertmanager_invalid() {
+    let config: KamutConfig = serde_yaml::from_str("name: main\nkind: Alertmanager").unwrap();
+    let err = config.validate_kind().unwrap_err();
+    assert!(matches!(err, KamutError::MissingField("image")));
+
+    for (field, expected) in [
+        ("retention: 15d", "Invalid Alertmanager retention '15d'"),
+        ("retention: 30m1h", "Invalid Alertmanager retention '30m1h'"),
+        (
+            "storage:\n  - size: 1Gi\n  - size: 2Gi",
+            "Alertmanager supports exactly one storage entry",
+        ),
+    ] {
+        let yaml = format!(
+            "name: main\nkind: Alertmanager\nimage: alertmanager:v0.27.0\n{}",
+            field
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_alertmanager_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+
+    for retention in ["0", "120h", "1h30m", "90s", "500ms", "1h0m0s500ms"] {
+        let yaml = format!(
+            "name: main\nkind: Alertmanager\nimage: alertmanager:v0.27.0\nretention: {}",
+            retention
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert!(
+            generate_alertmanager_manifest(&config).is_ok(),
+            "{}",
+            retention
+        );
+    }
+}
@@ -22,7 +22,7 @@ fn test_cli_generate_command_default_pattern() {
     // Test generate command with default pattern
     let cli = Cli::parse_from(["kamut", "generate"]);
     match cli.command {
-        Some(Commands::Generate { pattern }) => {
+        Some(Commands::Generate { pattern, .. }) => {
             assert_eq!(pattern, "*.kamut.yaml");
         }
         _ => panic!("Expected Generate command"),
@@ -34,13 +34,25 @@ fn test_cli_generate_command_custom_pattern() {
     // Test generate command with custom pattern
     let cli = Cli::parse_from(["kamut", "generate", "custom*.kamut.yaml"]);
     match cli.command {
-        Some(Commands::Generate { pattern }) => {
+        Some(Commands::Generate { pattern, .. }) => {
             assert_eq!(pattern, "custom*.kamut.yaml");
         }
         _ => panic!("Expected Generate command"),
     }
 }
 
+#[test]
+fn test_cli_list_command() {
+    // Test list command with custom pattern
+    let cli = Cli::parse_from(["kamut", "list", "custom*.kamut.yaml"]);
+    match cli.command {
+        Some(Commands::List { pattern }) => {
+            assert_eq!(pattern, "custom*.kamut.yaml");
+        }
+        _ => panic!("Expected List command"),
+    }
+}
+
 #[test]
 fn test_cli_version_command() {
     // Test version command
@@ -73,3 +85,123 @@ fn test_args_without_name() {
     let args = Args::parse_from(["kamut"]);
     assert_eq!(args.name, None);
 }
+
+#[test]
+fn test_cli_generate_command_render_only() {
+    // Test generate command with --render-only filter
+    let cli = Cli::parse_from(["kamut", "generate", "--render-only", "api"]);
+    match cli.command {
+        Some(Commands::Generate { generate_args, .. }) => {
+            assert_eq!(generate_args.render_only, Some("api".to_string()));
+        }
+        _ => panic!("Expected Generate command"),
+    }
+}
+
+#[test]
+fn test_cli_default_render_only() {
+    // Test --render-only without a subcommand
+    let cli = Cli::parse_from(["kamut", "--render-only", "api"]);
+    assert_eq!(cli.generate_args.render_only, Some("api".to_string()));
+    assert!(cli.command.is_none());
+}
+
+#[test]
+fn test_cli_validate_command() {
+    // Test validate command with default pattern
+    let cli = Cli::parse_from(["kamut", "validate"]);
+    match cli.command {
+        Some(Commands::Validate {
+            pattern,
+            max_errors,
+        }) => {
+            assert_eq!(pattern, "*.kamut.yaml");
+            assert_eq!(max_errors, None);
+        }
+        _ => panic!("Expected Validate command"),
+    }
+
+    let cli = Cli::parse_from(["kamut", "validate", "--max-errors", "5"]);
+    match cli.command {
+        Some(Commands::Validate { max_errors, .. }) => assert_eq!(max_errors, Some(5)),
+        _ => panic!("Expected Validate command"),
+    }
+    assert!(Cli::try_parse_from(["kamut", "validate", "--max-errors", "0"]).is_err());
+}
+
+#[test]
+fn test_cli_export_helm_command() {
+    // Test export-helm command with chart options
+    let cli = Cli::parse_from(["kamut", "export-helm", "charts/api", "--chart-name", "api"]);
+    match cli.command {
+        Some(Commands::ExportHelm {
+            dir,
+            pattern,
+            chart_name,
+            chart_version,
+        }) => {
+            assert_eq!(dir, std::path::PathBuf::from("charts/api"));
+            assert_eq!(pattern, "*.kamut.yaml");
+            assert_eq!(chart_name.as_deref(), Some("api"));
+            assert_eq!(chart_version, "0.1.0");
+        }
+        _ => panic!("Expected ExportHelm command"),
+    }
+}
+
+#[test]
+fn test_cli_name_template_requires_split_documents() {
+    // --name-template only applies when splitting documents
+    let result = Cli::try_parse_from(["kamut", "--name-template", "{kind}-{name}.yaml"]);
+    assert!(result.is_err());
+
+    let cli = Cli::parse_from([
+        "kamut",
+        "--split-documents",
+        "--name-template",
+        "{kind}-{name}.yaml",
+    ]);
+    let options = cli.generate_args.process_options();
+    assert!(options.split_documents);
+    assert_eq!(options.name_template.as_deref(), Some("{kind}-{name}.yaml"));
+}
+
+#[test]
+fn test_cli_generate_command_watch() {
+    let cli = Cli::parse_from(["kamut", "generate", "--watch", "deploy/*.kamut.yaml"]);
+    match cli.command {
+        Some(Commands::Generate { pattern, watch, .. }) => {
+            assert_eq!(pattern, "deploy/*.kamut.yaml");
+            assert!(watch);
+        }
+        _ => panic!("Expected Generate command"),
+    }
+
+    // Watching only regenerates files, so it can't be combined with a cluster diff
+    let result = Cli::try_parse_from(["kamut", "generate", "--watch", "--diff-against-cluster"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cli_generate_command_exec_requires_watch() {
+    let cli = Cli::parse_from([
+        "kamut",
+        "generate",
+        "--watch",
+        "--watch-exec",
+        "kubectl apply -f {output}",
+    ]);
+    match cli.command {
+        Some(Commands::Generate { exec, .. }) => {
+            assert_eq!(exec.as_deref(), Some("kubectl apply -f {output}"));
+        }
+        _ => panic!("Expected Generate command"),
+    }
+
+    let result = Cli::try_parse_from(["kamut", "generate", "--exec", "true"]);
+    assert!(result.is_err());
+    // The command needs the written files
+    let result =
+        Cli::try_parse_from(["kamut", "generate", "--watch", "--exec", "true", "--stdout"]);
+    assert!(result.is_err());
+}
@@ -0,0 +1,79 @@
+use kamut::cluster::{diff_lines, project_live_object, resource_ref, ResourceRef};
+use kamut::config::generate_deployment_manifest;
+use kamut::models::KamutConfig;
+
+#[test]
+fn test_resource_ref_for_deployment() {
+    let config = KamutConfig {
+        name: "api".to_string(),
+        kind: Some("Deployment".to_string()),
+        namespace: Some("default".to_string()),
+        image: Some("api:v1.0.0".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+
+    let resource = resource_ref(&manifest).unwrap();
+    assert_eq!(
+        resource,
+        ResourceRef {
+            group: "apps".to_string(),
+            version: "v1".to_string(),
+            kind: "Deployment".to_string(),
+            plural: "deployments".to_string(),
+            namespace: Some("default".to_string()),
+            name: "api".to_string(),
+        }
+    );
+    assert_eq!(resource.api_version(), "apps/v1");
+}
+
+#[test]
+fn test_resource_ref_plurals_and_core_group() {
+    let resource = resource_ref("apiVersion: v1\nkind: Service\nmetadata:\n  name: api\n").unwrap();
+    assert_eq!(resource.group, "");
+    assert_eq!(resource.api_version(), "v1");
+    assert_eq!(resource.plural, "services");
+
+    let plural = |kind: &str| {
+        resource_ref(&format!(
+            "apiVersion: v1\nkind: {}\nmetadata:\n  name: x\n",
+            kind
+        ))
+            .unwrap()
+            .plural
+    };
+    assert_eq!(plural("Prometheus"), "prometheuses");
+    assert_eq!(plural("Ingress"), "ingresses");
+    assert_eq!(plural("NetworkPolicy"), "networkpolicies");
+    assert_eq!(plural("Endpoints"), "endpoints");
+}
+
+#[test]
+fn test_project_live_object_and_diff() {
+    let live: serde_yaml::Value = serde_yaml::from_str(
+        r#"
+metadata:
+  name: api
+  uid: 1234
+spec:
+  replicas: 3
+  revisionHistoryLimit: 10
+status:
+  readyReplicas: 3
+"#,
+    )
+    .unwrap();
+    let generated: serde_yaml::Value =
+        serde_yaml::from_str("metadata:\n  name: api\nspec:\n  replicas: 2\n").unwrap();
+
+    // Server-populated fields are dropped
+    let projected = serde_yaml::to_string(&project_live_object(&live, &generated)).unwrap();
+    assert_eq!(projected, "metadata:\n  name: api\nspec:\n  replicas: 3\n");
+
+    let diff = diff_lines(&projected, &serde_yaml::to_string(&generated).unwrap());
+    assert_eq!(
+        diff,
+        " metadata:\n   name: api\n spec:\n-  replicas: 3\n+  replicas: 2\n"
+    );
+}
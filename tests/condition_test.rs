@@ -0,0 +1,42 @@
+use kamut::condition::evaluate;
+use std::collections::BTreeMap;
+
+fn vars(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_evaluate_comparisons() {
+    let vars = vars(&[("env", "prod"), ("region", "eu")]);
+
+    assert!(evaluate("env == prod", &vars).unwrap());
+    assert!(evaluate("env == \"prod\"", &vars).unwrap());
+    assert!(!evaluate("env != prod", &vars).unwrap());
+    assert!(evaluate("env == prod && region == eu", &vars).unwrap());
+    assert!(!evaluate("env == prod && region == us", &vars).unwrap());
+    assert!(evaluate("env == dev || region == eu", &vars).unwrap());
+}
+
+#[test]
+fn test_evaluate_booleans() {
+    let vars = vars(&[("canary", "false")]);
+
+    assert!(!evaluate("canary", &vars).unwrap());
+    assert!(evaluate("!canary", &vars).unwrap());
+    assert!(evaluate("true", &vars).unwrap());
+}
+
+#[test]
+fn test_evaluate_errors() {
+    let vars = vars(&[("env", "prod")]);
+
+    let err = evaluate("kamut_unset_variable == prod", &vars).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unknown variable 'kamut_unset_variable'"));
+    assert!(evaluate("env", &vars).is_err());
+    assert!(evaluate("", &vars).is_err());
+}
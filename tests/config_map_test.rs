@@ -0,0 +1,55 @@
+use kamut::config::{generate_configmap_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use std::collections::HashMap;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_configmap_from_inline_data() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("app-config.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: app-config
+kind: ConfigMap
+namespace: apps
+data:
+  LOG_LEVEL: info
+  app.properties: |
+    feature.enabled=true
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.contains("apiVersion: v1"));
+    assert!(manifest.contains("kind: ConfigMap"));
+    assert!(manifest.contains("namespace: apps"));
+    assert!(manifest.contains("app: app-config"));
+    assert!(manifest.contains("LOG_LEVEL: info"));
+    assert!(manifest.contains("feature.enabled=true"));
+}
+
+#[test]
+fn test_configmap_requires_data() {
+    let config = KamutConfig {
+        name: "app-config".to_string(),
+        kind: Some("ConfigMap".to_string()),
+        data: Some(HashMap::new()),
+        ..Default::default()
+    };
+
+    let result = generate_configmap_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Data is required for ConfigMap"));
+}
@@ -1,8 +1,14 @@
 use kamut::config::{
-    find_config_files, generate_deployment_manifest, generate_prometheus_ingress,
-    generate_prometheus_manifest, process_file,
+    apply_image_registry, find_config_files, format_manifests, generate_deployment_manifest,
+    generate_from_str, generate_prometheus_ingress, generate_prometheus_manifest,
+    generate_prometheus_manifests, generate_prometheus_service,
+    generate_prometheus_service_monitor, is_valid_byte_size, is_valid_quantity,
+    postprocess_manifest, process_file, process_file_with_options, render_file, serialize_manifest,
+    OutputFormat, ProcessOptions, CONTENT_HASH_LABEL, SYNC_WAVE_ANNOTATION,
+};
+use kamut::models::{
+    Ingress, KamutConfig, MonitorNamespace, ResourceSpec, Resources, Storage, StorageConfig,
 };
-use kamut::models::{Ingress, KamutConfig, Resources, ResourceSpec, Storage};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
@@ -63,7 +69,7 @@ fn test_generate_deployment_manifest() {
         kind: Some("Deployment".to_string()),
         namespace: Some("default".to_string()),
         image: Some("test-image:v1.0.0".to_string()),
-        env: Some(env),
+        env: Some(env.into()),
         resources: Some(resources),
         replicas: Some(3),
         retention: None,
@@ -112,7 +118,8 @@ fn test_generate_prometheus_manifest() {
 
     let storage = Storage {
         size: "100Gi".to_string(),
-        class_name: "standard".to_string(),
+        class_name: Some("standard".to_string()),
+        ..Default::default()
     };
 
     let mut node_selector = HashMap::new();
@@ -128,7 +135,7 @@ fn test_generate_prometheus_manifest() {
         replicas: Some(1),
         retention: Some("30d".to_string()),
         ingress: None,
-        storage: Some(storage),
+        storage: Some(storage.into()),
         node_selector: Some(node_selector),
         service_account: None,
         ..Default::default()
@@ -151,11 +158,551 @@ fn test_generate_prometheus_manifest() {
     assert!(manifest.contains("group: monitoring"));
 }
 
+#[test]
+fn test_generate_prometheus_manifest_rejects_multiple_storage() {
+    let storage = vec![
+        Storage {
+            size: "100Gi".to_string(),
+            class_name: Some("standard".to_string()),
+            name: Some("data".to_string()),
+            ..Default::default()
+        },
+        Storage {
+            size: "10Gi".to_string(),
+            class_name: Some("standard".to_string()),
+            name: Some("wal".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        storage: Some(StorageConfig::Multiple(storage)),
+        ..Default::default()
+    };
+
+    let result = generate_prometheus_manifest(&config);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_generate_prometheus_manifest_default_storage_class() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        storage: Some(StorageConfig::Single(Storage {
+            size: "100Gi".to_string(),
+            class_name: None,
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest.contains("storage: 100Gi"));
+    assert!(!manifest.contains("storageClassName"));
+}
+
+#[test]
+fn test_prometheus_monitor_namespace_own() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        namespace: Some("monitoring".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        monitor_namespace: Some(MonitorNamespace::Mode("own".to_string())),
+        ..Default::default()
+    };
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+
+    // A null selector restricts discovery to the Prometheus namespace
+    assert!(!manifest.contains("serviceMonitorNamespaceSelector"));
+    assert!(!manifest.contains("podMonitorNamespaceSelector"));
+
+    // Omitting the field behaves like `own`
+    let default_config = KamutConfig {
+        monitor_namespace: None,
+        ..config
+    };
+    assert_eq!(
+        generate_prometheus_manifest(&default_config).unwrap(),
+        manifest
+    );
+}
+
+#[test]
+fn test_prometheus_monitor_namespace_all() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    monitor_namespace: all
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+
+    assert!(manifest.contains("serviceMonitorNamespaceSelector:\n    matchLabels: {}"));
+    assert!(manifest.contains("podMonitorNamespaceSelector:\n    matchLabels: {}"));
+}
+
+#[test]
+fn test_prometheus_monitor_namespace_list() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    monitor_namespace:
+      - team-a
+      - team-b
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+
+    assert!(manifest.contains("serviceMonitorNamespaceSelector:"));
+    assert!(manifest.contains("podMonitorNamespaceSelector:"));
+    assert!(manifest.contains("key: kubernetes.io/metadata.name"));
+    assert!(manifest.contains("operator: In"));
+    assert!(manifest.contains("- team-a"));
+    assert!(manifest.contains("- team-b"));
+}
+
+#[test]
+fn test_prometheus_monitor_namespace_invalid_mode() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        monitor_namespace: Some(MonitorNamespace::Mode("everywhere".to_string())),
+        ..Default::default()
+    };
+
+    assert!(generate_prometheus_manifest(&config).is_err());
+}
+
+#[test]
+fn test_prometheus_scrape_classes() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    scrape_classes:
+      - name: internal-tls
+        default: true
+        tls_config:
+          ca_file: /etc/prometheus/certs/ca.crt
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+
+    assert!(manifest.contains("scrapeClasses:"));
+    assert!(manifest.contains("- default: true\n    name: internal-tls"));
+    assert!(manifest.contains("caFile: /etc/prometheus/certs/ca.crt"));
+}
+
+#[test]
+fn test_prometheus_scrape_class_requires_name() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    scrape_classes:
+      - default: true
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let result = generate_prometheus_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("scrape_classes entry 1 requires a name"));
+}
+
+#[test]
+fn test_generate_prometheus_service() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        namespace: Some("monitoring".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        ..Default::default()
+    };
+
+    let manifest = generate_prometheus_service(&config).unwrap();
+
+    assert!(manifest.contains("name: prometheus-test-prometheus"));
+    assert!(manifest.contains("- name: web\n    port: 9090"));
+    assert!(manifest.contains("prometheus: test-prometheus"));
+    assert!(manifest.contains("type: ClusterIP"));
+}
+
+#[test]
+fn test_generate_prometheus_service_extra_port() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    service:
+      extra_ports:
+        - name: reloader-web
+          port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_service(&config).unwrap();
+
+    assert!(manifest.contains("- name: web\n    port: 9090"));
+    assert!(manifest
+        .contains("- name: reloader-web\n    port: 8080\n    protocol: TCP\n    targetPort: 8080"));
+}
+
+#[test]
+fn test_generate_prometheus_service_node_port() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    ingress:
+      host: prometheus.example.com
+    service:
+      type: NodePort
+      port: 80
+      node_port: 30090
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_service(&config).unwrap();
+    assert!(manifest.contains(
+        "- name: web\n    nodePort: 30090\n    port: 80\n    protocol: TCP\n    targetPort: 9090"
+    ));
+    assert!(manifest.contains("type: NodePort"));
+
+    // The Ingress targets the configured Service port
+    let ingress = generate_prometheus_ingress(&config, config.ingress.as_ref().unwrap()).unwrap();
+    assert!(ingress.contains("number: 80"));
+
+    // Node ports must be in range and need a NodePort Service
+    let yaml = r#"
+    name: test-prometheus
+    image: prom/prometheus:v2.7.1
+    service:
+      type: NodePort
+      node_port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let err = generate_prometheus_service(&config).unwrap_err();
+    assert!(err.to_string().contains("outside the NodePort range"));
+
+    let yaml = r#"
+    name: test-prometheus
+    image: prom/prometheus:v2.7.1
+    service:
+      node_port: 30090
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let err = generate_prometheus_service(&config).unwrap_err();
+    assert!(err.to_string().contains("requires service type NodePort"));
+}
+
+#[test]
+fn test_generate_prometheus_service_port_name_override() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    service:
+      port_name: http-web
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_service(&config).unwrap();
+
+    assert!(manifest.contains("- name: http-web\n    port: 9090"));
+    assert!(!manifest.contains("name: web"));
+}
+
+#[test]
+fn test_prometheus_self_monitor() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    self_monitor: true
+    service:
+      port_name: http-web
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    // The ServiceMonitor selects the Prometheus Service and scrapes its main port
+    let service = generate_prometheus_service(&config).unwrap();
+    assert!(service.contains("labels:\n    app: test-prometheus\n    prometheus: test-prometheus"));
+    assert!(service.contains("- name: http-web\n    port: 9090"));
+
+    let service_monitor = generate_prometheus_service_monitor(&config).unwrap();
+    assert!(service_monitor.contains("kind: ServiceMonitor"));
+    assert!(service_monitor.contains("name: prometheus-test-prometheus\n  namespace: monitoring"));
+    assert!(service_monitor.contains("endpoints:\n  - port: http-web"));
+    assert!(
+        service_monitor.contains("selector:\n    matchLabels:\n      prometheus: test-prometheus")
+    );
+    assert!(service_monitor.contains("prometheus: test-prometheus\n  name:"));
+
+    // Prometheus selects the ServiceMonitor by its labels
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest
+        .contains("serviceMonitorSelector:\n    matchLabels:\n      prometheus: test-prometheus"));
+
+    let config = KamutConfig {
+        self_monitor: None,
+        ..config
+    };
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(!manifest.contains("serviceMonitorSelector"));
+}
+
+#[test]
+fn test_prometheus_self_monitor_without_app_label() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("prometheus.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: main
+kind: Prometheus
+namespace: monitoring
+image: prom/prometheus:v2.7.1
+self_monitor: true
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        no_app_label: true,
+        no_managed_labels: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    let manifests: Vec<serde_yaml::Value> = rendered
+        .manifests
+        .iter()
+        .map(|manifest| serde_yaml::from_str(manifest).unwrap())
+        .collect();
+    let find = |kind: &str| {
+        manifests
+            .iter()
+            .find(|manifest| manifest["kind"] == kind)
+            .unwrap()
+    };
+
+    // The selectors still match with the app label stripped
+    let prometheus = find("Prometheus");
+    let service = find("Service");
+    let service_monitor = find("ServiceMonitor");
+    assert!(service["metadata"]["labels"]["app"].is_null());
+    assert_eq!(service["metadata"]["labels"]["prometheus"], "main");
+    assert_eq!(
+        service_monitor["spec"]["selector"]["matchLabels"]["prometheus"],
+        "main"
+    );
+    assert_eq!(service_monitor["metadata"]["labels"]["prometheus"], "main");
+    assert_eq!(
+        prometheus["spec"]["serviceMonitorSelector"]["matchLabels"]["prometheus"],
+        "main"
+    );
+}
+
+#[test]
+fn test_prometheus_retention_size() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    retention: 30d
+    retention_size: 50GB
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    // Both limits are emitted, in operator and raw mode
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest.contains("retention: 30d"));
+    assert!(manifest.contains("retentionSize: 50GB"));
+
+    let raw_config = KamutConfig {
+        mode: Some("raw".to_string()),
+        ..config.clone()
+    };
+    let manifests = generate_prometheus_manifests(&raw_config).unwrap();
+    let deployment = manifests
+        .iter()
+        .find(|manifest| manifest.contains("kind: Deployment"))
+        .unwrap();
+    assert!(deployment.contains("--storage.tsdb.retention.time=30d"));
+    assert!(deployment.contains("--storage.tsdb.retention.size=50GB"));
+
+    // A size alone replaces the 15d time default
+    let config = KamutConfig {
+        retention: None,
+        ..config
+    };
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest.contains("retentionSize: 50GB"));
+    assert!(!manifest.contains("retention: "));
+
+    let config = KamutConfig {
+        retention_size: None,
+        ..config
+    };
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest.contains("retention: 15d"));
+    assert!(!manifest.contains("retentionSize"));
+
+    for valid in ["0", "512MB", "1.5TB", "100B"] {
+        assert!(is_valid_byte_size(valid), "{} should be valid", valid);
+    }
+    for invalid in ["50Gi", "50gb", "GB", "1.GB", "", "50 GB"] {
+        assert!(
+            !is_valid_byte_size(invalid),
+            "{} should be invalid",
+            invalid
+        );
+    }
+    let config = KamutConfig {
+        retention_size: Some("50Gi".to_string()),
+        ..config
+    };
+    let err = generate_prometheus_manifest(&config).unwrap_err();
+    assert!(err.to_string().contains("Invalid retention_size '50Gi'"));
+}
+
+#[test]
+fn test_prometheus_security_context() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    // The defaults apply when security_context is absent
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(manifest.contains(
+        "securityContext:\n    fsGroup: 2000\n    runAsNonRoot: true\n    runAsUser: 1000\n"
+    ));
+
+    // Configured fields override the defaults one by one
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    security_context:
+      run_as_user: 65534
+      run_as_group: 65534
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let security_context = &value["spec"]["securityContext"];
+    assert_eq!(security_context["fsGroup"], 2000);
+    assert_eq!(security_context["runAsUser"], 65534);
+    assert_eq!(security_context["runAsGroup"], 65534);
+    assert_eq!(security_context["runAsNonRoot"], true);
+
+    // enabled: false emits no securityContext, in raw mode as well
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    security_context:
+      enabled: false
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(!manifest.contains("securityContext"));
+
+    let raw_config = KamutConfig {
+        mode: Some("raw".to_string()),
+        ..config
+    };
+    let manifests = generate_prometheus_manifests(&raw_config).unwrap();
+    assert!(manifests
+        .iter()
+        .all(|manifest| !manifest.contains("securityContext")));
+
+    // Container-only fields have no place in the Prometheus pod security context
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    security_context:
+      restricted: true
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let err = generate_prometheus_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("security_context.restricted applies to containers"));
+}
+
+#[test]
+fn test_prometheus_external_labels_and_remote_write() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    external_labels:
+      cluster: prod-eu
+    remote_write:
+      - url: https://metrics.example.com/api/v1/write
+        name: central
+        remote_timeout: 30s
+        headers:
+          X-Scope-OrgID: team-a
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    assert_eq!(value["spec"]["externalLabels"]["cluster"], "prod-eu");
+
+    let remote_write = &value["spec"]["remoteWrite"][0];
+    assert_eq!(
+        remote_write["url"],
+        "https://metrics.example.com/api/v1/write"
+    );
+    assert_eq!(remote_write["name"], "central");
+    assert_eq!(remote_write["remoteTimeout"], "30s");
+    assert_eq!(remote_write["headers"]["X-Scope-OrgID"], "team-a");
+
+    // Absent fields leave the spec unchanged
+    let config = KamutConfig {
+        external_labels: HashMap::new(),
+        remote_write: Vec::new(),
+        ..config
+    };
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    assert!(!manifest.contains("externalLabels"));
+    assert!(!manifest.contains("remoteWrite"));
+}
+
 #[test]
 fn test_generate_prometheus_ingress() {
     // Create a test KamutConfig and Ingress for Prometheus
     let ingress_config = Ingress {
-        host: "test.example.com".to_string(),
+        host: Some("test.example.com".to_string()),
+        ..Default::default()
     };
 
     let config = KamutConfig {
@@ -187,6 +734,430 @@ fn test_generate_prometheus_ingress() {
     assert!(manifest.contains("number: 9090"));
 }
 
+#[test]
+fn test_generate_prometheus_ingress_rules() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    ingress:
+      host: prometheus.example.com
+      rules:
+        - host: metrics.example.com
+          paths:
+            - path: /prometheus
+            - path: /api/v1/query
+              path_type: Exact
+        - host: prom.example.com
+      tls_secret_name: prometheus-tls
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let ingress_config = config.ingress.clone().unwrap();
+
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let rules = value["spec"]["rules"].as_sequence().unwrap();
+    let paths = |index: usize| -> Vec<(String, String)> {
+        rules[index]["http"]["paths"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|path| {
+                (
+                    path["path"].as_str().unwrap().to_string(),
+                    path["pathType"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+
+    // The host shorthand comes first, then one rule per entry
+    let hosts: Vec<&str> = rules
+        .iter()
+        .map(|rule| rule["host"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        hosts,
+        [
+            "prometheus.example.com",
+            "metrics.example.com",
+            "prom.example.com"
+        ]
+    );
+    assert_eq!(paths(0), [("/".to_string(), "Prefix".to_string())]);
+    assert_eq!(
+        paths(1),
+        [
+            ("/prometheus".to_string(), "Prefix".to_string()),
+            ("/api/v1/query".to_string(), "Exact".to_string())
+        ]
+    );
+    assert_eq!(paths(2), [("/".to_string(), "Prefix".to_string())]);
+
+    // TLS covers every host by default
+    let tls_hosts = value["spec"]["tls"][0]["hosts"].as_sequence().unwrap();
+    assert_eq!(tls_hosts.len(), 3);
+
+    // Rules alone work without the host shorthand
+    let ingress_config = Ingress {
+        host: None,
+        ..ingress_config
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(!manifest.contains("host: prometheus.example.com"));
+
+    // At least one host is required, and path types are validated
+    let err = generate_prometheus_ingress(&config, &Ingress::default()).unwrap_err();
+    assert!(err.to_string().contains("requires host or rules"));
+    let yaml = r#"
+    host: prometheus.example.com
+    rules:
+      - host: metrics.example.com
+        paths:
+          - path: /
+            path_type: Regex
+    "#;
+    let ingress_config: Ingress = serde_yaml::from_str(yaml).unwrap();
+    let err = generate_prometheus_ingress(&config, &ingress_config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Invalid Ingress path_type 'Regex'"));
+}
+
+#[test]
+fn test_generate_prometheus_ingress_tls() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        ..Default::default()
+    };
+
+    // tls_hosts defaults to the ingress host
+    let ingress_config = Ingress {
+        host: Some("prometheus.example.com".to_string()),
+        tls_secret_name: Some("prometheus-tls".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(manifest.contains(
+        "tls:\n  - hosts:\n    - prometheus.example.com\n    secretName: prometheus-tls"
+    ));
+
+    let ingress_config = Ingress {
+        host: Some("prometheus.example.com".to_string()),
+        tls_secret_name: Some("wildcard-tls".to_string()),
+        tls_hosts: Some(vec!["*.example.com".to_string()]),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(manifest.contains("- '*.example.com'"));
+    assert!(manifest.contains("secretName: wildcard-tls"));
+
+    // Without TLS fields the ingress stays HTTP-only
+    let ingress_config = Ingress {
+        host: Some("prometheus.example.com".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(!manifest.contains("tls:"));
+}
+
+#[test]
+fn test_generate_prometheus_ingress_annotations() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        ..Default::default()
+    };
+
+    let mut annotations = HashMap::new();
+    annotations.insert(
+        "nginx.ingress.kubernetes.io/ssl-redirect".to_string(),
+        "true".to_string(),
+    );
+    annotations.insert(
+        "nginx.ingress.kubernetes.io/backend-protocol".to_string(),
+        "HTTP".to_string(),
+    );
+    let ingress_config = Ingress {
+        host: Some("prometheus.example.com".to_string()),
+        annotations: Some(annotations),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+
+    // Annotations are sorted by key
+    assert!(manifest.contains(
+        "annotations:\n    nginx.ingress.kubernetes.io/backend-protocol: HTTP\n    nginx.ingress.kubernetes.io/ssl-redirect: 'true'"
+    ));
+}
+
+#[test]
+fn test_generate_prometheus_ingress_cert_manager() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        ..Default::default()
+    };
+
+    let ingress_config: Ingress = serde_yaml::from_str(
+        r#"
+host: prometheus.example.com
+tls:
+  cert_manager_issuer: letsencrypt-prod
+"#,
+    )
+    .unwrap();
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    // The issuer annotation and the TLS block render together
+    assert_eq!(
+        value["metadata"]["annotations"]["cert-manager.io/cluster-issuer"],
+        "letsencrypt-prod"
+    );
+    assert_eq!(
+        value["spec"]["tls"][0]["hosts"][0],
+        "prometheus.example.com"
+    );
+    assert_eq!(
+        value["spec"]["tls"][0]["secretName"],
+        "prometheus.example.com-tls"
+    );
+
+    // The lower-level secret name still wins
+    let ingress_config = Ingress {
+        tls_secret_name: Some("custom-tls".to_string()),
+        ..ingress_config
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(manifest.contains("secretName: custom-tls"));
+}
+
+#[test]
+fn test_generate_prometheus_ingress_class_name() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        ..Default::default()
+    };
+
+    let ingress_config = Ingress {
+        host: Some("prometheus.example.com".to_string()),
+        class_name: Some("internal-nginx".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(manifest.contains("ingressClassName: internal-nginx"));
+
+    let ingress_config = Ingress {
+        class_name: None,
+        ..ingress_config
+    };
+    let manifest = generate_prometheus_ingress(&config, &ingress_config).unwrap();
+    assert!(!manifest.contains("ingressClassName"));
+}
+
+#[test]
+fn test_generated_manifests_omit_creation_timestamp() {
+    let ingress_config = Ingress {
+        host: Some("test.example.com".to_string()),
+        ..Default::default()
+    };
+
+    let config = KamutConfig {
+        name: "test-app".to_string(),
+        kind: Some("Prometheus".to_string()),
+        namespace: Some("monitoring".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        ingress: Some(ingress_config.clone()),
+        ..Default::default()
+    };
+
+    let manifests = vec![
+        generate_deployment_manifest(&config).unwrap(),
+        generate_prometheus_service(&config).unwrap(),
+        generate_prometheus_ingress(&config, &ingress_config).unwrap(),
+        generate_prometheus_manifest(&config).unwrap(),
+    ];
+
+    for manifest in manifests {
+        assert!(!manifest.contains("creationTimestamp"));
+        assert!(!manifest.contains("status:"));
+        assert!(!manifest.contains("null"));
+    }
+}
+
+#[test]
+fn test_serialize_manifest_strips_null_fields() {
+    let resource: serde_yaml::Value = serde_yaml::from_str(
+        r#"
+apiVersion: v1
+kind: Service
+metadata:
+  name: test
+  creationTimestamp: null
+spec:
+  template:
+    metadata:
+      creationTimestamp: null
+status: null
+"#,
+    )
+    .unwrap();
+
+    let manifest = serialize_manifest(&resource).unwrap();
+
+    assert!(manifest.contains("name: test"));
+    assert!(!manifest.contains("creationTimestamp"));
+    assert!(!manifest.contains("status"));
+}
+
+#[test]
+fn test_format_manifests_as_json_array() {
+    let manifests = vec![
+        "apiVersion: v1\nkind: Service\nmetadata:\n  name: a\n".to_string(),
+        "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: b\n".to_string(),
+    ];
+
+    let yaml = format_manifests(&manifests, OutputFormat::Yaml).unwrap();
+    assert_eq!(yaml, manifests.join("\n---\n"));
+
+    let json = format_manifests(&manifests, OutputFormat::Json).unwrap();
+    let resources: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let resources = resources.as_array().unwrap();
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0]["kind"], "Service");
+    assert_eq!(resources[1]["metadata"]["name"], "b");
+}
+
+#[test]
+fn test_no_managed_labels_keeps_user_labels() {
+    let manifest = r#"apiVersion: v1
+kind: Service
+metadata:
+  annotations:
+    kamut.dev/source: test.kamut.yaml
+    team.example.com/owner: observability
+  labels:
+    app: test-app
+    app.kubernetes.io/managed-by: kamut
+    kamut.dev/revision: abc123
+    team: observability
+  name: test-app
+spec:
+  selector:
+    app: test-app
+"#;
+
+    let options = ProcessOptions {
+        no_managed_labels: true,
+        ..Default::default()
+    };
+    let output = postprocess_manifest(manifest, &options).unwrap();
+
+    // Only user-declared metadata remains; the app label is controlled separately
+    assert!(!output.contains("kamut.dev/"));
+    assert!(!output.contains("app.kubernetes.io/managed-by"));
+    assert!(output.contains("team.example.com/owner: observability"));
+    assert!(output.contains("team: observability"));
+    assert!(output.contains("app: test-app"));
+
+    // --no-app-label drops the metadata label but keeps the selector
+    let options = ProcessOptions {
+        no_managed_labels: true,
+        no_app_label: true,
+        ..Default::default()
+    };
+    let output = postprocess_manifest(manifest, &options).unwrap();
+    assert!(output.contains("labels:\n    team: observability\n  name"));
+    assert!(output.contains("selector:\n    app: test-app"));
+}
+
+#[test]
+fn test_trim_removes_default_fields() {
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_prometheus_service(&config).unwrap();
+    assert!(manifest.contains("protocol: TCP"));
+    assert!(manifest.contains("type: ClusterIP"));
+
+    let options = ProcessOptions {
+        trim: true,
+        ..Default::default()
+    };
+    let trimmed = postprocess_manifest(&manifest, &options).unwrap();
+
+    assert!(!trimmed.contains("protocol: TCP"));
+    assert!(!trimmed.contains("type: ClusterIP"));
+    assert!(trimmed.contains("- name: web\n    port: 9090\n    targetPort: 9090"));
+
+    // Non-default values are kept
+    let udp = manifest.replace("protocol: TCP", "protocol: UDP");
+    let trimmed = postprocess_manifest(&udp, &options).unwrap();
+    assert!(trimmed.contains("protocol: UDP"));
+}
+
+#[test]
+fn test_kubectl_order_sorts_top_level_keys() {
+    let manifest = r#"spec:
+  replicas: 1
+metadata:
+  name: test-app
+automountServiceAccountToken: true
+kind: Deployment
+apiVersion: apps/v1
+"#;
+
+    let options = ProcessOptions {
+        kubectl_order: true,
+        ..Default::default()
+    };
+    let output = postprocess_manifest(manifest, &options).unwrap();
+
+    let value: serde_yaml::Mapping = serde_yaml::from_str(&output).unwrap();
+    let keys: Vec<&str> = value.keys().filter_map(|key| key.as_str()).collect();
+    assert_eq!(
+        keys,
+        [
+            "apiVersion",
+            "kind",
+            "metadata",
+            "spec",
+            "automountServiceAccountToken"
+        ]
+    );
+
+    // Nested keys keep their order
+    assert!(output.contains("metadata:\n  name: test-app\nspec:\n  replicas: 1"));
+}
+
+#[test]
+fn test_apply_image_registry() {
+    assert_eq!(
+        apply_image_registry("myapp:1.0", "registry.internal"),
+        "registry.internal/myapp:1.0"
+    );
+    assert_eq!(
+        apply_image_registry("prom/prometheus:v2.7.1", "registry.internal/"),
+        "registry.internal/prom/prometheus:v2.7.1"
+    );
+
+    // Images that already name a registry host are left alone
+    assert_eq!(
+        apply_image_registry("docker.io/library/nginx", "registry.internal"),
+        "docker.io/library/nginx"
+    );
+    assert_eq!(
+        apply_image_registry("localhost:5000/myapp:1.0", "registry.internal"),
+        "localhost:5000/myapp:1.0"
+    );
+}
+
 #[test]
 fn test_process_file() {
     // Create a temporary directory
@@ -258,4 +1229,546 @@ service_account:
     assert!(output_content.contains("name: test-prometheus-role-binding"));
     assert!(output_content.contains("kind: ServiceAccount"));
     assert!(output_content.contains("name: prometheus-test-prometheus"));
-}
\ No newline at end of file
+}
+#[test]
+fn test_companion_metadata_is_scoped() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("prometheus.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: main
+kind: Prometheus
+image: prom/prometheus:v2.7.1
+service:
+  annotations:
+    service.beta.kubernetes.io/aws-load-balancer-internal: "true"
+service_account:
+  labels:
+    team: observability
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    let manifest = |kind: &str| {
+        rendered
+            .manifests
+            .iter()
+            .find(|manifest| manifest.contains(&format!("kind: {}\n", kind)))
+            .unwrap()
+    };
+
+    // The annotation lands only on the Service
+    let annotation = "service.beta.kubernetes.io/aws-load-balancer-internal";
+    assert!(manifest("Service").contains(annotation));
+    assert!(!manifest("Prometheus").contains(annotation));
+
+    // The labels land only on the RBAC resources
+    for kind in ["ServiceAccount", "ClusterRole", "ClusterRoleBinding"] {
+        assert!(manifest(kind).contains("team: observability"), "{}", kind);
+    }
+    assert!(!manifest("Prometheus").contains("team: observability"));
+    assert!(!manifest("Service").contains("team: observability"));
+}
+
+#[test]
+fn test_output_uses_block_style_sequences() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: web
+kind: Deployment
+image: web:v1.0.0
+command: ["/bin/web", "--serve"]
+ports:
+  - name: http
+    container_port: 8080
+service:
+  port: 80
+---
+name: main
+kind: Prometheus
+image: prom/prometheus:v2.7.1
+ingress:
+  host: prometheus.example.com
+  tls_hosts: [prometheus.example.com, prom.example.com]
+---
+name: pods
+kind: KubeScrapeConfig
+role: pod
+"#,
+    )
+    .unwrap();
+
+    for (indent, indent_sequences) in [(None, false), (Some(4), false), (Some(2), true)] {
+        let options = ProcessOptions {
+            quiet: true,
+            indent,
+            indent_sequences,
+            ..Default::default()
+        };
+        let rendered = render_file(&file_path, &options).unwrap();
+        for manifest in &rendered.manifests {
+            for line in manifest.lines() {
+                let line = line.trim_start().trim_start_matches("- ");
+                assert!(
+                    (!line.starts_with('[') && !line.contains(": [")) || line.ends_with("[]"),
+                    "flow-style sequence in: {}",
+                    line
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_postprocess_manifest_indent() {
+    let manifest = "apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: app
+  labels:
+    app: app
+data:
+  app.properties: |
+    a=1
+    b=2
+list:
+- name: first
+  ports:
+  - 80
+  - 443
+- plain
+";
+
+    let options = ProcessOptions {
+        indent: Some(4),
+        indent_sequences: true,
+        ..Default::default()
+    };
+    let indented = postprocess_manifest(manifest, &options).unwrap();
+    assert!(indented.contains("metadata:\n    name: app\n    labels:\n        app: app\n"));
+    assert!(indented.contains(
+        "list:\n    - name: first\n      ports:\n          - 80\n          - 443\n    - plain\n"
+    ));
+    assert!(indented.contains("    app.properties: |\n        a=1\n        b=2\n"));
+
+    // Reformatting never changes the content
+    let original: serde_yaml::Value = serde_yaml::from_str(manifest).unwrap();
+    let reformatted: serde_yaml::Value = serde_yaml::from_str(&indented).unwrap();
+    assert_eq!(original, reformatted);
+}
+
+#[test]
+fn test_content_hash_label_is_stable() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("app.kamut.yaml");
+    let options = ProcessOptions {
+        content_hash: true,
+        ..Default::default()
+    };
+    let hashes = |config: &str| -> Vec<String> {
+        fs::write(&file_path, config).unwrap();
+        render_file(&file_path, &options)
+            .unwrap()
+            .manifests
+            .iter()
+            .map(|manifest| {
+                let value: serde_yaml::Value = serde_yaml::from_str(manifest).unwrap();
+                value["metadata"]["labels"][CONTENT_HASH_LABEL]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+
+    let config = "name: web\nkind: Deployment\nimage: web:v1.0.0\nservice:\n  port: 80\n";
+    let first = hashes(config);
+    assert_eq!(first.len(), 2);
+    assert!(first
+        .iter()
+        .all(|hash| hash.len() == 8 && hash.chars().all(|c| c.is_ascii_hexdigit())));
+
+    // Unchanged config, same hashes
+    assert_eq!(hashes(config), first);
+
+    // Only the changed Deployment gets a new hash
+    let changed = hashes(&config.replace("v1.0.0", "v1.0.1"));
+    assert_ne!(changed[0], first[0]);
+    assert_eq!(changed[1], first[1]);
+}
+
+#[test]
+fn test_sync_waves_order_resources_by_kind() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("prometheus.kamut.yaml");
+    let waves = |config: &str, sync_waves: bool| -> HashMap<String, i32> {
+        fs::write(&file_path, config).unwrap();
+        let options = ProcessOptions {
+            sync_waves,
+            ..Default::default()
+        };
+        render_file(&file_path, &options)
+            .unwrap()
+            .manifests
+            .iter()
+            .filter_map(|manifest| {
+                let value: serde_yaml::Value = serde_yaml::from_str(manifest).unwrap();
+                let wave = value["metadata"]["annotations"][SYNC_WAVE_ANNOTATION].as_str()?;
+                Some((
+                    value["kind"].as_str().unwrap().to_string(),
+                    wave.parse().unwrap(),
+                ))
+            })
+            .collect()
+    };
+
+    // Raw mode generates a Deployment and the ServiceAccount it runs as
+    let config = "name: prometheus\nkind: Prometheus\nimage: prom/prometheus:v2.53.0\nmode: raw\n";
+    let by_kind = waves(config, true);
+    assert!(by_kind["ServiceAccount"] < by_kind["Deployment"]);
+    assert!(by_kind["ConfigMap"] < by_kind["Deployment"]);
+    assert_eq!(by_kind["Deployment"], 0);
+
+    // Without the flag nothing is annotated
+    assert!(waves(config, false).is_empty());
+
+    // An explicit sync_wave applies to every resource of the document
+    let by_kind = waves(&format!("{}sync_wave: 5\n", config), false);
+    assert!(!by_kind.is_empty());
+    assert!(by_kind.values().all(|wave| *wave == 5));
+}
+
+#[test]
+fn test_quantity_validation() {
+    for valid in [
+        "500m", "1", "0.5", ".5", "+1", "128Mi", "1Gi", "2k", "1.5G", "1e3", "1E", "2E-3",
+    ] {
+        assert!(is_valid_quantity(valid), "{} should be valid", valid);
+    }
+    for invalid in ["100MB", "1gi", "", "Mi", "1..5", "1e", "1 Gi", "one"] {
+        assert!(!is_valid_quantity(invalid), "{} should be invalid", invalid);
+    }
+
+    let config = KamutConfig {
+        name: "test-prometheus".to_string(),
+        kind: Some("Prometheus".to_string()),
+        image: Some("prom/prometheus:v2.7.1".to_string()),
+        resources: Some(Resources {
+            requests: None,
+            limits: Some(ResourceSpec {
+                cpu: Some("1".to_string()),
+                memory: Some("100MB".to_string()),
+            }),
+        }),
+        ..Default::default()
+    };
+    let err = generate_prometheus_manifest(&config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid quantity '100MB' for resources.limits.memory (expected e.g. 500m, 128Mi, 1Gi)"
+    );
+
+    let config = KamutConfig {
+        kind: Some("Deployment".to_string()),
+        ..config
+    };
+    let err = generate_deployment_manifest(&config).unwrap_err();
+    assert!(err.to_string().contains("resources.limits.memory"));
+}
+
+#[test]
+fn test_namespaced_resources_carry_namespace() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("apps.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: web
+kind: Deployment
+namespace: apps
+image: web:v1.0.0
+port: "8080"
+autoscaling:
+  min_replicas: 2
+  max_replicas: 6
+---
+name: db
+kind: StatefulSet
+namespace: apps
+image: postgres:16
+storage:
+  - name: data
+    size: 20Gi
+---
+name: node-exporter
+kind: DaemonSet
+namespace: apps
+image: prom/node-exporter:v1.8.0
+---
+name: prometheus
+kind: Prometheus
+namespace: apps
+---
+name: prometheus-raw
+kind: Prometheus
+namespace: apps
+image: prom/prometheus:v2.53.0
+mode: raw
+storage:
+  size: 10Gi
+---
+name: pods
+kind: KubeScrapeConfig
+namespace: apps
+role: pod
+---
+name: api
+kind: ServiceMonitor
+namespace: apps
+port: http-metrics
+---
+name: high
+kind: PriorityClass
+namespace: apps
+value: 1000
+---
+name: settings
+kind: ConfigMap
+namespace: apps
+data:
+  LOG_LEVEL: info
+---
+name: credentials
+kind: Secret
+namespace: apps
+string_data:
+  password: s3cret
+---
+name: external-db
+kind: Endpoints
+namespace: apps
+endpoints:
+  addresses:
+    - 10.0.0.10
+  ports:
+    - name: postgres
+      port: 5432
+---
+name: shop
+kind: Ingress
+namespace: apps
+ingress:
+  host: shop.example.com
+  paths:
+    - path: /
+      service: shop
+      port: 80
+---
+name: team-alerts
+kind: AlertmanagerConfig
+namespace: apps
+route:
+  receiver: oncall
+receivers:
+  - name: oncall
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.rendered_count, 13);
+
+    for manifest in &rendered.manifests {
+        let resource: serde_yaml::Value = serde_yaml::from_str(manifest).unwrap();
+        let kind = resource["kind"].as_str().unwrap();
+        let namespace = resource["metadata"]["namespace"].as_str();
+        if kamut::cluster::CLUSTER_SCOPED_KINDS.contains(&kind) {
+            assert_eq!(namespace, None, "{} should be cluster-scoped", kind);
+        } else {
+            assert_eq!(namespace, Some("apps"), "{} is missing its namespace", kind);
+        }
+        assert!(
+            resource["metadata"]["labels"]["app"].is_string(),
+            "{}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_process_file_outcome() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: shop
+kind: Deployment
+image: shop:v1.0.0
+retention: 30d
+---
+name: settings
+kind: ConfigMap
+data:
+  LOG_LEVEL: info
+---
+name: broken
+kind: StatefulSet
+"#,
+    )
+    .unwrap();
+
+    let outcome = process_file(&file_path).unwrap();
+    assert_eq!(outcome.input, file_path);
+    assert_eq!(outcome.document_count, 3);
+    assert_eq!(outcome.rendered_count, 3);
+
+    let resources: Vec<(&str, &str)> = outcome
+        .resources
+        .iter()
+        .map(|resource| (resource.kind.as_str(), resource.name.as_str()))
+        .collect();
+    assert_eq!(
+        resources,
+        vec![("Deployment", "shop"), ("ConfigMap", "settings")]
+    );
+    assert!(outcome.resources[1].yaml.contains("LOG_LEVEL: info"));
+
+    // Problems are returned instead of printed
+    assert_eq!(outcome.warnings.len(), 1);
+    assert!(outcome.warnings[0].starts_with("field 'retention' is ignored for kind Deployment"));
+    assert_eq!(outcome.errors.len(), 1);
+    assert!(outcome.errors[0].starts_with("StatefulSet broken: "));
+
+    // The output file is still written
+    let output_path = temp_dir.path().join("shop.yaml");
+    assert_eq!(outcome.output_files, vec![output_path.clone()]);
+    let written = fs::read_to_string(output_path).unwrap();
+    for resource in &outcome.resources {
+        assert!(written.contains(&resource.yaml));
+    }
+}
+
+#[test]
+fn test_process_file_outcome_stdout_and_dry_run() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("shop.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: settings\nkind: ConfigMap\ndata:\n  LOG_LEVEL: info\n",
+    )
+    .unwrap();
+
+    // --stdout returns the manifest stream instead of printing it
+    let options = ProcessOptions {
+        stdout: true,
+        ..Default::default()
+    };
+    let outcome = process_file_with_options(&file_path, &options).unwrap();
+    let stdout = outcome.stdout.unwrap();
+    assert!(stdout.starts_with("---\n"));
+    assert!(stdout.contains("LOG_LEVEL: info"));
+    assert!(outcome.output_files.is_empty());
+
+    // --dry-run returns the files it would write with their sizes
+    let output_dir = temp_dir.path().join("manifests");
+    let options = ProcessOptions {
+        dry_run: true,
+        output_dir: Some(output_dir.clone()),
+        ..Default::default()
+    };
+    let outcome = process_file_with_options(&file_path, &options).unwrap();
+    assert!(outcome.stdout.is_none());
+    assert_eq!(outcome.output_files, vec![output_dir.join("shop.yaml")]);
+    assert_eq!(outcome.output_sizes.len(), 1);
+    assert!(outcome.output_sizes[0] > outcome.resources[0].yaml.len());
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn test_generate_from_str() {
+    let resources = generate_from_str(
+        r#"# kamut: namespace=shop
+name: shop
+kind: Deployment
+image: shop:v1.0.0
+service:
+  port: 8080
+---
+name: settings
+kind: ConfigMap
+namespace: config
+data:
+  LOG_LEVEL: info
+"#,
+    )
+    .unwrap();
+
+    let summary: Vec<(&str, Option<&str>, &str)> = resources
+        .iter()
+        .map(|resource| {
+            (
+                resource.kind.as_str(),
+                resource.namespace.as_deref(),
+                resource.name.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Deployment", Some("shop"), "shop"),
+            ("Service", Some("shop"), "shop"),
+            ("ConfigMap", Some("config"), "settings"),
+        ]
+    );
+    assert!(resources[2].yaml.contains("LOG_LEVEL: info"));
+
+    // A document that would be skipped fails the call
+    let err = generate_from_str("name: broken\nkind: StatefulSet\n").unwrap_err();
+    assert!(
+        err.to_string().starts_with("StatefulSet broken: "),
+        "{}",
+        err
+    );
+    let err = generate_from_str("name: broken\nimage: broken:v1\n").unwrap_err();
+    assert!(
+        err.to_string().contains("'kind' field is required"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_prometheus_tolerations_follow_sorted_node_selector() {
+    let yaml = r#"
+    name: prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.53.0
+    node_selector:
+      zone: eu-west-1a
+      group: monitoring
+      disk: ssd
+      arch: arm64
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_prometheus_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let keys: Vec<&str> = value["spec"]["tolerations"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|toleration| toleration["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(keys, vec!["arch", "disk", "group", "zone"]);
+}
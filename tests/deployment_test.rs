@@ -0,0 +1,1272 @@
+use kamut::config::{
+    generate_daemonset_manifest, generate_deployment_manifest, generate_deployment_manifests,
+    generate_deployment_service, generate_hpa_manifest, generate_keda_scaled_object,
+    generate_statefulset_manifest, render_file, ProcessOptions,
+};
+use kamut::models::{AutoscalingConfig, EnvFromRefConfig, KamutConfig, ResizePolicy, VolumeConfig};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_deployment_resize_policy() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    resize_policy:
+      - resourceName: cpu
+        restartPolicy: NotRequired
+      - resourceName: memory
+        restartPolicy: RestartContainer
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+
+    assert!(manifest.contains("resizePolicy:"));
+    assert!(manifest.contains("- resourceName: cpu\n          restartPolicy: NotRequired"));
+    assert!(manifest.contains("- resourceName: memory\n          restartPolicy: RestartContainer"));
+}
+
+#[test]
+fn test_deployment_resize_policy_invalid_resource() {
+    let config = KamutConfig {
+        name: "api".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("api:v1.0.0".to_string()),
+        resize_policy: Some(vec![ResizePolicy {
+            resource_name: "storage".to_string(),
+            restart_policy: "NotRequired".to_string(),
+        }]),
+        ..Default::default()
+    };
+
+    let result = generate_deployment_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid resize_policy resourceName 'storage'"));
+}
+
+#[test]
+fn test_statefulset_volume_claim_templates() {
+    let yaml = r#"
+    name: postgres
+    kind: StatefulSet
+    image: postgres:16
+    storage:
+      - name: data
+        size: 20Gi
+        className: gp3
+        mountPath: /var/lib/postgresql/data
+      - name: wal
+        size: 5Gi
+        className: gp3
+        mountPath: /var/lib/postgresql/wal
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_statefulset_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    assert_eq!(value["kind"], "StatefulSet");
+    assert_eq!(value["spec"]["serviceName"], "postgres");
+
+    let templates = value["spec"]["volumeClaimTemplates"].as_sequence().unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates[0]["metadata"]["name"], "data");
+    assert_eq!(
+        templates[0]["spec"]["resources"]["requests"]["storage"],
+        "20Gi"
+    );
+    assert_eq!(templates[1]["metadata"]["name"], "wal");
+    assert_eq!(templates[1]["spec"]["storageClassName"], "gp3");
+
+    let mounts = &value["spec"]["template"]["spec"]["containers"][0]["volumeMounts"];
+    assert_eq!(mounts[0]["name"], "data");
+    assert_eq!(mounts[0]["mountPath"], "/var/lib/postgresql/data");
+    assert_eq!(mounts[1]["name"], "wal");
+    assert_eq!(mounts[1]["mountPath"], "/var/lib/postgresql/wal");
+}
+
+#[test]
+fn test_statefulset_requires_image() {
+    let config = KamutConfig {
+        name: "postgres".to_string(),
+        kind: Some("StatefulSet".to_string()),
+        ..Default::default()
+    };
+
+    let result = generate_statefulset_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Image is required for StatefulSet"));
+}
+
+#[test]
+fn test_daemonset_host_port() {
+    let yaml = r#"
+    name: node-exporter
+    kind: DaemonSet
+    namespace: monitoring
+    image: prom/node-exporter:v1.8.2
+    ports:
+      - name: metrics
+        container_port: 9100
+        host_port: 9100
+    tolerations:
+      - operator: Exists
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_daemonset_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    assert_eq!(value["apiVersion"], "apps/v1");
+    assert_eq!(value["kind"], "DaemonSet");
+    assert_eq!(value["metadata"]["namespace"], "monitoring");
+    assert_eq!(
+        value["spec"]["selector"]["matchLabels"]["app"],
+        "node-exporter"
+    );
+
+    let pod_spec = &value["spec"]["template"]["spec"];
+    let port = &pod_spec["containers"][0]["ports"][0];
+    assert_eq!(port["name"], "metrics");
+    assert_eq!(port["containerPort"], 9100);
+    assert_eq!(port["hostPort"], 9100);
+    assert_eq!(pod_spec["tolerations"][0]["operator"], "Exists");
+}
+
+#[test]
+fn test_deployment_host_port_warns() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("agent.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: agent\nkind: Deployment\nimage: agent:v1.0.0\nports:\n  - container_port: 8125\n    host_port: 8125\n",
+    )
+    .unwrap();
+
+    // The hostPort is emitted with a warning
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert!(rendered.manifests[0].contains("hostPort: 8125"));
+
+    // Under --strict the warning fails the file
+    let options = ProcessOptions {
+        quiet: true,
+        strict: true,
+        ..Default::default()
+    };
+    let err = render_file(&file_path, &options).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("hostPort 8125 of Deployment agent allows one pod per node"));
+}
+
+#[test]
+fn test_deployment_probes() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    probes:
+      liveness:
+        path: /healthz
+        port: 8080
+        initial_delay_seconds: 10
+        period_seconds: 5
+      readiness:
+        path: /ready
+        port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let container = &value["spec"]["template"]["spec"]["containers"][0];
+
+    assert_eq!(container["livenessProbe"]["httpGet"]["path"], "/healthz");
+    assert_eq!(container["livenessProbe"]["httpGet"]["port"], 8080);
+    assert_eq!(container["livenessProbe"]["initialDelaySeconds"], 10);
+    assert_eq!(container["livenessProbe"]["periodSeconds"], 5);
+    assert_eq!(container["readinessProbe"]["httpGet"]["path"], "/ready");
+    assert!(container["readinessProbe"]
+        .get("initialDelaySeconds")
+        .is_none());
+}
+
+#[test]
+fn test_deployment_only_readiness_probe() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    probes:
+      readiness:
+        path: /ready
+        port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+
+    assert!(manifest.contains("readinessProbe:"));
+    assert!(!manifest.contains("livenessProbe:"));
+}
+
+#[test]
+fn test_deployment_without_probes() {
+    let config = KamutConfig {
+        name: "api".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("api:v1.0.0".to_string()),
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+
+    assert!(!manifest.contains("Probe:"));
+}
+
+#[test]
+fn test_deployment_container_ports() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    ports:
+      - name: http
+        container_port: 8080
+      - name: metrics
+        container_port: 9100
+        protocol: UDP
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let ports = &value["spec"]["template"]["spec"]["containers"][0]["ports"];
+
+    assert_eq!(ports[0]["name"], "http");
+    assert_eq!(ports[0]["containerPort"], 8080);
+    assert_eq!(ports[0]["protocol"], "TCP");
+    assert_eq!(ports[1]["name"], "metrics");
+    assert_eq!(ports[1]["containerPort"], 9100);
+    assert_eq!(ports[1]["protocol"], "UDP");
+}
+
+#[test]
+fn test_deployment_empty_ports() {
+    let config = KamutConfig {
+        name: "api".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("api:v1.0.0".to_string()),
+        ports: Some(Vec::new()),
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+
+    assert!(!manifest.contains("ports:"));
+}
+
+#[test]
+fn test_deployment_service() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    service:
+      type: NodePort
+      port: 80
+      target_port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_service(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    assert_eq!(value["kind"], "Service");
+    assert_eq!(value["metadata"]["name"], "api");
+    assert_eq!(value["spec"]["type"], "NodePort");
+    assert_eq!(value["spec"]["selector"]["app"], "api");
+    assert_eq!(value["spec"]["ports"][0]["name"], "http");
+    assert_eq!(value["spec"]["ports"][0]["port"], 80);
+    assert_eq!(value["spec"]["ports"][0]["targetPort"], 8080);
+}
+
+#[test]
+fn test_deployment_service_defaults_to_container_port() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    ports:
+      - name: http
+        container_port: 8080
+    service: {}
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_service(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    assert_eq!(value["spec"]["type"], "ClusterIP");
+    assert_eq!(value["spec"]["ports"][0]["port"], 8080);
+    assert_eq!(value["spec"]["ports"][0]["targetPort"], 8080);
+}
+
+#[test]
+fn test_deployment_service_only_when_configured() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("api.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: api\nkind: Deployment\nimage: api:v1.0.0\n---\nname: web\nkind: Deployment\nimage: web:v1.0.0\nservice:\n  port: 80\n",
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+
+    let kinds: Vec<&str> = rendered
+        .manifests
+        .iter()
+        .map(|manifest| {
+            manifest
+                .lines()
+                .find_map(|line| line.strip_prefix("kind: "))
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(kinds, ["Deployment", "Deployment", "Service"]);
+}
+
+#[test]
+fn test_deployment_restricted_security_context() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    security_context:
+      restricted: true
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let security_context = &value["spec"]["template"]["spec"]["containers"][0]["securityContext"];
+
+    assert_eq!(security_context["allowPrivilegeEscalation"], false);
+    assert_eq!(security_context["runAsNonRoot"], true);
+    assert_eq!(security_context["capabilities"]["drop"][0], "ALL");
+    assert_eq!(security_context["seccompProfile"]["type"], "RuntimeDefault");
+}
+
+#[test]
+fn test_deployment_security_context_capabilities() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    security_context:
+      restricted: true
+      capabilities:
+        add: [NET_BIND_SERVICE]
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let capabilities =
+        &value["spec"]["template"]["spec"]["containers"][0]["securityContext"]["capabilities"];
+
+    assert_eq!(capabilities["add"][0], "NET_BIND_SERVICE");
+    assert_eq!(capabilities["drop"][0], "ALL");
+}
+
+#[test]
+fn test_deployment_security_context_user_and_fs_group() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    security_context:
+      run_as_user: 10001
+      run_as_group: 10001
+      fs_group: 10001
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let pod_spec = &value["spec"]["template"]["spec"];
+
+    // The user and group apply to the container, the volume group to the pod
+    let container_security_context = &pod_spec["containers"][0]["securityContext"];
+    assert_eq!(container_security_context["runAsUser"], 10001);
+    assert_eq!(container_security_context["runAsGroup"], 10001);
+    assert_eq!(pod_spec["securityContext"]["fsGroup"], 10001);
+
+    // enabled: false drops both
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    security_context:
+      enabled: false
+      run_as_user: 10001
+      fs_group: 10001
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("securityContext"));
+}
+
+#[test]
+fn test_deployment_hpa() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        namespace: Some("apps".to_string()),
+        image: Some("web:v1.0.0".to_string()),
+        autoscaling: Some(AutoscalingConfig {
+            min_replicas: Some(2),
+            max_replicas: 10,
+            target_cpu_utilization: Some(70),
+        }),
+        ..Default::default()
+    };
+
+    let manifest = generate_hpa_manifest(&config).unwrap();
+    assert!(manifest.contains("apiVersion: autoscaling/v2"));
+    assert!(manifest.contains("kind: HorizontalPodAutoscaler"));
+    assert!(manifest.contains("namespace: apps"));
+    assert!(manifest.contains("kind: Deployment\n    name: web"));
+    assert!(manifest.contains("minReplicas: 2"));
+    assert!(manifest.contains("maxReplicas: 10"));
+    assert!(manifest.contains("averageUtilization: 70"));
+}
+
+#[test]
+fn test_deployment_hpa_rejects_inverted_bounds() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        image: Some("web:v1.0.0".to_string()),
+        autoscaling: Some(AutoscalingConfig {
+            min_replicas: Some(5),
+            max_replicas: 2,
+            target_cpu_utilization: None,
+        }),
+        ..Default::default()
+    };
+
+    let err = generate_hpa_manifest(&config).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("max_replicas (2) must be at least min_replicas (5)"));
+}
+
+#[test]
+fn test_deployment_autoscaling_overrides_replicas() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("web.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: web\nkind: Deployment\nimage: web:v1.0.0\nreplicas: 3\nautoscaling:\n  min_replicas: 2\n  max_replicas: 6\n",
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+
+    assert_eq!(rendered.manifests.len(), 2);
+    assert!(!rendered.manifests[0].contains("replicas: 3"));
+    assert!(rendered.manifests[1].contains("kind: HorizontalPodAutoscaler"));
+    assert!(rendered.manifests[1].contains("minReplicas: 2"));
+}
+
+#[test]
+fn test_deployment_env_map_is_sorted() {
+    let yaml = r#"
+    name: web
+    kind: Deployment
+    image: web:v1.0.0
+    env:
+      ZONE: eu-west-1a
+      APP_ENV: production
+      LOG_LEVEL: info
+      DB_HOST: db.internal
+      METRICS_PORT: "9100"
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    // Every run emits the map entries in the same, sorted order
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let positions: Vec<usize> = ["APP_ENV", "DB_HOST", "LOG_LEVEL", "METRICS_PORT", "ZONE"]
+        .iter()
+        .map(|name| manifest.find(&format!("name: {}", name)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    for _ in 0..5 {
+        assert_eq!(generate_deployment_manifest(&config).unwrap(), manifest);
+    }
+}
+
+#[test]
+fn test_deployment_env_list_keeps_order() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("web.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: web
+kind: Deployment
+image: web:v1.0.0
+env:
+  - name: POD_PORT
+    value: "8080"
+  - name: LISTEN_ADDR
+    value: "0.0.0.0:$(POD_PORT)"
+  - name: APP_ENV
+    value: production
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    let manifest = &rendered.manifests[0];
+
+    // The referenced variable must come before the one expanding it
+    let pod_port = manifest.find("name: POD_PORT").unwrap();
+    let listen_addr = manifest.find("name: LISTEN_ADDR").unwrap();
+    let app_env = manifest.find("name: APP_ENV").unwrap();
+    assert!(pod_port < listen_addr && listen_addr < app_env);
+    assert!(manifest.contains("value: 0.0.0.0:$(POD_PORT)"));
+}
+
+#[test]
+fn test_deployment_config_map_and_secret_volumes() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("web:v1.0.0".to_string()),
+        volumes: Some(vec![
+            VolumeConfig {
+                name: "config".to_string(),
+                config_map: Some("web-config".to_string()),
+                mount_path: "/etc/web".to_string(),
+                ..Default::default()
+            },
+            VolumeConfig {
+                name: "tls".to_string(),
+                secret: Some("web-tls".to_string()),
+                mount_path: "/etc/tls".to_string(),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains("volumeMounts:"));
+    assert!(manifest.contains("mountPath: /etc/web"));
+    assert!(manifest.contains("mountPath: /etc/tls"));
+    assert!(manifest.contains("configMap:\n          name: web-config"));
+    assert!(manifest.contains("secret:\n          secretName: web-tls"));
+}
+
+#[test]
+fn test_deployment_volume_rejects_config_map_and_secret() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("web:v1.0.0".to_string()),
+        volumes: Some(vec![VolumeConfig {
+            name: "config".to_string(),
+            config_map: Some("web-config".to_string()),
+            secret: Some("web-secret".to_string()),
+            mount_path: "/etc/web".to_string(),
+        }]),
+        ..Default::default()
+    };
+
+    let err = generate_deployment_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("volume 'config' sets both config_map and secret"));
+}
+
+#[test]
+fn test_workload_image_pull_secrets() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("registry.example.com/web:v1.0.0".to_string()),
+        image_pull_secrets: vec!["registry-credentials".to_string()],
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains("imagePullSecrets:\n      - name: registry-credentials"));
+
+    let manifest = generate_statefulset_manifest(&KamutConfig {
+        kind: Some("StatefulSet".to_string()),
+        ..config
+    })
+    .unwrap();
+    assert!(manifest.contains("imagePullSecrets:\n      - name: registry-credentials"));
+
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("web:v1.0.0".to_string()),
+        ..Default::default()
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("imagePullSecrets"));
+}
+
+#[test]
+fn test_deployment_command_and_args() {
+    let config = KamutConfig {
+        name: "worker".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("worker:v1.0.0".to_string()),
+        command: vec!["/bin/worker".to_string()],
+        args: vec!["--queue".to_string(), "default".to_string()],
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains("command:\n        - /bin/worker"));
+    assert!(manifest.contains("args:\n        - --queue\n        - default"));
+
+    let config = KamutConfig {
+        command: Vec::new(),
+        args: Vec::new(),
+        ..config
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("command:"));
+    assert!(!manifest.contains("args:"));
+}
+
+#[test]
+fn test_deployment_env_from_secret_and_config_map() {
+    let config = KamutConfig {
+        name: "web".to_string(),
+        kind: Some("Deployment".to_string()),
+        image: Some("web:v1.0.0".to_string()),
+        env_from_refs: Some(vec![
+            EnvFromRefConfig {
+                name: "DATABASE_PASSWORD".to_string(),
+                secret: Some("web-db".to_string()),
+                key: "password".to_string(),
+                ..Default::default()
+            },
+            EnvFromRefConfig {
+                name: "LOG_LEVEL".to_string(),
+                config_map: Some("web-config".to_string()),
+                key: "log-level".to_string(),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    };
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains(
+        "- name: DATABASE_PASSWORD\n          valueFrom:\n            secretKeyRef:\n              key: password\n              name: web-db"
+    ));
+    assert!(manifest.contains(
+        "- name: LOG_LEVEL\n          valueFrom:\n            configMapKeyRef:\n              key: log-level\n              name: web-config"
+    ));
+
+    let config = KamutConfig {
+        env_from_refs: Some(vec![EnvFromRefConfig {
+            name: "TOKEN".to_string(),
+            key: "token".to_string(),
+            ..Default::default()
+        }]),
+        ..config
+    };
+    let err = generate_deployment_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("requires either secret or config_map"));
+}
+
+#[test]
+fn test_deployment_env_from_prefix() {
+    let yaml = r#"
+    name: web
+    kind: Deployment
+    image: web:v1.0.0
+    env_from:
+      - config_map: web-config
+        prefix: APP_
+      - secret: web-secrets
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let env_from = &value["spec"]["template"]["spec"]["containers"][0]["envFrom"];
+    assert_eq!(env_from[0]["prefix"], "APP_");
+    assert_eq!(env_from[0]["configMapRef"]["name"], "web-config");
+    assert!(env_from[1].get("prefix").is_none());
+    assert_eq!(env_from[1]["secretRef"]["name"], "web-secrets");
+}
+
+#[test]
+fn test_deployment_copies() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("shards.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: worker
+kind: Deployment
+image: worker:v1.0.0
+copies: 3
+env:
+  QUEUE: jobs
+service:
+  port: 80
+"#,
+    )
+    .unwrap();
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 6);
+
+    let deployments: Vec<serde_yaml::Value> = rendered
+        .manifests
+        .iter()
+        .map(|manifest| serde_yaml::from_str::<serde_yaml::Value>(manifest).unwrap())
+        .filter(|value| value["kind"] == "Deployment")
+        .collect();
+    assert_eq!(deployments.len(), 3);
+
+    for (index, deployment) in deployments.iter().enumerate() {
+        let name = format!("worker-{}", index);
+        let index = index.to_string();
+        assert_eq!(deployment["metadata"]["name"].as_str(), Some(name.as_str()));
+
+        // Selectors are unique per copy and match the pod labels
+        let match_labels = &deployment["spec"]["selector"]["matchLabels"];
+        assert_eq!(match_labels["app"].as_str(), Some(name.as_str()));
+        assert_eq!(
+            match_labels["kamut.dev/shard-index"].as_str(),
+            Some(index.as_str())
+        );
+        assert_eq!(
+            &deployment["spec"]["template"]["metadata"]["labels"],
+            match_labels
+        );
+
+        let env = &deployment["spec"]["template"]["spec"]["containers"][0]["env"];
+        let shard_env = env
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .find(|var| var["name"] == "KAMUT_SHARD_INDEX")
+            .unwrap();
+        assert_eq!(shard_env["value"].as_str(), Some(index.as_str()));
+    }
+
+    // Copies must be at least 1
+    fs::write(
+        &file_path,
+        "name: worker\nkind: Deployment\nimage: worker:v1.0.0\ncopies: 0\n",
+    )
+    .unwrap();
+    assert!(render_file(&file_path, &options).is_err());
+}
+
+#[test]
+fn test_deployment_strategy() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    strategy:
+      max_surge: 1
+      max_unavailable: 25%
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains(
+        "strategy:\n    rollingUpdate:\n      maxSurge: 1\n      maxUnavailable: 25%\n    type: RollingUpdate"
+    ));
+
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    strategy:
+      type: Recreate
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains("strategy:\n    type: Recreate\n"));
+
+    // Without a strategy the API defaults apply
+    let config = KamutConfig {
+        strategy: None,
+        ..config
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("strategy:"));
+}
+
+#[test]
+fn test_deployment_strategy_rejects_invalid_settings() {
+    for (strategy, expected) in [
+        (
+            "type: Recreate\n      max_surge: 1",
+            "require type RollingUpdate, not Recreate",
+        ),
+        ("type: BlueGreen", "Invalid strategy type 'BlueGreen'"),
+        (
+            "max_unavailable: half",
+            "strategy.max_unavailable must be an integer or a percentage",
+        ),
+    ] {
+        let yaml = format!(
+            "
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    strategy:
+      {}
+    ",
+            strategy
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_deployment_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_deployment_keda_prometheus_trigger() {
+    let yaml = r#"
+    name: worker
+    kind: Deployment
+    namespace: jobs
+    image: worker:v1.0.0
+    replicas: 2
+    keda:
+      min_replicas: 0
+      max_replicas: 20
+      triggers:
+        - type: prometheus
+          metadata:
+            serverAddress: http://prometheus.monitoring:9090
+            query: sum(queue_depth{queue="jobs"})
+            threshold: "100"
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_keda_scaled_object(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    assert_eq!(value["apiVersion"], "keda.sh/v1alpha1");
+    assert_eq!(value["kind"], "ScaledObject");
+    assert_eq!(value["metadata"]["namespace"], "jobs");
+    assert_eq!(value["spec"]["scaleTargetRef"]["kind"], "Deployment");
+    assert_eq!(value["spec"]["scaleTargetRef"]["name"], "worker");
+    assert_eq!(value["spec"]["minReplicaCount"], 0);
+    assert_eq!(value["spec"]["maxReplicaCount"], 20);
+    let trigger = &value["spec"]["triggers"][0];
+    assert_eq!(trigger["type"], "prometheus");
+    assert_eq!(trigger["metadata"]["threshold"], "100");
+
+    // KEDA owns the replica count
+    let deployment = generate_deployment_manifest(&config).unwrap();
+    assert!(!deployment.contains("replicas:"));
+}
+
+#[test]
+fn test_deployment_keda_invalid() {
+    let config: KamutConfig =
+        serde_yaml::from_str("name: worker\nimage: worker:v1.0.0\nkeda:\n  triggers: []\n")
+            .unwrap();
+    let err = generate_keda_scaled_object(&config).unwrap_err();
+    assert!(err.to_string().contains("at least one trigger"));
+
+    let config: KamutConfig = serde_yaml::from_str(
+        "name: worker\nimage: worker:v1.0.0\nkeda:\n  triggers:\n    - type: cron\n      metadata:\n        timezone: UTC\n",
+    )
+    .unwrap();
+    let message = generate_keda_scaled_object(&config)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("cron trigger requires metadata.start"));
+
+    // The HorizontalPodAutoscaler cannot scale to zero
+    let config = KamutConfig {
+        name: "worker".to_string(),
+        autoscaling: Some(AutoscalingConfig {
+            min_replicas: Some(0),
+            max_replicas: 2,
+            target_cpu_utilization: None,
+        }),
+        ..Default::default()
+    };
+    let err = generate_hpa_manifest(&config).unwrap_err();
+    assert!(err.to_string().contains("use 'keda'"));
+}
+
+#[test]
+fn test_deployment_tolerations() {
+    let yaml = r#"
+    name: trainer
+    kind: Deployment
+    image: trainer:v1.0.0
+    tolerations:
+      - key: dedicated
+        value: gpu
+        effect: NoSchedule
+      - key: node.kubernetes.io/unreachable
+        operator: Exists
+        effect: NoExecute
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains(
+        "tolerations:\n      - effect: NoSchedule\n        key: dedicated\n        value: gpu\n      - effect: NoExecute\n        key: node.kubernetes.io/unreachable\n        operator: Exists\n"
+    ));
+
+    // Without tolerations the pod spec is unchanged
+    let config = KamutConfig {
+        tolerations: None,
+        ..config
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("tolerations:"));
+}
+
+#[test]
+fn test_deployment_tolerations_invalid() {
+    for (toleration, expected) in [
+        (
+            "operator: Exists\n        value: gpu",
+            "operator Exists must not set a value",
+        ),
+        (
+            "value: gpu",
+            "tolerations without a key require operator Exists",
+        ),
+        (
+            "key: dedicated\n        operator: In",
+            "Invalid toleration operator 'In'",
+        ),
+        (
+            "key: dedicated\n        effect: Never",
+            "Invalid toleration effect 'Never'",
+        ),
+    ] {
+        let yaml = format!(
+            "
+    name: trainer
+    kind: Deployment
+    image: trainer:v1.0.0
+    tolerations:
+      - {}
+    ",
+            toleration
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_deployment_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_deployment_native_sidecar_init_container() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    init_containers:
+      - name: migrate
+        image: api:v1.0.0
+        command: [/app/migrate]
+      - name: proxy
+        image: envoyproxy/envoy:v1.31.0
+        args: [-c, /etc/envoy/envoy.yaml]
+        restart_policy: Always
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(manifest.contains(
+        "initContainers:\n      - command:\n        - /app/migrate\n        image: api:v1.0.0\n        name: migrate\n      - args:\n        - -c\n        - /etc/envoy/envoy.yaml\n        image: envoyproxy/envoy:v1.31.0\n        name: proxy\n        restartPolicy: Always\n"
+    ));
+
+    // Without init containers the pod spec is unchanged
+    let config = KamutConfig {
+        init_containers: Vec::new(),
+        ..config
+    };
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("initContainers:"));
+}
+
+#[test]
+fn test_deployment_init_container_env_and_resources() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    init_containers:
+      - name: migrate
+        image: api:v1.0.0
+        command: [/app/migrate]
+        env:
+          DATABASE_URL: postgres://db:5432/api
+          MIGRATE_TIMEOUT: 60s
+        resources:
+          requests:
+            cpu: 100m
+            memory: 64Mi
+          limits:
+            memory: 128Mi
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    let init_container = &value["spec"]["template"]["spec"]["initContainers"][0];
+    assert_eq!(init_container["env"][0]["name"], "DATABASE_URL");
+    assert_eq!(init_container["env"][0]["value"], "postgres://db:5432/api");
+    assert_eq!(init_container["env"][1]["name"], "MIGRATE_TIMEOUT");
+    assert_eq!(init_container["resources"]["requests"]["cpu"], "100m");
+    assert_eq!(init_container["resources"]["limits"]["memory"], "128Mi");
+
+    // Resources go through the same validation as the main container's
+    let yaml = yaml.replace("memory: 128Mi", "memory: 128MB");
+    let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+    let err = generate_deployment_manifest(&config).unwrap_err();
+    assert!(err.to_string().contains("Invalid quantity '128MB'"), "{}", err);
+}
+
+#[test]
+fn test_deployment_init_container_invalid() {
+    for (init_container, expected) in [
+        (
+            "name: proxy\n        image: envoy:v1\n        restart_policy: OnFailure",
+            "only 'Always' (a native sidecar) is allowed",
+        ),
+        (
+            "name: api\n        image: envoy:v1",
+            "Duplicate container name 'api'",
+        ),
+        ("name: proxy", "require a name and an image"),
+    ] {
+        let yaml = format!(
+            "
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    init_containers:
+      - {}
+    ",
+            init_container
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_deployment_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_deployment_pre_stop_hook_and_grace_period() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    termination_grace_period_seconds: 60
+    lifecycle:
+      pre_stop:
+        exec: ["sleep", "10"]
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    let pod_spec = &value["spec"]["template"]["spec"];
+    assert_eq!(pod_spec["terminationGracePeriodSeconds"], 60);
+    let pre_stop = &pod_spec["containers"][0]["lifecycle"]["preStop"];
+    assert_eq!(pre_stop["exec"]["command"][0], "sleep");
+    assert_eq!(pre_stop["exec"]["command"][1], "10");
+    assert!(pre_stop["httpGet"].is_null());
+
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    lifecycle:
+      pre_stop:
+        http_get:
+          path: /drain
+          port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    let pod_spec = &value["spec"]["template"]["spec"];
+    assert!(pod_spec["terminationGracePeriodSeconds"].is_null());
+    let pre_stop = &pod_spec["containers"][0]["lifecycle"]["preStop"];
+    assert_eq!(pre_stop["httpGet"]["path"], "/drain");
+    assert_eq!(pre_stop["httpGet"]["port"], 8080);
+
+    // A hook needs exactly one handler
+    for (pre_stop, expected) in [
+        ("{}", "requires either exec or http_get"),
+        (
+            "{exec: [sleep, \"5\"], http_get: {path: /drain, port: 8080}}",
+            "sets both exec and http_get",
+        ),
+    ] {
+        let yaml = format!(
+            "
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    lifecycle:
+      pre_stop: {}
+    ",
+            pre_stop
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_deployment_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_deployment_service_account_name() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    service_account_name: api-runner
+    service:
+      port: 8080
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    assert_eq!(
+        value["spec"]["template"]["spec"]["serviceAccountName"],
+        "api-runner"
+    );
+
+    // The ServiceAccount is expected to exist; none is generated
+    let manifests = generate_deployment_manifests(&config).unwrap();
+    assert_eq!(manifests.len(), 2);
+    assert!(manifests
+        .iter()
+        .all(|manifest| !manifest.contains("kind: ServiceAccount")));
+
+    // Without the field the pods run as the namespace's default ServiceAccount
+    let config: KamutConfig =
+        serde_yaml::from_str("name: api\nkind: Deployment\nimage: api:v1.0.0").unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    assert!(!manifest.contains("serviceAccountName"));
+}
+
+#[test]
+fn test_deployment_sidecar_containers() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    containers:
+      - name: log-shipper
+        image: fluent-bit:3.0
+        args: [--config, /etc/fluent-bit/fluent-bit.yaml]
+        env:
+          LOG_PATH: /var/log/api
+        resources:
+          limits:
+            memory: 64Mi
+        ports:
+          - name: metrics
+            container_port: 2020
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_deployment_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+
+    // The container built from the top-level fields stays first
+    let containers = value["spec"]["template"]["spec"]["containers"]
+        .as_sequence()
+        .unwrap();
+    assert_eq!(containers.len(), 2);
+    assert_eq!(containers[0]["name"], "api");
+    assert_eq!(containers[0]["image"], "api:v1.0.0");
+
+    let sidecar = &containers[1];
+    assert_eq!(sidecar["name"], "log-shipper");
+    assert_eq!(sidecar["image"], "fluent-bit:3.0");
+    assert_eq!(sidecar["args"][0], "--config");
+    assert_eq!(sidecar["env"][0]["name"], "LOG_PATH");
+    assert_eq!(sidecar["resources"]["limits"]["memory"], "64Mi");
+    assert_eq!(sidecar["ports"][0]["containerPort"], 2020);
+    assert_eq!(sidecar["ports"][0]["protocol"], "TCP");
+    assert!(sidecar["command"].is_null());
+}
+
+#[test]
+fn test_deployment_sidecar_containers_invalid() {
+    for (extra, expected) in [
+        (
+            "containers:\n      - name: api\n        image: envoy:v1",
+            "Duplicate container name 'api'",
+        ),
+        (
+            "containers:\n      - name: proxy\n        image: envoy:v1\n    init_containers:\n      - name: proxy\n        image: envoy:v1",
+            "Duplicate container name 'proxy'",
+        ),
+        (
+            "containers:\n      - name: proxy",
+            "containers entries of Deployment api require a name and an image",
+        ),
+    ] {
+        let yaml = format!(
+            "
+    name: api
+    kind: Deployment
+    image: api:v1.0.0
+    {}
+    ",
+            extra
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_deployment_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
@@ -0,0 +1,60 @@
+use kamut::config::{generate_endpoints_manifest, render_file, ProcessOptions};
+use kamut::models::{EndpointsConfig, KamutConfig};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_endpoints_for_external_database() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("database.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: external-db
+kind: Endpoints
+namespace: apps
+endpoints:
+  addresses:
+    - 10.0.0.10
+    - 10.0.0.11
+  ports:
+    - name: postgres
+      port: 5432
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.contains("apiVersion: v1"));
+    assert!(manifest.contains("kind: Endpoints"));
+    assert!(manifest.contains("name: external-db"));
+    assert!(manifest.contains("namespace: apps"));
+    assert!(manifest.contains("- ip: 10.0.0.10"));
+    assert!(manifest.contains("- ip: 10.0.0.11"));
+    assert!(manifest.contains("name: postgres"));
+    assert!(manifest.contains("port: 5432"));
+    assert!(manifest.contains("protocol: TCP"));
+}
+
+#[test]
+fn test_endpoints_require_addresses() {
+    let config = KamutConfig {
+        name: "external-db".to_string(),
+        kind: Some("Endpoints".to_string()),
+        endpoints: Some(EndpointsConfig::default()),
+        ..Default::default()
+    };
+
+    let result = generate_endpoints_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("endpoints.addresses must list at least one address"));
+}
@@ -0,0 +1,76 @@
+use kamut::fetch::{fetch_url, is_url};
+use kamut::KamutError;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Serves one connection on a local port, replying with `response` after `delay`
+fn stub_server(response: &'static str, delay: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        // Read the whole request head so closing the stream doesn't reset it
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+        }
+        thread::sleep(delay);
+        let _ = stream.write_all(response.as_bytes());
+    });
+    format!("http://{}/app.kamut.yaml", address)
+}
+
+#[test]
+fn test_is_url() {
+    assert!(is_url("http://example.com/app.kamut.yaml"));
+    assert!(is_url("https://example.com/app.kamut.yaml"));
+    assert!(!is_url("examples/*.kamut.yaml"));
+    assert!(!is_url("-"));
+}
+
+#[test]
+fn test_fetch_url() {
+    let url = stub_server(
+        "HTTP/1.0 200 OK\r\nContent-Type: text/yaml\r\n\r\nname: app\nkind: Deployment\nimage: app:v1\n",
+        Duration::ZERO,
+    );
+
+    let contents = fetch_url(&url, Duration::from_secs(5)).unwrap();
+    assert_eq!(contents, "name: app\nkind: Deployment\nimage: app:v1\n");
+}
+
+#[test]
+fn test_fetch_url_http_error() {
+    let url = stub_server("HTTP/1.0 404 Not Found\r\n\r\nmissing", Duration::ZERO);
+
+    let err = fetch_url(&url, Duration::from_secs(5)).unwrap_err();
+    assert!(err.to_string().contains("HTTP status 404"), "{}", err);
+}
+
+#[test]
+fn test_fetch_url_times_out() {
+    let url = stub_server("HTTP/1.0 200 OK\r\n\r\nname: app\n", Duration::from_secs(5));
+
+    let started = Instant::now();
+    let err = fetch_url(&url, Duration::from_millis(300)).unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(3));
+
+    // The error is typed and names the URL
+    match err.downcast_ref::<KamutError>() {
+        Some(KamutError::FetchTimeout(timed_out_url, _)) => assert_eq!(timed_out_url, &url),
+        _ => panic!("expected a fetch timeout, got: {}", err),
+    }
+    assert!(err.to_string().contains(&format!("fetching {}", url)));
+}
+
+#[test]
+fn test_fetch_url_rejects_https() {
+    let err = fetch_url("https://example.com/app.kamut.yaml", Duration::from_secs(1)).unwrap_err();
+    assert!(err.to_string().contains("only http:// inputs"), "{}", err);
+}
@@ -0,0 +1,106 @@
+use kamut::config::{process_file, ProcessOptions, PROVENANCE_HEADER};
+use kamut::fmt::{format_file, format_manifest_str, FmtOutcome};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_fmt_normalizes_messy_manifest() {
+    let messy = r#"# Generated by kamut from app.kamut.yaml; do not edit
+---
+kind: Service
+spec:
+  type: ClusterIP
+  ports: [{port: 80, protocol: TCP, name: http}]
+  selector: {app: app1}
+apiVersion: v1
+metadata: {name: app1, labels: {app: app1}}
+---
+
+---
+metadata:
+    name: app1
+apiVersion: v1
+kind: ConfigMap
+data:
+    b: "2"
+    a: "1"
+"#;
+
+    let options = ProcessOptions {
+        trim: true,
+        kubectl_order: true,
+        ..Default::default()
+    };
+    let formatted = format_manifest_str(messy, &options).unwrap();
+    assert_eq!(
+        formatted,
+        r#"# Generated by kamut from app.kamut.yaml; do not edit
+apiVersion: v1
+kind: Service
+metadata:
+  labels:
+    app: app1
+  name: app1
+spec:
+  ports:
+  - name: http
+    port: 80
+  selector:
+    app: app1
+
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: app1
+data:
+  a: '1'
+  b: '2'
+"#
+    );
+
+    // Formatting is idempotent
+    assert_eq!(
+        format_manifest_str(&formatted, &options).unwrap(),
+        formatted
+    );
+}
+
+#[test]
+fn test_fmt_files() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Freshly generated files are already canonical
+    let source = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &source,
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\nservice:\n  port: 80\n",
+    )
+    .unwrap();
+    process_file(&source).unwrap();
+    let generated = temp_path.join("app.yaml");
+    assert!(fs::read_to_string(&generated)
+        .unwrap()
+        .starts_with(PROVENANCE_HEADER));
+    let options = ProcessOptions::default();
+    assert_eq!(
+        format_file(&generated, &options).unwrap(),
+        FmtOutcome::Unchanged
+    );
+
+    let trim = ProcessOptions {
+        trim: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        format_file(&generated, &trim).unwrap(),
+        FmtOutcome::Formatted
+    );
+    assert!(!fs::read_to_string(&generated)
+        .unwrap()
+        .contains("protocol: TCP"));
+
+    // Sources and hand-written manifests have no provenance header
+    assert_eq!(format_file(&source, &trim).unwrap(), FmtOutcome::Skipped);
+}
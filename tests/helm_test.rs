@@ -0,0 +1,49 @@
+use kamut::config::ProcessOptions;
+use kamut::helm::{export_helm_chart, ChartMetadata};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_export_helm_chart() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    let file_path = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: api
+kind: Deployment
+namespace: default
+image: api:v1.0.0
+service:
+  port: 80
+"#,
+    )
+    .unwrap();
+
+    let chart_dir = temp_path.join("charts").join("api");
+    let chart = ChartMetadata::new("api", "1.2.3");
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let templates = export_helm_chart(&[file_path], &chart_dir, &chart, &options).unwrap();
+
+    // Chart.yaml describes the chart
+    let chart_yaml = fs::read_to_string(chart_dir.join("Chart.yaml")).unwrap();
+    assert!(chart_yaml.contains("apiVersion: v2"));
+    assert!(chart_yaml.contains("name: api"));
+    assert!(chart_yaml.contains("version: 1.2.3"));
+
+    // Each resource gets its own template
+    assert_eq!(
+        templates,
+        [
+            chart_dir.join("templates").join("deployment-api.yaml"),
+            chart_dir.join("templates").join("service-api.yaml"),
+        ]
+    );
+    let deployment = fs::read_to_string(&templates[0]).unwrap();
+    assert!(deployment.contains("kind: Deployment"));
+    assert!(deployment.contains("image: api:v1.0.0"));
+}
@@ -0,0 +1,90 @@
+use kamut::config::{generate_ingress_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_ingress_paths_to_different_services() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("web.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: shop
+kind: Ingress
+namespace: apps
+ingress:
+  host: shop.example.com
+  class_name: nginx
+  paths:
+    - path: /api
+      service: shop-api
+      port: 8080
+    - path: /web
+      service: shop-web
+      port: 80
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let value: serde_yaml::Value = serde_yaml::from_str(&rendered.manifests[0]).unwrap();
+    assert_eq!(value["apiVersion"].as_str(), Some("networking.k8s.io/v1"));
+    assert_eq!(value["kind"].as_str(), Some("Ingress"));
+    assert_eq!(value["metadata"]["name"].as_str(), Some("shop"));
+    assert_eq!(value["metadata"]["namespace"].as_str(), Some("apps"));
+    assert_eq!(value["spec"]["ingressClassName"].as_str(), Some("nginx"));
+
+    // One host rule with a path per backend
+    let rules = value["spec"]["rules"].as_sequence().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["host"].as_str(), Some("shop.example.com"));
+    let backends: Vec<(&str, &str, i64)> = rules[0]["http"]["paths"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|path| {
+            let service = &path["backend"]["service"];
+            (
+                path["path"].as_str().unwrap(),
+                service["name"].as_str().unwrap(),
+                service["port"]["number"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        backends,
+        [("/api", "shop-api", 8080), ("/web", "shop-web", 80)]
+    );
+}
+
+#[test]
+fn test_ingress_paths_require_backend() {
+    let yaml = r#"
+    name: shop
+    kind: Ingress
+    ingress:
+      host: shop.example.com
+      paths:
+        - path: /api
+          service: shop-api
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let err = generate_ingress_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Ingress path shop.example.com/api of shop requires service and port"));
+
+    // The ingress field itself is required
+    let config = KamutConfig {
+        ingress: None,
+        ..config
+    };
+    assert!(config.validate_kind().is_err());
+}
@@ -1,7 +1,9 @@
-use kamut::config::{find_config_files, process_file};
+use kamut::config::{find_config_files, process_file, process_file_with_options, ProcessOptions};
+use serde::Deserialize;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
+use std::process::{Command, Stdio};
 use tempfile::tempdir;
 
 // This is an integration test that simulates the main function's behavior
@@ -202,3 +204,1020 @@ retention: 15d
     let doc_count = output_content.matches("---").count() + 1;
     assert_eq!(doc_count, 7);
 }
+
+// Test limiting generation to a single named document
+#[test]
+fn test_render_only_filter() {
+    // Create a temporary directory
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Create a kamut file with two documents
+    let file_path = temp_path.join("two-docs.kamut.yaml");
+    let mut file = File::create(&file_path).unwrap();
+    let content = r#"name: app1
+kind: Deployment
+image: app1:v1.0.0
+---
+name: app2
+kind: Deployment
+image: app2:v1.0.0
+"#;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+
+    // Process the file with only app2 selected
+    let options = ProcessOptions {
+        render_only: Some("app2".to_string()),
+        ..Default::default()
+    };
+    let processed = process_file_with_options(&file_path, &options).unwrap();
+    assert_eq!(processed.rendered_count, 1);
+
+    // Only the selected document should be in the output
+    let output_content = fs::read_to_string(temp_path.join("two-docs.yaml")).unwrap();
+    assert!(output_content.contains("name: app2"));
+    assert!(!output_content.contains("name: app1"));
+    assert!(!output_content.contains("---"));
+
+    // A name that matches no document fails the run with a clear error
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--render-only")
+        .arg("app3")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no matching document named app3"));
+}
+
+// Test that fields ignored by the document's kind warn, or fail under --strict
+#[test]
+fn test_ignored_field_strict_mode() {
+    // Create a temporary directory
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Create a Deployment with a Prometheus-only field
+    let file_path = temp_path.join("ignored.kamut.yaml");
+    let mut file = File::create(&file_path).unwrap();
+    let content = r#"name: app1
+kind: Deployment
+image: app1:v1.0.0
+retention: 30d
+"#;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+
+    // By default the field only produces a warning
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(file_path.to_str().unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Warning: field 'retention' is ignored for kind Deployment"));
+    assert!(temp_path.join("ignored.yaml").exists());
+
+    // Under --strict it is an error
+    let options = ProcessOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let result = process_file_with_options(&file_path, &options);
+    assert!(result.is_err());
+    let error = result.unwrap_err().to_string();
+    assert!(error.starts_with("field 'retention' is ignored for kind Deployment"));
+}
+
+// Test that kamut.defaults.yaml fills in unset fields, and --defaults replaces it
+#[test]
+fn test_defaults_file() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("kamut.defaults.yaml"),
+        "replicas: 3\nimage: app:v1.0.0\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("other-defaults.yaml"),
+        "replicas: 2\nimage: other:v1.0.0\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("apps.kamut.yaml"),
+        r#"name: app1
+kind: Deployment
+---
+name: app2
+kind: Deployment
+image: app2:v1.0.0
+replicas: 5
+"#,
+    )
+    .unwrap();
+    let pattern = format!("{}/*.kamut.yaml", temp_path.display());
+
+    // The defaults file is not a kamut file itself
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("validate")
+        .arg(&pattern)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(&pattern)
+        .arg("--stdout")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let documents: Vec<&str> = stdout.split("\n---\n").collect();
+    assert!(documents[0].contains("name: app1"));
+    assert!(documents[0].contains("replicas: 3"));
+    assert!(documents[0].contains("image: app:v1.0.0"));
+    // Explicit values win
+    assert!(documents[1].contains("name: app2"));
+    assert!(documents[1].contains("replicas: 5"));
+    assert!(documents[1].contains("image: app2:v1.0.0"));
+
+    // --defaults names the file to use instead
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(&pattern)
+        .arg("--defaults")
+        .arg(temp_path.join("other-defaults.yaml"))
+        .arg("--stdout")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("replicas: 2"));
+    assert!(stdout.contains("image: other:v1.0.0"));
+    assert!(!stdout.contains("replicas: 3"));
+}
+
+// Test that resources generated twice across files warn, or fail under --strict
+#[test]
+fn test_duplicate_resources_across_files() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    for file_name in ["a.kamut.yaml", "b.kamut.yaml"] {
+        fs::write(
+            temp_path.join(file_name),
+            "name: api\nkind: Deployment\nnamespace: apps\nimage: api:v1.0.0\n",
+        )
+        .unwrap();
+    }
+    let pattern = format!("{}/*.kamut.yaml", temp_path.display());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(&pattern)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!(
+        "Warning: Deployment apps/api is generated 2 times, from {}, {}",
+        temp_path.join("a.kamut.yaml").display(),
+        temp_path.join("b.kamut.yaml").display()
+    )));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(&pattern)
+        .arg("--strict")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: Deployment apps/api is generated 2 times"));
+    assert!(!stderr.contains("Error: Error:"));
+}
+
+// Test that a mistyped reference fails under --check-references --strict
+#[test]
+fn test_check_references_strict() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("web.kamut.yaml"),
+        r#"name: web-config
+kind: ConfigMap
+data:
+  LOG_LEVEL: info
+---
+name: web
+kind: Deployment
+image: web:v1.0.0
+env_from:
+  - config_map: web-cofnig
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--check-references")
+        .arg("--strict")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: Deployment/web references ConfigMap 'web-cofnig'"));
+    assert!(!stderr.contains("Error: Error:"));
+}
+
+// Test that a file-level namespace directive flows into documents without a namespace
+#[test]
+fn test_file_level_namespace_directive() {
+    // Create a temporary directory
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Create a kamut file with a namespace directive
+    let file_path = temp_path.join("directive.kamut.yaml");
+    let mut file = File::create(&file_path).unwrap();
+    let content = r#"# kamut: namespace=monitoring
+---
+name: app1
+kind: Deployment
+image: app1:v1.0.0
+---
+name: app2
+kind: Deployment
+namespace: apps
+image: app2:v1.0.0
+"#;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+
+    // Process the file
+    process_file(&file_path).unwrap();
+
+    // Split the output back into documents
+    let output_content = fs::read_to_string(temp_path.join("directive.yaml")).unwrap();
+    let documents: Vec<&str> = output_content.split("\n---\n").collect();
+    assert_eq!(documents.len(), 2);
+
+    // The document without a namespace inherits the file default
+    assert!(documents[0].contains("name: app1"));
+    assert!(documents[0].contains("namespace: monitoring"));
+
+    // A per-document namespace overrides the default
+    assert!(documents[1].contains("name: app2"));
+    assert!(documents[1].contains("namespace: apps"));
+    assert!(!documents[1].contains("namespace: monitoring"));
+}
+
+// Test that --default-namespace fills in the namespace of namespaced resources only
+#[test]
+fn test_default_namespace_flag() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("stack.kamut.yaml"),
+        r#"name: app1
+kind: Deployment
+image: app1:v1.0.0
+---
+name: prom
+kind: Prometheus
+image: prom/prometheus:v2.42.0
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--default-namespace")
+        .arg("apps")
+        .arg("--stdout")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let documents: Vec<&str> = stdout.split("\n---\n").collect();
+
+    // The Deployment picks up the default namespace
+    let deployment = documents
+        .iter()
+        .find(|doc| doc.contains("kind: Deployment") && doc.contains("name: app1"))
+        .unwrap();
+    assert!(deployment.contains("namespace: apps"));
+
+    // The ClusterRole stays cluster-scoped
+    let cluster_role = documents
+        .iter()
+        .find(|doc| doc.contains("kind: ClusterRole\n"))
+        .unwrap();
+    assert!(!cluster_role.contains("namespace:"));
+}
+
+// Test that --namespace replaces the namespace of every namespaced resource
+#[test]
+fn test_namespace_override_flag() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("stack.kamut.yaml"),
+        r#"# kamut: namespace=monitoring
+name: app1
+kind: Deployment
+image: app1:v1.0.0
+---
+name: app2
+kind: Deployment
+namespace: apps
+image: app2:v1.0.0
+---
+name: prom
+kind: Prometheus
+image: prom/prometheus:v2.42.0
+"#,
+    )
+    .unwrap();
+
+    let generate = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+            .arg("generate")
+            .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+            .args(extra)
+            .arg("--stdout")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let stdout = generate(&["--namespace", "staging", "--default-namespace", "apps"]);
+    let documents: Vec<&str> = stdout.split("\n---\n").collect();
+    for name in ["app1", "app2"] {
+        let deployment = documents
+            .iter()
+            .find(|doc| {
+                doc.contains("kind: Deployment") && doc.contains(&format!("name: {}", name))
+            })
+            .unwrap();
+        assert!(deployment.contains("namespace: staging"));
+    }
+    let cluster_role = documents
+        .iter()
+        .find(|doc| doc.contains("kind: ClusterRole\n"))
+        .unwrap();
+    assert!(!cluster_role.contains("namespace:"));
+
+    // Without the flag each document keeps its own namespace
+    let stdout = generate(&[]);
+    assert!(stdout.contains("namespace: monitoring"));
+    assert!(stdout.contains("namespace: apps"));
+    assert!(!stdout.contains("namespace: staging"));
+}
+
+// Test that Prometheus in raw mode produces a plain Deployment instead of the CR
+#[test]
+fn test_prometheus_raw_mode() {
+    // Create a temporary directory
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Create a Prometheus kamut file in raw mode
+    let file_path = temp_path.join("raw.kamut.yaml");
+    let mut file = File::create(&file_path).unwrap();
+    let content = r#"name: prom
+kind: Prometheus
+namespace: monitoring
+mode: raw
+image: prom/prometheus:v2.42.0
+retention: 7d
+storage:
+  size: 50Gi
+  className: standard
+"#;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+
+    // Process the file
+    process_file(&file_path).unwrap();
+
+    let output_content = fs::read_to_string(temp_path.join("raw.yaml")).unwrap();
+
+    // No operator custom resource
+    assert!(!output_content.contains("kind: Prometheus\n"));
+    assert!(!output_content.contains("monitoring.coreos.com"));
+
+    // ConfigMap with prometheus.yml, PVC, Deployment, and Service instead
+    assert!(output_content.contains("kind: ConfigMap"));
+    assert!(output_content.contains("prometheus.yml:"));
+    assert!(output_content.contains("kind: PersistentVolumeClaim"));
+    assert!(output_content.contains("storage: 50Gi"));
+    assert!(output_content.contains("kind: Deployment"));
+    assert!(output_content.contains("image: prom/prometheus:v2.42.0"));
+    assert!(output_content.contains("--storage.tsdb.retention.time=7d"));
+    assert!(output_content.contains("claimName: prometheus-prom-data"));
+    assert!(output_content.contains("kind: Service"));
+    assert!(output_content.contains("prometheus: prom"));
+}
+
+#[test]
+fn test_generate_to_stdout() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    let file_path = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--stdout")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // stdout holds only the manifests, which parse as a YAML stream
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("---\n"));
+    assert!(stdout.contains("kind: Deployment"));
+    assert!(!stdout.contains("Processing"));
+    for document in serde_yaml::Deserializer::from_str(&stdout) {
+        serde_yaml::Value::deserialize(document).unwrap();
+    }
+
+    // Progress output moves to stderr and no file is written
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Processing file"));
+    assert!(!stderr.contains("Saved manifest"));
+    assert!(!temp_path.join("app.yaml").exists());
+}
+
+#[test]
+fn test_output_dir() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    let file_path = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\n",
+    )
+    .unwrap();
+
+    // The output directory is created if it doesn't exist
+    let output_dir = temp_path.join("manifests").join("prod");
+    let options = ProcessOptions {
+        output_dir: Some(output_dir.clone()),
+        ..Default::default()
+    };
+    process_file_with_options(&file_path, &options).unwrap();
+
+    let output_content = fs::read_to_string(output_dir.join("app.yaml")).unwrap();
+    assert!(output_content.contains("name: app1"));
+    assert!(!temp_path.join("app.yaml").exists());
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("app.kamut.yaml"),
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\n",
+    )
+    .unwrap();
+
+    let output_dir = temp_path.join("manifests");
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--dry-run")
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected_path = output_dir.join("app.yaml");
+    assert!(stdout.contains(&format!("bytes to {}", expected_path.display())));
+    assert!(stdout.contains("Would write "));
+    assert!(!output_dir.exists());
+
+    // Generation still runs, so errors surface
+    fs::write(
+        temp_path.join("app.kamut.yaml"),
+        "name: app1\nkind: Deployment\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Error"));
+    assert!(!stdout.contains("Would write"));
+    assert!(!temp_path.join("app.yaml").exists());
+}
+
+#[test]
+fn test_when_condition_uses_env_var() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    let file_path = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: app1
+kind: Deployment
+image: app1:v1.0.0
+---
+name: app1-debug
+kind: Deployment
+image: app1-debug:v1.0.0
+when: env != prod
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .env("KAMUT_ENV", "prod")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // The debug Deployment is skipped in prod
+    let output_content = fs::read_to_string(temp_path.join("app.yaml")).unwrap();
+    assert!(output_content.contains("name: app1\n"));
+    assert!(!output_content.contains("app1-debug"));
+
+    // --var takes precedence over the environment
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .args(["--var", "env=dev"])
+        .env("KAMUT_ENV", "prod")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output_content = fs::read_to_string(temp_path.join("app.yaml")).unwrap();
+    assert!(output_content.contains("app1-debug"));
+}
+
+#[test]
+fn test_validate_reports_all_problems() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("apps.kamut.yaml"),
+        r#"name: app1
+kind: Deployment
+---
+name: app2
+kind: Deployment
+image: app2:v1.0.0
+---
+name: scrape
+kind: KubeScrapeConfig
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("broken.kamut.yaml"),
+        "name: broken\nkind: CronJob\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("validate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    // Every problem is reported, not just the first
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("document 1: 'image' field is required"));
+    assert!(stderr.contains("document 3: 'role' field is required"));
+    assert!(stderr.contains("document 1: unsupported kind 'CronJob'"));
+    assert!(stderr.contains("3 problem(s) found in 2 file(s)"));
+
+    // Nothing is written
+    assert!(!temp_path.join("apps.yaml").exists());
+    assert!(!temp_path.join("broken.yaml").exists());
+}
+
+#[test]
+fn test_require_match_exit_codes() {
+    let temp_dir = tempdir().unwrap();
+    let pattern = temp_dir.path().join("*.kamut.yaml");
+
+    // Without --require-match an empty match is only a notice
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(pattern.to_str().unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(pattern.to_str().unwrap())
+        .arg("--require-match")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no matching kamut files found for pattern"));
+
+    // Render errors use a different exit code
+    fs::write(
+        temp_dir.path().join("broken.kamut.yaml"),
+        "name: web\nkind: Deployment\nimage: web:v1\nresize_policy:\n  - resourceName: disk\n    restartPolicy: NotRequired\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg(pattern.to_str().unwrap())
+        .arg("--require-match")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_split_documents_name_template() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    let file_path = temp_path.join("app.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: web\nkind: Deployment\nnamespace: apps\nimage: web:v1.0.0\nservice:\n  port: 80\n---\nname: high\nkind: PriorityClass\nvalue: 1000\n",
+    )
+    .unwrap();
+
+    let output_dir = temp_path.join("manifests");
+    let options = ProcessOptions {
+        quiet: true,
+        output_dir: Some(output_dir.clone()),
+        split_documents: true,
+        name_template: Some("{namespace}/{kind}-{name}.yaml".to_string()),
+        ..Default::default()
+    };
+    process_file_with_options(&file_path, &options).unwrap();
+
+    let deployment = fs::read_to_string(output_dir.join("apps/deployment-web.yaml")).unwrap();
+    assert!(deployment.contains("kind: Deployment"));
+    assert!(!deployment.contains("---"));
+    let service = fs::read_to_string(output_dir.join("apps/service-web.yaml")).unwrap();
+    assert!(service.contains("kind: Service"));
+    assert!(output_dir.join("cluster/priorityclass-high.yaml").exists());
+    assert!(!output_dir.join("app.yaml").exists());
+
+    // Without a template each resource is named after the input file and the resource
+    let options = ProcessOptions {
+        quiet: true,
+        split_documents: true,
+        ..Default::default()
+    };
+    process_file_with_options(&file_path, &options).unwrap();
+    assert!(temp_path.join("app-deployment-web.yaml").exists());
+    assert!(temp_path.join("app-priorityclass-high.yaml").exists());
+}
+
+#[test]
+fn test_concurrent_writes_are_complete() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let output_dir = temp_path.join("manifests");
+
+    // Distinct outputs plus several sources racing for the same `shared.yaml`
+    let mut files = Vec::new();
+    for i in 0..32 {
+        let file_path = temp_path.join(format!("app{}.kamut.yaml", i));
+        fs::write(
+            &file_path,
+            format!(
+                "name: app{}\nkind: Deployment\nimage: app:v1.0.0\nreplicas: 2\n",
+                i
+            ),
+        )
+        .unwrap();
+        files.push(file_path);
+    }
+    for i in 0..8 {
+        let source_dir = temp_path.join(format!("source{}", i));
+        fs::create_dir(&source_dir).unwrap();
+        let file_path = source_dir.join("shared.kamut.yaml");
+        fs::write(
+            &file_path,
+            format!(
+                "name: shared{}\nkind: Deployment\nimage: shared:v1.0.0\n",
+                i
+            ),
+        )
+        .unwrap();
+        files.push(file_path);
+    }
+
+    let options = ProcessOptions {
+        quiet: true,
+        output_dir: Some(output_dir.clone()),
+        ..Default::default()
+    };
+    std::thread::scope(|scope| {
+        for chunk in files.chunks(4) {
+            let options = &options;
+            scope.spawn(move || {
+                for _ in 0..5 {
+                    for file_path in chunk {
+                        process_file_with_options(file_path, options).unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    for i in 0..32 {
+        let content = fs::read_to_string(output_dir.join(format!("app{}.yaml", i))).unwrap();
+        assert!(content.contains(&format!("name: app{}", i)));
+        assert!(content.contains("replicas: 2"));
+    }
+
+    // The raced file holds exactly one complete manifest
+    let shared = fs::read_to_string(output_dir.join("shared.yaml")).unwrap();
+    let deployment: serde_yaml::Value = serde_yaml::from_str(&shared).unwrap();
+    assert_eq!(deployment["kind"].as_str(), Some("Deployment"));
+    assert!(deployment["metadata"]["name"]
+        .as_str()
+        .unwrap()
+        .starts_with("shared"));
+
+    // No temporary files are left behind
+    let leftovers: Vec<_> = fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[test]
+fn test_validate_max_errors() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Files are processed in sorted order: a, b, c
+    fs::write(
+        temp_path.join("a.kamut.yaml"),
+        "name: a1\nkind: Deployment\n---\nname: a2\nkind: Deployment\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("b.kamut.yaml"),
+        "name: b\nkind: Deployment\n",
+    )
+    .unwrap();
+    fs::write(temp_path.join("c.kamut.yaml"), "name: c\nkind: CronJob\n").unwrap();
+
+    let validate = |max_errors: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_kamut"));
+        command
+            .arg("validate")
+            .arg(format!("{}/*.kamut.yaml", temp_path.display()));
+        if let Some(max_errors) = max_errors {
+            command.args(["--max-errors", max_errors]);
+        }
+        let output = command.output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    // Processing stops once the limit is reached, even in the middle of a file
+    let stderr = validate(Some("1"));
+    assert_eq!(stderr.matches("'image' field is required").count(), 1);
+    assert!(!stderr.contains("CronJob"));
+    assert!(stderr.contains("stopped after 1 problem(s) in 1 of 3 file(s)"));
+
+    let stderr = validate(Some("3"));
+    assert_eq!(stderr.matches("'image' field is required").count(), 3);
+    assert!(!stderr.contains("CronJob"));
+    assert!(stderr.contains("stopped after 3 problem(s) in 2 of 3 file(s)"));
+
+    // Without a limit every problem is reported
+    let stderr = validate(None);
+    assert!(stderr.contains("unsupported kind 'CronJob'"));
+    assert!(stderr.contains("4 problem(s) found in 3 file(s)"));
+
+    let stderr = validate(Some("10"));
+    assert!(stderr.contains("4 problem(s) found in 3 file(s)"));
+}
+
+#[test]
+fn test_kustomize_lists_generated_files() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("app.kamut.yaml"),
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("worker.kamut.yaml"),
+        "name: worker\nkind: Deployment\nimage: worker:v1.0.0\n",
+    )
+    .unwrap();
+
+    let output_dir = temp_path.join("manifests");
+    let generate = || {
+        Command::new(env!("CARGO_BIN_EXE_kamut"))
+            .arg("generate")
+            .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+            .arg("--kustomize")
+            .arg("--output-dir")
+            .arg(&output_dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = generate();
+    assert!(output.status.success());
+    let kustomization_path = output_dir.join("kustomization.yaml");
+    let kustomization = fs::read_to_string(&kustomization_path).unwrap();
+    assert!(kustomization.starts_with(kamut::kustomize::GENERATED_HEADER));
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&kustomization).unwrap();
+    assert_eq!(parsed["kind"], "Kustomization");
+    assert_eq!(
+        parsed["resources"],
+        serde_yaml::from_str::<serde_yaml::Value>("[app.yaml, worker.yaml]").unwrap()
+    );
+
+    // A kustomization written by kamut is regenerated
+    fs::remove_file(temp_path.join("worker.kamut.yaml")).unwrap();
+    let output = generate();
+    assert!(output.status.success());
+    let parsed: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&kustomization_path).unwrap()).unwrap();
+    assert_eq!(
+        parsed["resources"],
+        serde_yaml::from_str::<serde_yaml::Value>("[app.yaml]").unwrap()
+    );
+
+    // A hand-written one is kept, with a warning
+    let hand_written = "resources:\n  - app.yaml\n  - extra.yaml\n";
+    fs::write(&kustomization_path, hand_written).unwrap();
+    let output = generate();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("was not generated by kamut"));
+    assert_eq!(
+        fs::read_to_string(&kustomization_path).unwrap(),
+        hand_written
+    );
+}
+
+#[test]
+fn test_format_json() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join("app.kamut.yaml"),
+        "name: app1\nkind: Deployment\nimage: app1:v1.0.0\nservice:\n  port: 80\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!temp_path.join("app.yaml").exists());
+
+    let resources: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(temp_path.join("app.json")).unwrap()).unwrap();
+    let kinds: Vec<_> = resources
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|resource| resource["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, ["Deployment", "Service"]);
+
+    // Split documents are single JSON objects with a .json extension
+    let output_dir = temp_path.join("split");
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("generate")
+        .arg(format!("{}/*.kamut.yaml", temp_path.display()))
+        .arg("--format")
+        .arg("json")
+        .arg("--split-documents")
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let deployment: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output_dir.join("app-deployment-app1.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(deployment["metadata"]["name"], "app1");
+}
+
+#[test]
+fn test_generate_from_stdin() {
+    let temp_dir = tempdir().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .current_dir(temp_dir.path())
+        .arg("generate")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"name: app1\nkind: Deployment\nimage: app1:v1.0.0\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let manifest: serde_yaml::Value = serde_yaml::from_str(&stdout).unwrap();
+    assert_eq!(manifest["kind"], "Deployment");
+    assert_eq!(manifest["metadata"]["name"], "app1");
+
+    // Progress output stays on stderr and nothing is written
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Processing file: <stdin>"));
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+// Test that list and validate apply the .kamutrc files above each kamut file
+#[test]
+fn test_list_and_validate_use_kamutrc() {
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    fs::write(
+        temp_path.join(".kamutrc"),
+        "root: true\nnamespace: payments\nstrict: true\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("api.kamut.yaml"),
+        "name: api\nkind: Deployment\nimage: api:v1.0.0\n",
+    )
+    .unwrap();
+    let pattern = format!("{}/*.kamut.yaml", temp_path.display());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("list")
+        .arg(&pattern)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row = stdout
+        .lines()
+        .find(|line| line.starts_with("Deployment"))
+        .unwrap();
+    assert!(row.contains("payments"), "{}", stdout);
+
+    // strict: true makes fields ignored by the kind problems
+    fs::write(
+        temp_path.join("api.kamut.yaml"),
+        "name: api\nkind: Deployment\nimage: api:v1.0.0\nretention: 30d\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_kamut"))
+        .arg("validate")
+        .arg(&pattern)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("document 1: field 'retention' is ignored for kind Deployment"));
+}
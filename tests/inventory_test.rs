@@ -0,0 +1,107 @@
+use kamut::config::ProcessOptions;
+use kamut::inventory::{find_duplicates, format_resource_table, list_resources, ResourceEntry};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[test]
+fn test_list_resources_includes_generated_companions() {
+    // Create a temporary directory
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path();
+
+    // Create a kamut file with a Prometheus resource
+    let file_path = temp_path.join("prom.kamut.yaml");
+    let mut file = File::create(&file_path).unwrap();
+    let content = r#"name: prom
+kind: Prometheus
+namespace: monitoring
+image: prom/prometheus:v2.42.0
+"#;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let entries = list_resources(std::slice::from_ref(&file_path), &options).unwrap();
+
+    // The Prometheus CR plus its Service and RBAC resources
+    let kinds: Vec<&str> = entries.iter().map(|entry| entry.kind.as_str()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "Prometheus",
+            "Service",
+            "ServiceAccount",
+            "ClusterRole",
+            "ClusterRoleBinding"
+        ]
+    );
+    assert!(entries.contains(&ResourceEntry {
+        kind: "Service".to_string(),
+        namespace: Some("monitoring".to_string()),
+        name: "prometheus-prom".to_string(),
+        source: file_path.clone(),
+    }));
+
+    // Listing never writes output files
+    assert!(!temp_path.join("prom.yaml").exists());
+
+    // The table has a row per resource; cluster-scoped resources show no namespace
+    let table = format_resource_table(&entries);
+    let source = file_path.display().to_string();
+    assert!(table.starts_with("KIND"));
+    assert!(table.contains("Service             monitoring  prometheus-prom"));
+    assert!(table.contains("ServiceAccount      monitoring  prometheus-prom"));
+    assert!(table.contains(&format!(
+        "ClusterRole         -           prom-role          {}",
+        source
+    )));
+    assert!(table.contains("ClusterRoleBinding  -           prom-role-binding"));
+}
+
+#[test]
+fn test_format_resource_table_empty() {
+    let table = format_resource_table(&[]);
+    assert_eq!(table, "KIND  NAMESPACE  NAME  SOURCE\n");
+
+    let entries = vec![ResourceEntry {
+        kind: "Deployment".to_string(),
+        namespace: None,
+        name: "api".to_string(),
+        source: PathBuf::from("api.kamut.yaml"),
+    }];
+    let table = format_resource_table(&entries);
+    assert!(table.contains("Deployment  -          api   api.kamut.yaml\n"));
+}
+
+#[test]
+fn test_find_duplicates() {
+    let entry = |kind: &str, namespace: Option<&str>, name: &str, source: &str| ResourceEntry {
+        kind: kind.to_string(),
+        namespace: namespace.map(str::to_string),
+        name: name.to_string(),
+        source: PathBuf::from(source),
+    };
+    let entries = vec![
+        entry("Deployment", Some("apps"), "api", "a.kamut.yaml"),
+        entry("Deployment", Some("apps"), "api", "b.kamut.yaml"),
+        // Other namespaces and kinds don't collide
+        entry("Deployment", Some("staging"), "api", "b.kamut.yaml"),
+        entry("Service", Some("apps"), "api", "a.kamut.yaml"),
+        entry("ClusterRole", None, "prom-role", "a.kamut.yaml"),
+        entry("ClusterRole", None, "prom-role", "a.kamut.yaml"),
+    ];
+
+    assert_eq!(
+        find_duplicates(&entries),
+        vec![
+            "ClusterRole prom-role is generated 2 times, from a.kamut.yaml, a.kamut.yaml",
+            "Deployment apps/api is generated 2 times, from a.kamut.yaml, b.kamut.yaml",
+        ]
+    );
+    assert!(find_duplicates(&entries[2..5]).is_empty());
+}
@@ -0,0 +1,161 @@
+use kamut::config::{render_file, ProcessOptions};
+use kamut::kamutrc::{discover, options_for_file};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_nested_kamutrc_overrides_root() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let team_dir = root.join("teams").join("payments");
+    fs::create_dir_all(&team_dir).unwrap();
+
+    fs::write(
+        root.join(".kamutrc"),
+        "root: true\nnamespace: platform\nimage_registry: registry.internal\nvars:\n  env: prod\n",
+    )
+    .unwrap();
+    fs::write(
+        team_dir.join(".kamutrc"),
+        "namespace: payments\nvars:\n  tier: gold\n",
+    )
+    .unwrap();
+
+    let document = "name: api\nkind: Deployment\nimage: api:v1.0.0\n";
+    fs::write(root.join("api.kamut.yaml"), document).unwrap();
+    fs::write(team_dir.join("api.kamut.yaml"), document).unwrap();
+
+    // The root file only sees the root defaults
+    let rc = discover(&root.join("api.kamut.yaml")).unwrap();
+    assert_eq!(rc.namespace.as_deref(), Some("platform"));
+    assert_eq!(rc.vars.len(), 1);
+
+    // The nested file inherits the root defaults and overrides the namespace
+    let rc = discover(&team_dir.join("api.kamut.yaml")).unwrap();
+    assert_eq!(rc.namespace.as_deref(), Some("payments"));
+    assert_eq!(rc.image_registry.as_deref(), Some("registry.internal"));
+    assert_eq!(rc.vars.get("env").map(String::as_str), Some("prod"));
+    assert_eq!(rc.vars.get("tier").map(String::as_str), Some("gold"));
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    for (dir, namespace) in [(root, "platform"), (team_dir.as_path(), "payments")] {
+        let file_path = dir.join("api.kamut.yaml");
+        let file_options = options_for_file(&file_path, &options).unwrap();
+        let rendered = render_file(&file_path, &file_options).unwrap();
+        assert!(rendered.manifests[0].contains(&format!("namespace: {}", namespace)));
+        assert!(rendered.manifests[0].contains("image: registry.internal/api:v1.0.0"));
+    }
+}
+
+#[test]
+fn test_kamutrc_precedence() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::write(
+        root.join(".kamutrc"),
+        "root: true\nnamespace: platform\nimage_registry: registry.internal\nvars:\n  env: prod\n",
+    )
+    .unwrap();
+
+    // Command-line options win over .kamutrc defaults
+    let options = ProcessOptions {
+        image_registry: Some("mirror.example.com".to_string()),
+        vars: [("env".to_string(), "dev".to_string())]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let file_options = options_for_file(&root.join("api.kamut.yaml"), &options).unwrap();
+    assert_eq!(
+        file_options.image_registry.as_deref(),
+        Some("mirror.example.com")
+    );
+    assert_eq!(
+        file_options.vars.get("env").map(String::as_str),
+        Some("dev")
+    );
+
+    // Documents and file-level directives win over the .kamutrc namespace
+    let file_path = root.join("api.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: explicit
+kind: Deployment
+namespace: custom
+image: api:v1.0.0
+---
+# kamut: namespace=directive
+name: directive
+kind: Deployment
+image: api:v1.0.0
+"#,
+    )
+    .unwrap();
+    let file_options = options_for_file(
+        &file_path,
+        &ProcessOptions {
+            quiet: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let rendered = render_file(&file_path, &file_options).unwrap();
+    assert!(rendered.manifests[0].contains("namespace: custom"));
+    assert!(rendered.manifests[1].contains("namespace: directive"));
+
+    // Unknown keys are rejected
+    fs::write(root.join(".kamutrc"), "namespaces: typo\n").unwrap();
+    assert!(discover(&file_path).is_err());
+}
+
+#[test]
+fn test_kamutrc_defaults_file() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let shared_dir = root.join("shared");
+    let team_dir = root.join("teams").join("payments");
+    fs::create_dir_all(&shared_dir).unwrap();
+    fs::create_dir_all(&team_dir).unwrap();
+
+    fs::write(shared_dir.join("base.yaml"), "replicas: 2\n").unwrap();
+    fs::write(shared_dir.join("payments.yaml"), "replicas: 5\n").unwrap();
+    fs::write(
+        root.join(".kamutrc"),
+        "root: true\ndefaults: shared/base.yaml\n",
+    )
+    .unwrap();
+    // Paths are relative to the .kamutrc naming them
+    fs::write(
+        team_dir.join(".kamutrc"),
+        "defaults: ../../shared/payments.yaml\n",
+    )
+    .unwrap();
+
+    let document = "name: api\nkind: Deployment\nimage: api:v1.0.0\n";
+    fs::write(root.join("api.kamut.yaml"), document).unwrap();
+    fs::write(team_dir.join("api.kamut.yaml"), document).unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    for (dir, replicas) in [(root, 2), (team_dir.as_path(), 5)] {
+        let file_path = dir.join("api.kamut.yaml");
+        let file_options = options_for_file(&file_path, &options).unwrap();
+        let rendered = render_file(&file_path, &file_options).unwrap();
+        assert!(rendered.manifests[0].contains(&format!("replicas: {}", replicas)));
+    }
+
+    // --defaults wins over the .kamutrc file
+    let file_path = team_dir.join("api.kamut.yaml");
+    let options = ProcessOptions {
+        defaults_file: Some(shared_dir.join("base.yaml")),
+        ..options
+    };
+    let file_options = options_for_file(&file_path, &options).unwrap();
+    let rendered = render_file(&file_path, &file_options).unwrap();
+    assert!(rendered.manifests[0].contains("replicas: 2"));
+}
@@ -1,4 +1,5 @@
-use kamut::models::KamutConfig;
+use kamut::models::{ignored_fields, merge_defaults, KamutConfig, KamutDefaults};
+use kamut::KamutError;
 
 #[test]
 fn test_kamut_config_deserialization() {
@@ -96,8 +97,9 @@ fn test_prometheus_config_deserialization() {
     
     // Check storage
     let storage = config.storage.unwrap();
+    let storage = &storage.entries()[0];
     assert_eq!(storage.size, "200Gi");
-    assert_eq!(storage.class_name, "gp3-prom");
+    assert_eq!(storage.class_name, Some("gp3-prom".to_string()));
     
     // Check node selector
     let node_selector = config.node_selector.unwrap();
@@ -105,7 +107,7 @@ fn test_prometheus_config_deserialization() {
     
     // Check ingress
     let ingress = config.ingress.unwrap();
-    assert_eq!(ingress.host, "example.com");
+    assert_eq!(ingress.host.as_deref(), Some("example.com"));
 }
 
 #[test]
@@ -172,7 +174,7 @@ fn test_service_account_deserialization() {
     
     // Check service account
     let service_account = config.service_account.unwrap();
-    assert_eq!(service_account.create, true);
+    assert!(service_account.create);
     assert_eq!(service_account.cluster_role, Some(true));
     
     // Check annotations
@@ -181,4 +183,144 @@ fn test_service_account_deserialization() {
         annotations.get("eks.amazonaws.com/role-arn").unwrap(),
         "arn:aws:iam::123456789012:role/prometheus-role"
     );
-}
\ No newline at end of file
+}
+#[test]
+fn test_storage_list_deserialization() {
+    // Test storage declared as a list of named entries
+    let yaml = r#"
+    name: database
+    kind: StatefulSet
+    image: postgres:16
+    storage:
+      - name: data
+        size: 100Gi
+        className: gp3
+        mountPath: /var/lib/postgresql/data
+      - name: wal
+        size: 20Gi
+        className: io2
+        mountPath: /var/lib/postgresql/wal
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let storage = config.storage.unwrap();
+    let entries = storage.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, Some("data".to_string()));
+    assert_eq!(entries[0].size, "100Gi");
+    assert_eq!(
+        entries[0].mount_path,
+        Some("/var/lib/postgresql/data".to_string())
+    );
+    assert_eq!(entries[1].name, Some("wal".to_string()));
+    assert_eq!(entries[1].class_name, Some("io2".to_string()));
+}
+
+#[test]
+fn test_from_yaml_str() {
+    // Valid document parses and validates
+    let config =
+        KamutConfig::from_yaml_str("name: api\nkind: Deployment\nimage: api:v1.0.0").unwrap();
+    assert_eq!(config.name, "api");
+    assert_eq!(config.kind, Some("Deployment".to_string()));
+
+    // Missing kind is reported as a structured error
+    let err = KamutConfig::from_yaml_str("name: api\nimage: api:v1.0.0").unwrap_err();
+    assert!(matches!(err, KamutError::MissingField("kind")));
+
+    // Malformed YAML is reported as a parse error
+    let err = KamutConfig::from_yaml_str("name: [api").unwrap_err();
+    assert!(matches!(err, KamutError::Parse(_)));
+}
+
+#[test]
+fn test_ignored_fields_for_kind() {
+    // retention only applies to Prometheus
+    let yaml = "name: api\nkind: Deployment\nimage: api:v1.0.0\nretention: 30d\n";
+    assert_eq!(
+        ignored_fields("Deployment", yaml),
+        vec!["retention".to_string()]
+    );
+
+    // The same field is fine on Prometheus
+    let yaml = "name: prom\nkind: Prometheus\nimage: prom/prometheus\nretention: 30d\n";
+    assert!(ignored_fields("Prometheus", yaml).is_empty());
+
+    // Unsupported kinds are not checked
+    assert!(ignored_fields("Unknown", "name: x\nretention: 30d\n").is_empty());
+}
+
+#[test]
+fn test_validate_kind() {
+    let config = KamutConfig::from_yaml_str("name: api\nkind: Deployment").unwrap();
+    assert!(matches!(
+        config.validate_kind(),
+        Err(KamutError::MissingField("image"))
+    ));
+
+    let config =
+        KamutConfig::from_yaml_str("name: api\nkind: Deployment\nimage: api:v1.0.0").unwrap();
+    assert!(config.validate_kind().is_ok());
+
+    let config = KamutConfig::from_yaml_str("name: api\nkind: CronJob").unwrap();
+    let err = config.validate_kind().unwrap_err();
+    assert_eq!(err.to_string(), "unsupported kind 'CronJob'");
+}
+
+#[test]
+fn test_merge_defaults() {
+    let yaml = r#"
+    name: api
+    kind: Deployment
+    replicas: 5
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let defaults_yaml = r#"
+    name: ignored
+    kind: StatefulSet
+    replicas: 3
+    image: api:v1.0.0
+    image_pull_secrets: [registry]
+    "#;
+    let defaults: KamutConfig = serde_yaml::from_str(defaults_yaml).unwrap();
+
+    let merged = merge_defaults(config, &defaults);
+    // Explicit values win, unset ones are filled in
+    assert_eq!(merged.replicas, Some(5));
+    assert_eq!(merged.image, Some("api:v1.0.0".to_string()));
+    assert_eq!(merged.image_pull_secrets, vec!["registry"]);
+    // The name and kind always come from the document
+    assert_eq!(merged.name, "api");
+    assert_eq!(merged.kind, Some("Deployment".to_string()));
+}
+
+#[test]
+fn test_kamut_defaults_per_kind() {
+    let defaults = KamutDefaults::from_yaml_str(
+        r#"
+replicas: 3
+image_pull_secrets: [registry]
+kinds:
+  StatefulSet:
+    replicas: 1
+"#,
+    )
+    .unwrap();
+
+    let deployment: KamutConfig = serde_yaml::from_str("name: api\nkind: Deployment").unwrap();
+    let deployment = defaults.apply(deployment);
+    assert_eq!(deployment.replicas, Some(3));
+
+    let statefulset: KamutConfig = serde_yaml::from_str("name: db\nkind: StatefulSet").unwrap();
+    let statefulset = defaults.apply(statefulset);
+    assert_eq!(statefulset.replicas, Some(1));
+    assert_eq!(statefulset.image_pull_secrets, vec!["registry"]);
+
+    // An empty defaults file changes nothing
+    let empty = KamutDefaults::from_yaml_str("").unwrap();
+    let config: KamutConfig = serde_yaml::from_str("name: api\nkind: Deployment").unwrap();
+    assert_eq!(empty.apply(config).replicas, None);
+
+    assert!(KamutDefaults::from_yaml_str("kinds: [Deployment]").is_err());
+}
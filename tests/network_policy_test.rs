@@ -0,0 +1,133 @@
+use kamut::config::{generate_network_policy_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use kamut::KamutError;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_network_policy_ingress_and_egress() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("api.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: api
+kind: NetworkPolicy
+namespace: apps
+network_policy:
+  ingress:
+    - from:
+        - pod_selector:
+            app: web
+        - namespace_selector:
+            team: frontend
+          pod_selector:
+            role: gateway
+      ports:
+        - port: 8080
+        - port: metrics
+          protocol: TCP
+  egress:
+    - to:
+        - cidr: 10.0.0.0/8
+          except: [10.1.0.0/16]
+      ports:
+        - port: 5432
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let value: serde_yaml::Value = serde_yaml::from_str(&rendered.manifests[0]).unwrap();
+    assert_eq!(value["apiVersion"], "networking.k8s.io/v1");
+    assert_eq!(value["kind"], "NetworkPolicy");
+    assert_eq!(value["metadata"]["namespace"], "apps");
+
+    let spec = &value["spec"];
+    assert_eq!(spec["podSelector"]["matchLabels"]["app"], "api");
+    assert_eq!(spec["policyTypes"][0], "Ingress");
+    assert_eq!(spec["policyTypes"][1], "Egress");
+
+    let ingress = &spec["ingress"][0];
+    assert_eq!(
+        ingress["from"][0]["podSelector"]["matchLabels"]["app"],
+        "web"
+    );
+    assert!(ingress["from"][0]["namespaceSelector"].is_null());
+    assert_eq!(
+        ingress["from"][1]["namespaceSelector"]["matchLabels"]["team"],
+        "frontend"
+    );
+    assert_eq!(
+        ingress["from"][1]["podSelector"]["matchLabels"]["role"],
+        "gateway"
+    );
+    assert_eq!(ingress["ports"][0]["port"], 8080);
+    assert_eq!(ingress["ports"][0]["protocol"], "TCP");
+    assert_eq!(ingress["ports"][1]["port"], "metrics");
+
+    let egress = &spec["egress"][0];
+    assert_eq!(egress["to"][0]["ipBlock"]["cidr"], "10.0.0.0/8");
+    assert_eq!(egress["to"][0]["ipBlock"]["except"][0], "10.1.0.0/16");
+    assert_eq!(egress["ports"][0]["port"], 5432);
+}
+
+#[test]
+fn test_network_policy_default_deny() {
+    let yaml = r#"
+    name: api
+    kind: NetworkPolicy
+    network_policy:
+      ingress: []
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_network_policy_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let spec = &value["spec"];
+    assert_eq!(spec["policyTypes"].as_sequence().unwrap().len(), 1);
+    assert_eq!(spec["policyTypes"][0], "Ingress");
+    assert_eq!(spec["ingress"].as_sequence().unwrap().len(), 0);
+    // Egress stays unrestricted
+    assert!(spec["egress"].is_null());
+}
+
+#[test]
+fn test_network_policy_invalid() {
+    let config: KamutConfig = serde_yaml::from_str("name: api\nkind: NetworkPolicy").unwrap();
+    let err = config.validate_kind().unwrap_err();
+    assert!(matches!(err, KamutError::MissingField("network_policy")));
+
+    for (network_policy, expected) in [
+        ("{}", "must list ingress or egress rules"),
+        (
+            "{ingress: [{from: [{cidr: 10.0.0.0/8, pod_selector: {app: web}}]}]}",
+            "sets both cidr and a selector",
+        ),
+        (
+            "{egress: [{to: [{}]}]}",
+            "requires pod_selector, namespace_selector, or cidr",
+        ),
+        (
+            "{egress: [{to: [{except: [10.1.0.0/16]}]}]}",
+            "sets except without cidr",
+        ),
+        (
+            "{ingress: [{ports: [{port: 53, protocol: ICMP}]}]}",
+            "Invalid network_policy port protocol 'ICMP'",
+        ),
+    ] {
+        let yaml = format!(
+            "name: api\nkind: NetworkPolicy\nnetwork_policy: {}",
+            network_policy
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_network_policy_manifest(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
@@ -0,0 +1,52 @@
+use kamut::config::{generate_priority_class_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_priority_class_is_cluster_scoped() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("priority.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: high-priority
+kind: PriorityClass
+namespace: monitoring
+value: 1000000
+globalDefault: false
+description: For latency-sensitive workloads
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.contains("apiVersion: scheduling.k8s.io/v1"));
+    assert!(manifest.contains("kind: PriorityClass"));
+    assert!(manifest.contains("value: 1000000"));
+    assert!(manifest.contains("globalDefault: false"));
+    assert!(manifest.contains("description: For latency-sensitive workloads"));
+    assert!(!manifest.contains("namespace:"));
+}
+
+#[test]
+fn test_priority_class_requires_value() {
+    let config = KamutConfig {
+        name: "high-priority".to_string(),
+        kind: Some("PriorityClass".to_string()),
+        ..Default::default()
+    };
+
+    let result = generate_priority_class_manifest(&config);
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Value is required for PriorityClass"));
+}
@@ -0,0 +1,73 @@
+use kamut::config::{render_file, ProcessOptions};
+use kamut::references::{check_references, find_references};
+use std::fs;
+use tempfile::tempdir;
+
+// Renders a ConfigMap and a Deployment importing `config_map` into the same namespace
+fn render_with_reference(config_map: &str) -> Vec<String> {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("web.kamut.yaml");
+    fs::write(
+        &file_path,
+        format!(
+            r#"name: web-config
+kind: ConfigMap
+namespace: apps
+data:
+  LOG_LEVEL: info
+---
+name: web
+kind: Deployment
+namespace: apps
+image: web:v1.0.0
+env_from:
+  - config_map: {}
+  - secret: web-secrets
+"#,
+            config_map
+        ),
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    render_file(&file_path, &options).unwrap().manifests
+}
+
+#[test]
+fn test_reference_to_generated_config_map() {
+    let manifests = render_with_reference("web-config");
+
+    let references = find_references(&manifests[1]).unwrap();
+    let names: Vec<(&str, &str)> = references
+        .iter()
+        .map(|reference| (reference.kind, reference.name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("ConfigMap", "web-config"), ("Secret", "web-secrets")]
+    );
+    assert_eq!(references[0].namespace.as_deref(), Some("apps"));
+    assert_eq!(references[0].referrer, "Deployment/web");
+
+    // The ConfigMap resolves and the Secret is assumed to exist outside the run
+    assert!(check_references(&manifests).unwrap().is_empty());
+}
+
+#[test]
+fn test_reference_with_typo_warns() {
+    let manifests = render_with_reference("web-cofnig");
+
+    let warnings = check_references(&manifests).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Deployment/web references ConfigMap 'web-cofnig'"));
+    assert!(warnings[0].contains("did you mean 'web-config'?"));
+}
+
+#[test]
+fn test_external_reference_is_assumed_to_exist() {
+    let manifests = render_with_reference("shared-settings");
+    assert!(check_references(&manifests).unwrap().is_empty());
+}
@@ -0,0 +1,60 @@
+use kamut::config::{render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use kamut::registry::{is_registered, kind_generator, register_kind, registered_kinds};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_builtin_kinds_are_registered() {
+    let kinds = registered_kinds();
+    for kind in ["Deployment", "Prometheus", "KubeScrapeConfig", "Secret"] {
+        assert!(
+            kinds.contains(&kind.to_string()),
+            "{} is not registered",
+            kind
+        );
+    }
+
+    // Built-in kinds skip documents that lack a required field
+    let config = KamutConfig {
+        name: "api".to_string(),
+        kind: Some("Deployment".to_string()),
+        ..Default::default()
+    };
+    let generated = kind_generator("Deployment")
+        .unwrap()
+        .generate(&config)
+        .unwrap();
+    assert!(generated.is_empty());
+}
+
+#[test]
+fn test_register_custom_kind() {
+    register_kind("DummyGreeting", |config: &KamutConfig| {
+        Ok(vec![format!(
+            "apiVersion: example.com/v1\nkind: Greeting\nmetadata:\n  name: {}\nspec:\n  message: hello {}\n",
+            config.name,
+            config.namespace.as_deref().unwrap_or("world")
+        )])
+    });
+    assert!(is_registered("DummyGreeting"));
+
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("greeting.kamut.yaml");
+    fs::write(
+        &file_path,
+        "# kamut: namespace=team-a\nname: hi\nkind: DummyGreeting\n",
+    )
+    .unwrap();
+
+    let rendered = render_file(&file_path, &ProcessOptions::default()).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+    assert_eq!(
+        rendered.manifests[0],
+        "apiVersion: example.com/v1\nkind: Greeting\nmetadata:\n  name: hi\nspec:\n  message: hello team-a\n"
+    );
+
+    // Registered kinds pass validation
+    let config = KamutConfig::from_yaml_str("name: hi\nkind: DummyGreeting").unwrap();
+    assert!(config.validate_kind().is_ok());
+}
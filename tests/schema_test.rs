@@ -0,0 +1,115 @@
+use kamut::cluster::resource_ref;
+use kamut::schema::{is_custom_resource, schema_path};
+use std::path::Path;
+
+#[test]
+fn test_schema_path_layout() {
+    let dir = Path::new("schemas");
+
+    let deployment =
+        resource_ref("apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\n").unwrap();
+    assert!(!is_custom_resource(&deployment));
+    assert_eq!(
+        schema_path(dir, &deployment),
+        dir.join("deployment-apps-v1.json")
+    );
+
+    let service = resource_ref("apiVersion: v1\nkind: Service\nmetadata:\n  name: web\n").unwrap();
+    assert_eq!(schema_path(dir, &service), dir.join("service-v1.json"));
+
+    let ingress =
+        resource_ref("apiVersion: networking.k8s.io/v1\nkind: Ingress\nmetadata:\n  name: web\n")
+            .unwrap();
+    assert_eq!(
+        schema_path(dir, &ingress),
+        dir.join("ingress-networking-v1.json")
+    );
+
+    let prometheus = resource_ref(
+        "apiVersion: monitoring.coreos.com/v1\nkind: Prometheus\nmetadata:\n  name: prom\n",
+    )
+    .unwrap();
+    assert!(is_custom_resource(&prometheus));
+    assert_eq!(
+        schema_path(dir, &prometheus),
+        dir.join("monitoring.coreos.com").join("prometheus_v1.json")
+    );
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_validate_manifest_flags_invalid_image_pull_policy() {
+    use kamut::schema::validate_manifest;
+    use std::fs;
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    fs::write(
+        temp_dir.path().join("deployment-apps-v1.json"),
+        r#"{
+  "type": "object",
+  "properties": {
+    "spec": {
+      "type": "object",
+      "properties": {
+        "template": {
+          "type": "object",
+          "properties": {
+            "spec": {
+              "type": "object",
+              "properties": {
+                "containers": {
+                  "type": "array",
+                  "items": {
+                    "type": "object",
+                    "properties": {
+                      "imagePullPolicy": {
+                        "type": "string",
+                        "enum": ["Always", "IfNotPresent", "Never"]
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}"#,
+    )
+    .unwrap();
+
+    let manifest = "apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  template:
+    spec:
+      containers:
+      - name: web
+        image: web:v1.0.0
+        imagePullPolicy: Sometimes
+";
+    let violations = validate_manifest(manifest, temp_dir.path()).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0]
+        .starts_with("Deployment web: /spec/template/spec/containers/0/imagePullPolicy"));
+
+    let valid = manifest.replace("Sometimes", "IfNotPresent");
+    assert!(validate_manifest(&valid, temp_dir.path())
+        .unwrap()
+        .is_empty());
+
+    // Custom resources without a schema are skipped, built-in kinds are not
+    let prometheus =
+        "apiVersion: monitoring.coreos.com/v1\nkind: Prometheus\nmetadata:\n  name: prom\n";
+    assert!(validate_manifest(prometheus, temp_dir.path())
+        .unwrap()
+        .is_empty());
+    let service = "apiVersion: v1\nkind: Service\nmetadata:\n  name: web\n";
+    let violations = validate_manifest(service, temp_dir.path()).unwrap();
+    assert!(violations[0].contains("no schema found"));
+}
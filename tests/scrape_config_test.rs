@@ -0,0 +1,194 @@
+use kamut::config::{
+    generate_scrape_config_manifest, lint_scrape_config, process_file_with_options, render_file,
+    ProcessOptions, SCRAPE_CONFIG_API_VERSION,
+};
+use kamut::models::KamutConfig;
+use kube_custom_resources_rs::monitoring_coreos_com::v1alpha1::scrapeconfigs::ScrapeConfig;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_scrape_config_default_api_version() {
+    let config = KamutConfig {
+        name: "hello-sc".to_string(),
+        kind: Some("KubeScrapeConfig".to_string()),
+        role: Some("pod".to_string()),
+        ..Default::default()
+    };
+
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+
+    assert!(manifest.contains(&format!("apiVersion: {}", SCRAPE_CONFIG_API_VERSION)));
+    assert!(manifest.contains("apiVersion: monitoring.coreos.com/v1alpha1"));
+    assert!(manifest.contains("kind: ScrapeConfig"));
+}
+
+#[test]
+fn test_scrape_config_explicit_supported_api_version() {
+    let yaml = r#"
+    name: hello-sc
+    kind: KubeScrapeConfig
+    role: pod
+    apiVersion: monitoring.coreos.com/v1alpha1
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+
+    assert!(manifest.contains("apiVersion: monitoring.coreos.com/v1alpha1"));
+}
+
+#[test]
+fn test_scrape_config_unsupported_api_version() {
+    let config = KamutConfig {
+        name: "hello-sc".to_string(),
+        kind: Some("KubeScrapeConfig".to_string()),
+        role: Some("pod".to_string()),
+        api_version: Some("monitoring.coreos.com/v1".to_string()),
+        ..Default::default()
+    };
+
+    let result = generate_scrape_config_manifest(&config);
+
+    assert!(result.is_err());
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("Unsupported ScrapeConfig apiVersion 'monitoring.coreos.com/v1'"));
+    assert!(error.contains("monitoring.coreos.com/v1alpha1"));
+}
+
+#[test]
+fn test_scrape_config_api_version_override() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("sc.kamut.yaml");
+    fs::write(
+        &file_path,
+        "name: hello-sc\nkind: KubeScrapeConfig\nrole: pod\napi_version_override: monitoring.coreos.com/v1\n",
+    )
+    .unwrap();
+
+    let rendered = render_file(&file_path, &ProcessOptions::default()).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.starts_with("apiVersion: monitoring.coreos.com/v1\nkind: ScrapeConfig\n"));
+    assert!(!manifest.contains(SCRAPE_CONFIG_API_VERSION));
+    assert!(manifest.contains("kubernetesSDConfigs:"));
+
+    // The override is not checked against the supported versions, but must be group/version
+    for (extra, expected) in [
+        (
+            "api_version_override: v1",
+            "Invalid api_version_override 'v1'",
+        ),
+        (
+            "api_version_override: monitoring.coreos.com/v1\napiVersion: monitoring.coreos.com/v1alpha1",
+            "sets both 'apiVersion' and 'api_version_override'",
+        ),
+    ] {
+        fs::write(
+            &file_path,
+            format!("name: hello-sc\nkind: KubeScrapeConfig\nrole: pod\n{}\n", extra),
+        )
+        .unwrap();
+        let err = render_file(&file_path, &ProcessOptions::default()).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_scrape_config_custom_relabelings() {
+    let yaml = r#"
+    name: hello-sc
+    kind: KubeScrapeConfig
+    role: pod
+    relabelings:
+      - action: replace
+        sourceLabels: [__meta_kubernetes_pod_label_team]
+        targetLabel: team
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let relabelings = value["spec"]["relabelings"].as_sequence().unwrap();
+
+    // The custom relabeling comes after the default `pod` replace rule
+    let target_labels: Vec<_> = relabelings
+        .iter()
+        .filter_map(|relabeling| relabeling["targetLabel"].as_str())
+        .collect();
+    assert_eq!(target_labels, ["pod", "team"]);
+    assert_eq!(relabelings.last().unwrap()["targetLabel"], "team");
+    assert_eq!(relabelings.len(), 4);
+}
+
+#[test]
+fn test_scrape_config_replace_default_relabelings() {
+    let yaml = r#"
+    name: hello-sc
+    kind: KubeScrapeConfig
+    role: pod
+    replaceDefaultRelabelings: true
+    relabelings:
+      - action: keep
+        sourceLabels: [__meta_kubernetes_pod_label_scrape]
+        regex: "true"
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+    let value: serde_yaml::Value = serde_yaml::from_str(&manifest).unwrap();
+    let relabelings = value["spec"]["relabelings"].as_sequence().unwrap();
+
+    assert_eq!(relabelings.len(), 1);
+    assert_eq!(
+        relabelings[0]["sourceLabels"][0],
+        "__meta_kubernetes_pod_label_scrape"
+    );
+}
+
+#[test]
+fn test_scrape_config_without_keep_rule_warns() {
+    let yaml = r#"
+    name: everything
+    kind: KubeScrapeConfig
+    role: pod
+    replaceDefaultRelabelings: true
+    relabelings:
+      - action: replace
+        sourceLabels: [__meta_kubernetes_pod_name]
+        targetLabel: pod
+    "#;
+
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+    let scrape_config: ScrapeConfig = serde_yaml::from_str(&manifest).unwrap();
+    let warnings = lint_scrape_config(&scrape_config);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("ScrapeConfig everything has no 'keep' relabeling"));
+
+    // The generated defaults keep only the matching pods
+    let config: KamutConfig =
+        serde_yaml::from_str("name: hello-sc\nkind: KubeScrapeConfig\nrole: pod\n").unwrap();
+    let manifest = generate_scrape_config_manifest(&config).unwrap();
+    let scrape_config: ScrapeConfig = serde_yaml::from_str(&manifest).unwrap();
+    assert!(lint_scrape_config(&scrape_config).is_empty());
+
+    // A warning by default, an error under --strict
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("everything.kamut.yaml");
+    fs::write(&file_path, yaml).unwrap();
+    process_file_with_options(&file_path, &ProcessOptions::default()).unwrap();
+    assert!(temp_dir.path().join("everything.yaml").exists());
+
+    let options = ProcessOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = process_file_with_options(&file_path, &options).unwrap_err();
+    let err = err.to_string();
+    assert!(
+        err.starts_with("ScrapeConfig everything has no 'keep' relabeling"),
+        "{}",
+        err
+    );
+}
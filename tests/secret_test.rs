@@ -0,0 +1,94 @@
+use kamut::config::{generate_secret_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use std::collections::HashMap;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_secret_opaque_string_data() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("db.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: db-credentials
+kind: Secret
+namespace: apps
+string_data:
+  username: app
+  password: s3cret
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.contains("apiVersion: v1"));
+    assert!(manifest.contains("kind: Secret"));
+    assert!(manifest.contains("namespace: apps"));
+    assert!(manifest.contains("type: Opaque"));
+    // stringData is left as written
+    assert!(manifest.contains("stringData:\n  password: s3cret\n  username: app\n"));
+    assert!(!manifest.contains("\ndata:"));
+}
+
+#[test]
+fn test_secret_dockerconfigjson_data_is_base64_encoded() {
+    let yaml = r#"
+    name: registry-credentials
+    kind: Secret
+    secret_type: kubernetes.io/dockerconfigjson
+    data:
+      .dockerconfigjson: '{"auths":{}}'
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_secret_manifest(&config).unwrap();
+    assert!(manifest.contains("type: kubernetes.io/dockerconfigjson"));
+    assert!(manifest.contains("data:\n  .dockerconfigjson: eyJhdXRocyI6e319\n"));
+    assert!(!manifest.contains("stringData:"));
+}
+
+#[test]
+fn test_secret_invalid() {
+    let config = KamutConfig {
+        name: "registry-credentials".to_string(),
+        kind: Some("Secret".to_string()),
+        secret_type: Some("kubernetes.io/dockerconfigjson".to_string()),
+        string_data: Some(HashMap::from([(
+            "config.json".to_string(),
+            "{}".to_string(),
+        )])),
+        ..Default::default()
+    };
+    let err = generate_secret_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("requires a '.dockerconfigjson' key"));
+
+    let config = KamutConfig {
+        secret_type: None,
+        data: Some(HashMap::from([(
+            "config.json".to_string(),
+            "{}".to_string(),
+        )])),
+        ..config
+    };
+    let err = generate_secret_manifest(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("sets key 'config.json' in both string_data and data"));
+
+    let config = KamutConfig {
+        string_data: None,
+        data: None,
+        ..config
+    };
+    let err = generate_secret_manifest(&config).unwrap_err();
+    assert!(err.to_string().contains("string_data or data is required"));
+}
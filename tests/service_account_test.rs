@@ -1,5 +1,5 @@
 use kamut::config::generate_prometheus_service_account;
-use kamut::models::{KamutConfig, ServiceAccount};
+use kamut::models::{KamutConfig, PolicyRuleConfig, ServiceAccount};
 use std::collections::HashMap;
 
 #[test]
@@ -14,7 +14,11 @@ fn test_generate_prometheus_service_account() {
     let service_account = ServiceAccount {
         create: true,
         annotations: Some(annotations),
+        labels: None,
         cluster_role: Some(true),
+        cluster_role_rules: None,
+        scope: None,
+        rbac_scope: None,
     };
 
     // Create a test KamutConfig
@@ -80,7 +84,11 @@ fn test_service_account_without_cluster_role() {
     let service_account = ServiceAccount {
         create: true,
         annotations: None,
+        labels: None,
         cluster_role: Some(false),
+        cluster_role_rules: None,
+        scope: None,
+        rbac_scope: None,
     };
 
     // Create a test KamutConfig
@@ -119,7 +127,11 @@ fn test_service_account_not_created() {
     let service_account = ServiceAccount {
         create: false,
         annotations: None,
+        labels: None,
         cluster_role: None,
+        cluster_role_rules: None,
+        scope: None,
+        rbac_scope: None,
     };
 
     // Create a test KamutConfig
@@ -181,7 +193,11 @@ fn test_default_service_account_create() {
     let service_account = ServiceAccount {
         create: true, // Explicitly set to true
         annotations: None,
+        labels: None,
         cluster_role: Some(true), // Explicitly set to Some(true)
+        cluster_role_rules: None,
+        scope: None,
+        rbac_scope: None,
     };
 
     // Create a test KamutConfig
@@ -224,3 +240,183 @@ fn test_default_service_account_create() {
     assert!(crb_manifest.contains("kind: ClusterRoleBinding"));
     assert!(crb_manifest.contains("name: test-prometheus-role-binding"));
 }
+
+#[test]
+fn test_service_account_custom_cluster_role_rules() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    service_account:
+      cluster_role_rules:
+        - api_groups: [""]
+          resources: [pods, endpoints]
+          verbs: [get, list, watch]
+        - non_resource_urls: [/metrics]
+          verbs: [get]
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifests = generate_prometheus_service_account(&config).unwrap();
+    assert_eq!(manifests.len(), 3);
+
+    // The configured rules replace the built-in ones
+    let cr_manifest = &manifests[1];
+    assert!(cr_manifest.contains(
+        "rules:\n- apiGroups:\n  - ''\n  resources:\n  - pods\n  - endpoints\n  verbs:\n  - get\n  - list\n  - watch\n- nonResourceURLs:\n  - /metrics\n  verbs:\n  - get\n"
+    ));
+    assert!(!cr_manifest.contains("nodes/proxy"));
+    assert!(!cr_manifest.contains("ingresses"));
+
+    // Rules need verbs, one of resources and non_resource_urls, and API groups for resources
+    for (rule, expected) in [
+        (
+            "{resources: [pods], verbs: [get]}",
+            "cluster_role_rules[0] requires api_groups for its resources; use [\"\"] for the core group",
+        ),
+        ("resources: [pods]", "cluster_role_rules[0] requires verbs"),
+        (
+            "verbs: [get]",
+            "must set exactly one of resources and non_resource_urls",
+        ),
+    ] {
+        let yaml = format!(
+            "
+    name: test-prometheus
+    kind: Prometheus
+    image: prom/prometheus:v2.7.1
+    service_account:
+      cluster_role_rules:
+        - {}
+    ",
+            rule
+        );
+        let config: KamutConfig = serde_yaml::from_str(&yaml).unwrap();
+        let err = generate_prometheus_service_account(&config).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_service_account_namespace_scope() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: team-a
+    image: prom/prometheus:v2.7.1
+    service_account:
+      scope: namespace
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifests = generate_prometheus_service_account(&config).unwrap();
+    assert_eq!(manifests.len(), 3);
+
+    // A Role and RoleBinding in the Prometheus namespace replace the cluster-wide RBAC
+    let role_manifest = &manifests[1];
+    assert!(role_manifest.starts_with("apiVersion: rbac.authorization.k8s.io/v1\nkind: Role\n"));
+    assert!(role_manifest.contains("name: test-prometheus-role\n  namespace: team-a\n"));
+    assert!(role_manifest.contains("- pods"));
+    assert!(!role_manifest.contains("nodes"));
+    assert!(!role_manifest.contains("nonResourceURLs"));
+
+    let binding_manifest = &manifests[2];
+    assert!(binding_manifest.contains("kind: RoleBinding\n"));
+    assert!(binding_manifest.contains("name: test-prometheus-role-binding\n  namespace: team-a\n"));
+    assert!(binding_manifest.contains(
+        "roleRef:\n  apiGroup: rbac.authorization.k8s.io\n  kind: Role\n  name: test-prometheus-role\n"
+    ));
+
+    // The namespace is required, and Roles cannot grant non-resource URLs
+    let err = generate_prometheus_service_account(&KamutConfig {
+        namespace: None,
+        ..config.clone()
+    })
+    .unwrap_err();
+    assert!(err.to_string().contains("requires a namespace"), "{}", err);
+
+    let service_account = ServiceAccount {
+        cluster_role_rules: Some(vec![PolicyRuleConfig {
+            non_resource_urls: vec!["/metrics".to_string()],
+            verbs: vec!["get".to_string()],
+            ..Default::default()
+        }]),
+        ..config.service_account.clone().unwrap()
+    };
+    let err = generate_prometheus_service_account(&KamutConfig {
+        service_account: Some(service_account),
+        ..config
+    })
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("cannot grant non_resource_urls"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_service_account_rbac_scope() {
+    let yaml = r#"
+    name: test-prometheus
+    kind: Prometheus
+    namespace: monitoring
+    image: prom/prometheus:v2.7.1
+    service_account:
+      rbac_scope: [pod]
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifests = generate_prometheus_service_account(&config).unwrap();
+    assert_eq!(manifests.len(), 3);
+
+    // A pod-only scope reads pods and keeps /metrics, without node or ingress access
+    let cluster_role: serde_yaml::Value = serde_yaml::from_str(&manifests[1]).unwrap();
+    assert_eq!(cluster_role["kind"], "ClusterRole");
+    let rules = cluster_role["rules"].as_sequence().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["resources"].as_sequence().unwrap().len(), 1);
+    assert_eq!(rules[0]["resources"][0], "pods");
+    assert_eq!(rules[1]["nonResourceURLs"][0], "/metrics");
+    assert!(!manifests[1].contains("nodes"));
+    assert!(!manifests[1].contains("ingresses"));
+
+    // Endpoints discovery also reads the services and pods behind the endpoints
+    let mut service_account = config.service_account.clone().unwrap();
+    service_account.rbac_scope = Some(vec!["endpoints".to_string(), "ingress".to_string()]);
+    let manifests = generate_prometheus_service_account(&KamutConfig {
+        service_account: Some(service_account.clone()),
+        ..config.clone()
+    })
+    .unwrap();
+    assert!(manifests[1].contains("  - endpoints\n  - pods\n  - services\n"));
+    assert!(manifests[1].contains("networking.k8s.io"));
+    assert!(!manifests[1].contains("nodes"));
+
+    // Unknown roles, and the node role of a namespaced Role, are rejected
+    service_account.rbac_scope = Some(vec!["pods".to_string()]);
+    let err = generate_prometheus_service_account(&KamutConfig {
+        service_account: Some(service_account.clone()),
+        ..config.clone()
+    })
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Invalid service_account.rbac_scope role 'pods'"),
+        "{}",
+        err
+    );
+
+    service_account.rbac_scope = Some(vec!["node".to_string()]);
+    service_account.scope = Some("namespace".to_string());
+    let err = generate_prometheus_service_account(&KamutConfig {
+        service_account: Some(service_account),
+        ..config
+    })
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("requires scope 'cluster'"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,73 @@
+use kamut::config::{generate_service_monitor_manifest, render_file, ProcessOptions};
+use kamut::models::KamutConfig;
+use kamut::KamutError;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_service_monitor_named_port() {
+    let temp_dir = tempdir().unwrap();
+    let file_path = temp_dir.path().join("api.kamut.yaml");
+    fs::write(
+        &file_path,
+        r#"name: api
+kind: ServiceMonitor
+namespace: monitoring
+port: http-metrics
+metricsPath: /internal/metrics
+scrapeInterval: 15s  # every quarter minute
+scrapeTimeout: 5s # must stay below the interval
+labels:
+  team: payments
+"#,
+    )
+    .unwrap();
+
+    let options = ProcessOptions {
+        quiet: true,
+        ..Default::default()
+    };
+    let rendered = render_file(&file_path, &options).unwrap();
+    assert_eq!(rendered.manifests.len(), 1);
+
+    let manifest = &rendered.manifests[0];
+    assert!(manifest.contains("apiVersion: monitoring.coreos.com/v1"));
+    assert!(manifest.contains("kind: ServiceMonitor"));
+    assert!(manifest.contains("namespace: monitoring"));
+    assert!(manifest.contains("matchLabels:\n      team: payments"));
+    assert!(manifest.contains("port: http-metrics"));
+    assert!(manifest.contains("path: /internal/metrics"));
+    // Trailing comments are stripped from the durations
+    assert!(manifest.contains("interval: 15s\n"));
+    assert!(manifest.contains("scrapeTimeout: 5s\n"));
+    assert!(!manifest.contains("targetPort"));
+}
+
+#[test]
+fn test_service_monitor_numeric_port_defaults_selector() {
+    let yaml = r#"
+    name: worker
+    kind: ServiceMonitor
+    port: "8080"
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let manifest = generate_service_monitor_manifest(&config).unwrap();
+    assert!(manifest.contains("matchLabels:\n      app: worker"));
+    assert!(manifest.contains("targetPort: 8080"));
+    assert!(!manifest.contains("  port:"));
+    assert!(!manifest.contains("interval"));
+}
+
+#[test]
+fn test_service_monitor_requires_port() {
+    let yaml = r#"
+    name: worker
+    kind: ServiceMonitor
+    "#;
+    let config: KamutConfig = serde_yaml::from_str(yaml).unwrap();
+
+    let err = config.validate_kind().unwrap_err();
+    assert!(matches!(err, KamutError::MissingField("port")));
+    assert!(generate_service_monitor_manifest(&config).is_err());
+}
@@ -0,0 +1,118 @@
+use kamut::watch::{
+    changed_config_files, exec_command_line, run_exec, run_watch_loop, watch_root, WatchEvent,
+};
+use notify::RecursiveMode;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_watch_root() {
+    assert_eq!(
+        watch_root("*.kamut.yaml"),
+        (PathBuf::from("."), RecursiveMode::NonRecursive)
+    );
+    assert_eq!(
+        watch_root("deploy/*.kamut.yaml"),
+        (PathBuf::from("deploy"), RecursiveMode::NonRecursive)
+    );
+    assert_eq!(
+        watch_root("deploy/**/*.kamut.yaml"),
+        (PathBuf::from("deploy"), RecursiveMode::Recursive)
+    );
+    assert_eq!(
+        watch_root("deploy/*/app.kamut.yaml"),
+        (PathBuf::from("deploy"), RecursiveMode::Recursive)
+    );
+    // A plain file name watches its directory
+    assert_eq!(
+        watch_root("deploy/app.kamut.yaml"),
+        (PathBuf::from("deploy"), RecursiveMode::NonRecursive)
+    );
+}
+
+#[test]
+fn test_changed_config_files_ignores_generated_manifests() {
+    let temp_dir = tempdir().unwrap();
+    let config = temp_dir.path().join("app.kamut.yaml");
+    let manifest = temp_dir.path().join("app.yaml");
+    let other = temp_dir.path().join("other.kamut.yaml");
+    for path in [&config, &manifest, &other] {
+        fs::write(path, "").unwrap();
+    }
+
+    let pattern = format!("{}/*.kamut.yaml", temp_dir.path().display());
+    let changed = vec![
+        manifest.clone(),
+        config.clone(),
+        config.clone(),
+        temp_dir.path().join("deleted.kamut.yaml"),
+    ];
+    let files = changed_config_files(&pattern, &changed).unwrap();
+    assert_eq!(files, vec![config]);
+}
+
+#[test]
+fn test_watch_loop_keeps_watching_after_errors() {
+    let temp_dir = tempdir().unwrap();
+    let broken = temp_dir.path().join("broken.kamut.yaml");
+    let fixed = temp_dir.path().join("fixed.kamut.yaml");
+    fs::write(&broken, "").unwrap();
+    fs::write(&fixed, "").unwrap();
+    let pattern = format!("{}/*.kamut.yaml", temp_dir.path().display());
+
+    let (sender, events) = channel();
+    let (first, second) = (broken.clone(), fixed.clone());
+    let editor = thread::spawn(move || {
+        // A burst of edits is handled once, after the quiet period
+        for path in [&first, &second, &first] {
+            sender
+                .send(WatchEvent::Changed(vec![path.clone()]))
+                .unwrap();
+        }
+        thread::sleep(Duration::from_millis(200));
+        sender.send(WatchEvent::Stop).unwrap();
+        // Events after Stop are not handled
+        let _ = sender.send(WatchEvent::Changed(vec![second]));
+    });
+
+    let mut handled = Vec::new();
+    run_watch_loop(&pattern, events, Duration::from_millis(10), |file_path| {
+        handled.push(file_path.to_path_buf());
+        if file_path == broken {
+            return Err(anyhow::anyhow!("invalid kamut file"));
+        }
+        Ok(())
+    })
+    .unwrap();
+    editor.join().unwrap();
+
+    assert_eq!(handled, vec![broken, fixed]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_runs_with_output_path() {
+    let temp_dir = tempdir().unwrap();
+    let output = temp_dir.path().join("my app.yaml");
+
+    assert_eq!(
+        exec_command_line("kubectl apply -f {output}", &output),
+        format!("kubectl apply -f '{}'", output.display())
+    );
+
+    let result = run_exec("echo applied {output}", &output).unwrap();
+    assert!(result.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout),
+        format!("applied {}\n", output.display())
+    );
+
+    // A failing command is reported through its status, not as an error
+    let result = run_exec("echo failed {output} >&2; exit 3", &output).unwrap();
+    assert_eq!(result.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&result.stderr).contains("failed"));
+}